@@ -0,0 +1,242 @@
+//! The ways of computing `F(n)`, from the obvious to the fast.
+//!
+//! The linear loop takes `n` additions. The other two take about `log2(n)`
+//! steps of a few multiplications each, which matters once `n` is in the
+//! thousands: the numbers then have hundreds of limbs, where Karatsuba does
+//! better than the schoolbook. Fast doubling needs fewer multiplications per
+//! step than squaring the matrix, so it's the default.
+
+use crate::biguint::BigUint;
+use crate::sequence::Fibonacci;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// Adds up the sequence from the start.
+    Linear,
+    /// `F(2k)` and `F(2k + 1)` from `F(k)` and `F(k + 1)`.
+    #[default]
+    Doubling,
+    /// Raises `[[1, 1], [1, 0]]` to the `n`-th power.
+    Matrix,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 3] = [Algorithm::Linear, Algorithm::Doubling, Algorithm::Matrix];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Linear => "linear",
+            Algorithm::Doubling => "doubling",
+            Algorithm::Matrix => "matrix",
+        }
+    }
+
+    /// `F(n)`, computed this way.
+    pub fn compute(self, n: u64) -> BigUint {
+        match self {
+            Algorithm::Linear => linear(n),
+            Algorithm::Doubling => fast_doubling(n),
+            Algorithm::Matrix => matrix_power(n),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = UnknownAlgorithm;
+
+    fn from_str(s: &str) -> Result<Algorithm, UnknownAlgorithm> {
+        Algorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownAlgorithm(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownAlgorithm(pub String);
+
+impl fmt::Display for UnknownAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown algorithm `{}`, expected linear, doubling or matrix",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownAlgorithm {}
+
+/// `F(n)` after `n` additions.
+pub fn linear(n: u64) -> BigUint {
+    let mut terms = Fibonacci::<BigUint>::new();
+    for _ in 0..n {
+        terms.next();
+    }
+    terms.next().expect("big integers never run out of room")
+}
+
+/// `F(n)` by fast doubling, going through the bits of `n` from the top with
+///
+/// ```text
+/// F(2k)     = F(k) (2 F(k + 1) - F(k))
+/// F(2k + 1) = F(k)^2 + F(k + 1)^2
+/// ```
+pub fn fast_doubling(n: u64) -> BigUint {
+    fast_doubling_pair(n).0
+}
+
+/// `(F(n), F(n + 1))` by fast doubling.
+pub fn fast_doubling_pair(n: u64) -> (BigUint, BigUint) {
+    // (F(k), F(k + 1)) for k the bits of n seen so far
+    let mut current = BigUint::zero();
+    let mut next = BigUint::one();
+
+    for bit in (0..u64::BITS - n.leading_zeros()).rev() {
+        let doubled = &current * &(&(&next + &next) - &current);
+        let doubled_next = &(&current * &current) + &(&next * &next);
+        if n >> bit & 1 == 1 {
+            current = doubled_next;
+            next = &doubled + &current;
+        } else {
+            current = doubled;
+            next = doubled_next;
+        }
+    }
+    (current, next)
+}
+
+/// `F(n)` as a corner of `[[1, 1], [1, 0]]^n`, which is
+/// `[[F(n + 1), F(n)], [F(n), F(n - 1)]]`, squaring and multiplying by the
+/// bits of `n`.
+pub fn matrix_power(n: u64) -> BigUint {
+    let mut power = Symmetric::identity();
+    for bit in (0..u64::BITS - n.leading_zeros()).rev() {
+        power = power.times(&power);
+        if n >> bit & 1 == 1 {
+            power = power.times(&Symmetric::step());
+        }
+    }
+    power.corner
+}
+
+/// A 2x2 matrix `[[diagonal, corner], [corner, last]]`; the powers of
+/// `[[1, 1], [1, 0]]` all have this shape, which saves computing the second
+/// corner.
+struct Symmetric {
+    diagonal: BigUint,
+    corner: BigUint,
+    last: BigUint,
+}
+
+impl Symmetric {
+    fn identity() -> Symmetric {
+        Symmetric {
+            diagonal: BigUint::one(),
+            corner: BigUint::zero(),
+            last: BigUint::one(),
+        }
+    }
+
+    fn step() -> Symmetric {
+        Symmetric {
+            diagonal: BigUint::one(),
+            corner: BigUint::one(),
+            last: BigUint::zero(),
+        }
+    }
+
+    /// The product with `other`, which is only symmetric again because both
+    /// are powers of the same matrix.
+    fn times(&self, other: &Symmetric) -> Symmetric {
+        Symmetric {
+            diagonal: &(&self.diagonal * &other.diagonal) + &(&self.corner * &other.corner),
+            corner: &(&self.diagonal * &other.corner) + &(&self.corner * &other.last),
+            last: &(&self.corner * &other.corner) + &(&self.last * &other.last),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_the_sequence() {
+        for algorithm in Algorithm::ALL {
+            let first: Vec<String> = (0..12).map(|n| algorithm.compute(n).to_string()).collect();
+            assert_eq!(
+                first,
+                ["0", "1", "1", "2", "3", "5", "8", "13", "21", "34", "55", "89"],
+                "{algorithm}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_algorithm_agrees_up_to_2000() {
+        for (n, term) in (0..=2000).zip(Fibonacci::<BigUint>::new()) {
+            assert_eq!(linear(n), term, "linear F({n})");
+            assert_eq!(fast_doubling(n), term, "doubling F({n})");
+            assert_eq!(matrix_power(n), term, "matrix F({n})");
+        }
+    }
+
+    #[test]
+    fn goes_past_the_machine_types() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.compute(47), BigUint::from(2_971_215_073u32));
+            assert_eq!(algorithm.compute(48), BigUint::from(4_807_526_976u64));
+            assert_eq!(
+                algorithm.compute(93),
+                BigUint::from(12_200_160_415_121_876_738u64)
+            );
+            assert_eq!(
+                algorithm.compute(186),
+                BigUint::from(332_825_110_087_067_562_321_196_029_789_634_457_848u128)
+            );
+        }
+    }
+
+    #[test]
+    fn finds_large_numbers_exactly() {
+        let large = fast_doubling(10_000);
+        assert_eq!(large, linear(10_000));
+        assert_eq!(large, matrix_power(10_000));
+
+        let digits = large.to_string();
+        assert_eq!(digits.len(), 2090);
+        assert!(digits.starts_with("3364476487643178326662161200510754331030"));
+        assert!(digits.ends_with("66073310059947366875"));
+    }
+
+    #[test]
+    fn agrees_far_out() {
+        // large enough for Karatsuba to take over the multiplications
+        for n in [65_536, 100_001] {
+            assert_eq!(fast_doubling(n), matrix_power(n), "F({n})");
+        }
+        assert_eq!(fast_doubling(100_001), linear(100_001));
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!("Matrix".parse(), Ok(Algorithm::Matrix));
+        assert_eq!(Algorithm::default(), Algorithm::Doubling);
+        assert_eq!(
+            "binet".parse::<Algorithm>(),
+            Err(UnknownAlgorithm("binet".to_string()))
+        );
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.name().parse(), Ok(algorithm));
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Unsigned integers of any size, with nothing but std.
+//!
+//! A number is a vector of base 2^32 limbs, least significant first and with
+//! no zero limbs at the top, so every number has exactly one representation
+//! and zero is the empty vector. Products of long numbers split in halves with
+//! Karatsuba's trick, three half-size products instead of four, which starts
+//! paying off above a few dozen limbs.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+
+/// Operands shorter than this many limbs are multiplied the schoolbook way.
+pub const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Formatting works in chunks of this many decimal digits, the most that fit a limb.
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 9;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn one() -> BigUint {
+        BigUint { limbs: vec![1] }
+    }
+
+    /// The number with these limbs, least significant first.
+    pub fn from_limbs(limbs: Vec<u32>) -> BigUint {
+        let mut number = BigUint { limbs };
+        number.normalize();
+        number
+    }
+
+    pub fn limbs(&self) -> &[u32] {
+        &self.limbs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// How many bits it takes to write the number, 0 for zero.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 32 - u64::from(top.leading_zeros()),
+            None => 0,
+        }
+    }
+
+    /// The product with `other` the schoolbook way, whatever the lengths;
+    /// `*` switches to Karatsuba for long operands.
+    pub fn mul_schoolbook(&self, other: &BigUint) -> BigUint {
+        BigUint::from_limbs(schoolbook(&self.limbs, &other.limbs))
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u32> for BigUint {
+    fn from(n: u32) -> BigUint {
+        BigUint::from_limbs(vec![n])
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> BigUint {
+        BigUint::from_limbs(vec![n as u32, (n >> 32) as u32])
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(n: u128) -> BigUint {
+        BigUint::from_limbs((0..4).map(|limb| (n >> (32 * limb)) as u32).collect())
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        // without leading zeros, the longer number is the larger one
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, other: &BigUint) {
+        add_shifted(&mut self.limbs, &other.limbs, 0);
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut sum = self.clone();
+        sum += other;
+        sum
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, other: BigUint) -> BigUint {
+        self += &other;
+        self
+    }
+}
+
+impl SubAssign<&BigUint> for BigUint {
+    /// Panics if `other` is the larger number, as the machine types do in
+    /// debug builds.
+    fn sub_assign(&mut self, other: &BigUint) {
+        assert!(*self >= *other, "attempt to subtract with overflow");
+        subtract_in_place(&mut self.limbs, &other.limbs);
+        self.normalize();
+    }
+}
+
+impl Sub<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: &BigUint) -> BigUint {
+        let mut difference = self.clone();
+        difference -= other;
+        difference
+    }
+}
+
+impl Sub for BigUint {
+    type Output = BigUint;
+
+    fn sub(mut self, other: BigUint) -> BigUint {
+        self -= &other;
+        self
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        BigUint::from_limbs(multiply(&self.limbs, &other.limbs))
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: BigUint) -> BigUint {
+        &self * &other
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // peel off nine digits at a time, least significant first
+        let mut rest = self.limbs.clone();
+        let mut chunks = Vec::new();
+        while !rest.is_empty() {
+            chunks.push(divide_in_place(&mut rest, DECIMAL_CHUNK));
+        }
+
+        let mut digits = match chunks.pop() {
+            Some(top) => top.to_string(),
+            None => "0".to_string(),
+        };
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{chunk:0width$}", width = DECIMAL_CHUNK_DIGITS));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+/// Divides `limbs` by `divisor` in place, dropping zero limbs from the top,
+/// and returns the remainder.
+fn divide_in_place(limbs: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for limb in limbs.iter_mut().rev() {
+        let current = (remainder << 32) | u64::from(*limb);
+        *limb = (current / u64::from(divisor)) as u32;
+        remainder = current % u64::from(divisor);
+    }
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+    remainder as u32
+}
+
+/// Adds `addend` times 2^(32 `shift`) to `sum`, growing it as needed.
+fn add_shifted(sum: &mut Vec<u32>, addend: &[u32], shift: usize) {
+    if sum.len() < shift + addend.len() {
+        sum.resize(shift + addend.len(), 0);
+    }
+
+    let mut carry = 0u64;
+    let mut index = shift;
+    for &limb in addend {
+        let total = u64::from(sum[index]) + u64::from(limb) + carry;
+        sum[index] = total as u32;
+        carry = total >> 32;
+        index += 1;
+    }
+    while carry > 0 {
+        if index == sum.len() {
+            sum.push(0);
+        }
+        let total = u64::from(sum[index]) + carry;
+        sum[index] = total as u32;
+        carry = total >> 32;
+        index += 1;
+    }
+}
+
+/// Subtracts `subtrahend` from `difference`, which must be at least as large.
+fn subtract_in_place(difference: &mut [u32], subtrahend: &[u32]) {
+    let mut borrow = 0i64;
+    for (index, limb) in difference.iter_mut().enumerate() {
+        if index >= subtrahend.len() && borrow == 0 {
+            break;
+        }
+        let taken = i64::from(subtrahend.get(index).copied().unwrap_or(0)) + borrow;
+        let total = i64::from(*limb) - taken;
+        *limb = total.rem_euclid(1 << 32) as u32;
+        borrow = i64::from(total < 0);
+    }
+    debug_assert_eq!(borrow, 0, "subtracted a larger number");
+}
+
+fn schoolbook(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            // at most (2^32 - 1)^2 + 2 (2^32 - 1), which still fits a u64
+            let total = u64::from(x) * u64::from(y) + u64::from(product[i + j]) + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+fn multiply(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.len().min(b.len()) < KARATSUBA_THRESHOLD {
+        return schoolbook(a, b);
+    }
+
+    // a = a1 2^(32 half) + a0 and the same for b, so that
+    // a b = z2 2^(64 half) + z1 2^(32 half) + z0 with z1 = (a0 + a1)(b0 + b1) - z0 - z2
+    let half = a.len().max(b.len()) / 2;
+    let (a0, a1) = a.split_at(half.min(a.len()));
+    let (b0, b1) = b.split_at(half.min(b.len()));
+
+    let z0 = multiply(a0, b0);
+    let z2 = multiply(a1, b1);
+    let mut a_sum = a0.to_vec();
+    add_shifted(&mut a_sum, a1, 0);
+    let mut b_sum = b0.to_vec();
+    add_shifted(&mut b_sum, b1, 0);
+    let mut z1 = multiply(&a_sum, &b_sum);
+    subtract_in_place(&mut z1, &z0);
+    subtract_in_place(&mut z1, &z2);
+
+    let mut product = z0;
+    add_shifted(&mut product, &z1, half);
+    add_shifted(&mut product, &z2, 2 * half);
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A number of `limbs` limbs that looks random, from a fixed generator.
+    fn arbitrary(limbs: usize, seed: u64) -> BigUint {
+        let mut state = seed;
+        BigUint::from_limbs(
+            (0..limbs)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    (state >> 32) as u32
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn keeps_one_representation() {
+        assert_eq!(BigUint::from_limbs(vec![0, 0]), BigUint::zero());
+        assert_eq!(BigUint::from(5u64), BigUint::from_limbs(vec![5, 0, 0]));
+        assert!(BigUint::zero().is_zero());
+        assert_eq!(BigUint::from(u128::MAX).limbs().len(), 4);
+        assert_eq!(BigUint::zero().bits(), 0);
+        assert_eq!(BigUint::from(1u64 << 40).bits(), 41);
+    }
+
+    #[test]
+    fn adds_with_carries() {
+        let max = BigUint::from(u64::MAX);
+        assert_eq!(&max + &BigUint::one(), BigUint::from(1u128 << 64));
+        assert_eq!(&max + &max, BigUint::from(u128::from(u64::MAX) * 2),);
+        assert_eq!(&BigUint::zero() + &max, max);
+    }
+
+    #[test]
+    fn subtracts_with_borrows() {
+        let power = BigUint::from(1u128 << 96);
+        assert_eq!(&power - &BigUint::one(), BigUint::from((1u128 << 96) - 1));
+        assert_eq!(&power - &power, BigUint::zero());
+
+        let a = arbitrary(70, 1);
+        let b = arbitrary(40, 2);
+        assert_eq!(&(&a + &b) - &b, a);
+    }
+
+    #[test]
+    #[should_panic(expected = "subtract with overflow")]
+    fn refuses_to_go_below_zero() {
+        let _ = BigUint::one() - BigUint::from(2u32);
+    }
+
+    #[test]
+    fn multiplies_like_u128() {
+        let pairs = [
+            (0u64, 12345u64),
+            (1, u64::MAX),
+            (u64::MAX, u64::MAX),
+            (0xdead_beef_1234, 0x98_7654_3210),
+        ];
+        for (a, b) in pairs {
+            assert_eq!(
+                BigUint::from(a) * BigUint::from(b),
+                BigUint::from(u128::from(a) * u128::from(b)),
+                "{a} * {b}"
+            );
+        }
+    }
+
+    #[test]
+    fn karatsuba_agrees_with_the_schoolbook() {
+        let lengths = [
+            (KARATSUBA_THRESHOLD, KARATSUBA_THRESHOLD),
+            (KARATSUBA_THRESHOLD + 1, 3 * KARATSUBA_THRESHOLD),
+            (100, 100),
+            (257, 129),
+            (500, 40),
+        ];
+        for (seed, (x, y)) in lengths.into_iter().enumerate() {
+            let a = arbitrary(x, seed as u64);
+            let b = arbitrary(y, seed as u64 + 100);
+            assert_eq!(&a * &b, a.mul_schoolbook(&b), "{x} by {y} limbs");
+        }
+
+        let all_ones = BigUint::from_limbs(vec![u32::MAX; 200]);
+        assert_eq!(&all_ones * &all_ones, all_ones.mul_schoolbook(&all_ones));
+    }
+
+    #[test]
+    fn orders_by_value() {
+        let small = BigUint::from(u64::MAX);
+        let large = BigUint::from(1u128 << 64);
+        assert!(small < large);
+        assert!(BigUint::from(3u32) > BigUint::from(2u32));
+        assert_eq!(large.cmp(&large.clone()), Ordering::Equal);
+    }
+
+    #[test]
+    fn formats_in_decimal() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(1_000_000_000u32).to_string(), "1000000000");
+        assert_eq!(BigUint::from(u128::MAX).to_string(), u128::MAX.to_string());
+
+        let mut power = BigUint::one();
+        for _ in 0..50 {
+            power = power * BigUint::from(10u32);
+        }
+        assert_eq!(power.to_string(), format!("1{}", "0".repeat(50)));
+        assert_eq!(format!("{:>5}", BigUint::from(42u32)), "   42");
+    }
+}
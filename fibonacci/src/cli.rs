@@ -0,0 +1,363 @@
+use crate::algorithm::{Algorithm, UnknownAlgorithm};
+use crate::limits::{IntegerType, UnknownType};
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+pub const USAGE: &str = "\
+Usage: fibonacci [OPTIONS] [N]
+       fibonacci terms <K | A..B>
+       fibonacci mod <N> <M>
+       fibonacci pisano <M>
+       fibonacci limits
+       fibonacci timings
+
+Prints the N-th Fibonacci number, asking for N if it isn't given.
+`terms` prints the first K numbers, or those from the A-th up to but not
+including the B-th. `mod` prints the N-th number modulo M, for any N up to
+18446744073709551615, and `pisano` the period those remainders repeat with.
+`limits` prints the largest N whose number fits each integer type, and
+`timings` times every algorithm for N from 10 up to 1000000.
+
+Options:
+  -a, --algorithm <NAME>  linear, doubling (the default) or matrix
+  -t, --type <TYPE>       fail unless the number fits this type, like u64 or i32
+  -h, --help              print this message
+";
+
+/// What the user asked the binary to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Print `F(n)`, reading `n` from stdin if it's `None`.
+    Compute {
+        n: Option<u64>,
+        algorithm: Algorithm,
+        /// The type `F(n)` has to fit, if any.
+        integer_type: Option<IntegerType>,
+    },
+    /// Print `F(n)` for every `n` in the range.
+    Terms(Range<u64>),
+    /// Print `F(n) mod m` with [`crate::modular::fib_mod`].
+    Mod {
+        n: u64,
+        m: u64,
+    },
+    /// Print the Pisano period of this modulus.
+    Pisano(u64),
+    /// Print the largest `n` every [`IntegerType`] can hold `F(n)` of.
+    Limits,
+    /// Compare how long the algorithms take.
+    Timings,
+    Help,
+}
+
+/// Parses the command line, without the program name.
+pub fn parse_args<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    if let Some(name) = args.next_if(|arg| SUBCOMMANDS.contains(&arg.as_str())) {
+        return parse_subcommand(&name, args.collect());
+    }
+
+    let mut n = None;
+    let mut algorithm = None;
+    let mut integer_type = None;
+    while let Some(arg) = args.next() {
+        // both `--algorithm matrix` and `--algorithm=matrix` are accepted
+        let (option, inline_value) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => {
+                (option.to_string(), Some(value.to_string()))
+            }
+            _ => (arg, None),
+        };
+        let mut value = |option: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| CliError::MissingValue(option.to_string()))
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-a" | "--algorithm" => algorithm = Some(value("--algorithm")?.parse()?),
+            "-t" | "--type" => integer_type = Some(value("--type")?.parse()?),
+            // a negative number is a mistaken N rather than an unknown option
+            number if number.starts_with('-') && number[1..].parse::<u64>().is_err() => {
+                return Err(CliError::UnknownOption(option))
+            }
+            _ if n.is_some() => return Err(CliError::UnexpectedArgument(option)),
+            _ => n = Some(parse_n(&option)?),
+        }
+    }
+
+    Ok(Command::Compute {
+        n,
+        algorithm: algorithm.unwrap_or_default(),
+        integer_type,
+    })
+}
+
+/// Reads `N`, which can be any `u64`.
+pub fn parse_n(value: &str) -> Result<u64, CliError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| CliError::InvalidNumber(value.trim().to_string()))
+}
+
+const SUBCOMMANDS: [&str; 5] = ["terms", "mod", "pisano", "limits", "timings"];
+
+fn parse_subcommand(name: &str, args: Vec<String>) -> Result<Command, CliError> {
+    let arity = match name {
+        "terms" | "pisano" => 1,
+        "mod" => 2,
+        _ => 0,
+    };
+    if args.len() < arity {
+        return Err(CliError::MissingValue(name.to_string()));
+    }
+    if let Some(extra) = args.get(arity) {
+        return Err(CliError::UnexpectedArgument(extra.clone()));
+    }
+
+    Ok(match name {
+        "terms" => Command::Terms(parse_terms(&args[0])?),
+        "mod" => Command::Mod {
+            n: parse_n(&args[0])?,
+            m: parse_modulus(&args[1])?,
+        },
+        "pisano" => Command::Pisano(parse_modulus(&args[0])?),
+        "limits" => Command::Limits,
+        _ => Command::Timings,
+    })
+}
+
+fn parse_modulus(value: &str) -> Result<u64, CliError> {
+    match parse_n(value)? {
+        0 => Err(CliError::ZeroModulus),
+        m => Ok(m),
+    }
+}
+
+/// Reads `K` as the range `0..K`, or `A..B` and `A..=B` as they are.
+fn parse_terms(value: &str) -> Result<Range<u64>, CliError> {
+    let invalid = || CliError::InvalidTerms(value.to_string());
+
+    let Some((start, end)) = value.split_once("..") else {
+        return Ok(0..parse_n(value)?);
+    };
+    let start = start.parse().map_err(|_| invalid())?;
+    let end = match end.strip_prefix('=') {
+        Some(last) => last
+            .parse::<u64>()
+            .ok()
+            .and_then(|last| last.checked_add(1))
+            .ok_or_else(invalid)?,
+        None => end.parse().map_err(|_| invalid())?,
+    };
+    if start > end {
+        return Err(invalid());
+    }
+    Ok(start..end)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(String),
+    /// Not a number `N` can be.
+    InvalidNumber(String),
+    /// Not a count or a range of terms like `100..120`.
+    InvalidTerms(String),
+    /// Nothing is a remainder modulo 0.
+    ZeroModulus,
+    /// An argument after everything the command takes.
+    UnexpectedArgument(String),
+    Algorithm(UnknownAlgorithm),
+    Type(UnknownType),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "unknown option `{option}`"),
+            CliError::MissingValue(option) => write!(f, "`{option}` needs a value"),
+            CliError::InvalidNumber(value) => {
+                write!(f, "`{value}` is not a whole number from 0 to {}", u64::MAX)
+            }
+            CliError::InvalidTerms(value) => {
+                write!(f, "`{value}` is not a count or a range like 100..120")
+            }
+            CliError::ZeroModulus => write!(f, "the modulus must be at least 1"),
+            CliError::UnexpectedArgument(argument) => {
+                write!(f, "unexpected argument `{argument}`")
+            }
+            CliError::Algorithm(error) => error.fmt(f),
+            CliError::Type(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CliError::Algorithm(error) => Some(error),
+            CliError::Type(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<UnknownAlgorithm> for CliError {
+    fn from(error: UnknownAlgorithm) -> CliError {
+        CliError::Algorithm(error)
+    }
+}
+
+impl From<UnknownType> for CliError {
+    fn from(error: UnknownType) -> CliError {
+        CliError::Type(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn asks_for_n_by_default() {
+        assert_eq!(
+            parse(&[]),
+            Ok(Command::Compute {
+                n: None,
+                algorithm: Algorithm::Doubling,
+                integer_type: None,
+            })
+        );
+    }
+
+    #[test]
+    fn takes_n_and_an_algorithm() {
+        let expected = Ok(Command::Compute {
+            n: Some(1000),
+            algorithm: Algorithm::Matrix,
+            integer_type: None,
+        });
+        assert_eq!(parse(&["1000", "--algorithm", "matrix"]), expected);
+        assert_eq!(parse(&["--algorithm=matrix", "1000"]), expected);
+        assert_eq!(parse(&["-a", "MATRIX", "1000"]), expected);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert_eq!(
+            parse(&["-5"]),
+            Err(CliError::InvalidNumber("-5".to_string()))
+        );
+        assert_eq!(
+            parse(&["18446744073709551616"]),
+            Err(CliError::InvalidNumber("18446744073709551616".to_string()))
+        );
+        assert_eq!(
+            parse(&["--fast"]),
+            Err(CliError::UnknownOption("--fast".to_string()))
+        );
+        assert_eq!(
+            parse(&["1", "2"]),
+            Err(CliError::UnexpectedArgument("2".to_string()))
+        );
+        assert_eq!(
+            parse(&["--algorithm"]),
+            Err(CliError::MissingValue("--algorithm".to_string()))
+        );
+        assert_eq!(
+            parse(&["-a", "binet"]),
+            Err(CliError::Algorithm(UnknownAlgorithm("binet".to_string())))
+        );
+    }
+
+    #[test]
+    fn parses_terms() {
+        assert_eq!(parse(&["terms", "10"]), Ok(Command::Terms(0..10)));
+        assert_eq!(parse(&["terms", "100..120"]), Ok(Command::Terms(100..120)));
+        assert_eq!(parse(&["terms", "100..=120"]), Ok(Command::Terms(100..121)));
+        assert_eq!(parse(&["terms", "5..5"]), Ok(Command::Terms(5..5)));
+        for bad in ["120..100", "1..x", "..5", "1..=18446744073709551615"] {
+            assert_eq!(
+                parse(&["terms", bad]),
+                Err(CliError::InvalidTerms(bad.to_string()))
+            );
+        }
+        assert_eq!(
+            parse(&["terms"]),
+            Err(CliError::MissingValue("terms".to_string()))
+        );
+        assert_eq!(
+            parse(&["terms", "-1"]),
+            Err(CliError::InvalidNumber("-1".to_string()))
+        );
+    }
+
+    #[test]
+    fn takes_a_type_to_fit() {
+        assert_eq!(
+            parse(&["--type", "u64", "93"]),
+            Ok(Command::Compute {
+                n: Some(93),
+                algorithm: Algorithm::Doubling,
+                integer_type: Some(IntegerType::U64),
+            })
+        );
+        assert_eq!(
+            parse(&["-t", "float"]),
+            Err(CliError::Type(UnknownType("float".to_string())))
+        );
+    }
+
+    #[test]
+    fn parses_modular_commands() {
+        assert_eq!(
+            parse(&["mod", "18446744073709551615", "1000000007"]),
+            Ok(Command::Mod {
+                n: u64::MAX,
+                m: 1_000_000_007
+            })
+        );
+        assert_eq!(parse(&["pisano", "10"]), Ok(Command::Pisano(10)));
+        assert_eq!(parse(&["pisano", "0"]), Err(CliError::ZeroModulus));
+        assert_eq!(parse(&["mod", "5", "0"]), Err(CliError::ZeroModulus));
+        assert_eq!(
+            parse(&["mod", "5"]),
+            Err(CliError::MissingValue("mod".to_string()))
+        );
+        assert_eq!(
+            parse(&["pisano", "10", "20"]),
+            Err(CliError::UnexpectedArgument("20".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_limits() {
+        assert_eq!(parse(&["limits"]), Ok(Command::Limits));
+        assert_eq!(
+            parse(&["limits", "u8"]),
+            Err(CliError::UnexpectedArgument("u8".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_timings() {
+        assert_eq!(parse(&["timings"]), Ok(Command::Timings));
+        assert_eq!(
+            parse(&["timings", "10"]),
+            Err(CliError::UnexpectedArgument("10".to_string()))
+        );
+        assert_eq!(parse(&["--help"]), Ok(Command::Help));
+    }
+}
@@ -0,0 +1,22 @@
+//! Fibonacci numbers, exact however large they get.
+//!
+//! `F(0) = 0`, `F(1) = 1` and every later number is the sum of the two
+//! before it. The numbers grow by a factor of about 1.618 per step, so
+//! `F(47)` is the last one that fits a `u32` and `F(93)` the last one that
+//! fits a `u64`; everything here works with [`BigUint`] to go past that.
+
+pub mod algorithm;
+pub mod biguint;
+pub mod cli;
+pub mod limits;
+pub mod modular;
+pub mod sequence;
+
+pub use algorithm::Algorithm;
+pub use biguint::BigUint;
+pub use sequence::{FibNum, Fibonacci};
+
+/// `F(n)`, computed with the default [`Algorithm`].
+pub fn fibonacci(n: u64) -> BigUint {
+    Algorithm::default().compute(n)
+}
@@ -0,0 +1,266 @@
+//! How far the sequence goes in each machine integer type.
+//!
+//! Nothing here is a table typed in by hand: the largest index for a type is
+//! how many terms [`Fibonacci`] yields in it before the next one would
+//! overflow, so the limits follow the types wherever the code is built.
+
+use crate::sequence::{FibNum, Fibonacci};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerType {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+}
+
+macro_rules! dispatch {
+    ($self:expr, $t:ident => $body:expr) => {
+        match $self {
+            IntegerType::U8 => {
+                type $t = u8;
+                $body
+            }
+            IntegerType::U16 => {
+                type $t = u16;
+                $body
+            }
+            IntegerType::U32 => {
+                type $t = u32;
+                $body
+            }
+            IntegerType::U64 => {
+                type $t = u64;
+                $body
+            }
+            IntegerType::U128 => {
+                type $t = u128;
+                $body
+            }
+            IntegerType::I8 => {
+                type $t = i8;
+                $body
+            }
+            IntegerType::I16 => {
+                type $t = i16;
+                $body
+            }
+            IntegerType::I32 => {
+                type $t = i32;
+                $body
+            }
+            IntegerType::I64 => {
+                type $t = i64;
+                $body
+            }
+            IntegerType::I128 => {
+                type $t = i128;
+                $body
+            }
+        }
+    };
+}
+
+impl IntegerType {
+    pub const ALL: [IntegerType; 10] = [
+        IntegerType::U8,
+        IntegerType::U16,
+        IntegerType::U32,
+        IntegerType::U64,
+        IntegerType::U128,
+        IntegerType::I8,
+        IntegerType::I16,
+        IntegerType::I32,
+        IntegerType::I64,
+        IntegerType::I128,
+    ];
+
+    /// The name Rust gives the type, like `u64`.
+    pub fn name(self) -> &'static str {
+        match self {
+            IntegerType::U8 => "u8",
+            IntegerType::U16 => "u16",
+            IntegerType::U32 => "u32",
+            IntegerType::U64 => "u64",
+            IntegerType::U128 => "u128",
+            IntegerType::I8 => "i8",
+            IntegerType::I16 => "i16",
+            IntegerType::I32 => "i32",
+            IntegerType::I64 => "i64",
+            IntegerType::I128 => "i128",
+        }
+    }
+
+    /// The largest `n` whose `F(n)` the type can hold.
+    pub fn max_index(self) -> u64 {
+        dispatch!(self, T => last_index::<T>())
+    }
+
+    /// `F(n)` for the largest `n` the type can hold, in decimal.
+    pub fn max_term(self) -> String {
+        dispatch!(self, T => last_term::<T>())
+    }
+
+    /// The largest value of the type, in decimal.
+    pub fn max_value(self) -> String {
+        dispatch!(self, T => T::MAX.to_string())
+    }
+
+    /// Checks that `F(n)` fits the type.
+    pub fn check(self, n: u64) -> Result<(), TooLarge> {
+        let limit = self.max_index();
+        if n <= limit {
+            Ok(())
+        } else {
+            Err(TooLarge {
+                integer_type: self,
+                n,
+                limit,
+            })
+        }
+    }
+}
+
+fn last_index<T: FibNum>() -> u64 {
+    Fibonacci::<T>::new().count() as u64 - 1
+}
+
+fn last_term<T: FibNum + fmt::Display>() -> String {
+    let last = Fibonacci::<T>::new().last();
+    last.expect("every type holds F(0)").to_string()
+}
+
+impl fmt::Display for IntegerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for IntegerType {
+    type Err = UnknownType;
+
+    fn from_str(s: &str) -> Result<IntegerType, UnknownType> {
+        IntegerType::ALL
+            .into_iter()
+            .find(|integer_type| integer_type.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownType(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownType(pub String);
+
+impl fmt::Display for UnknownType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown type `{}`, expected one of u8, u16, u32, u64, u128, i8, i16, i32, i64 or i128",
+            self.0
+        )
+    }
+}
+
+impl Error for UnknownType {}
+
+/// `F(n)` is too large for the type that was asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooLarge {
+    pub integer_type: IntegerType,
+    pub n: u64,
+    /// The largest `n` the type can hold, [`IntegerType::max_index`].
+    pub limit: u64,
+}
+
+impl fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "F({}) doesn't fit in {}, which goes up to F({})",
+            self.n, self.integer_type, self.limit
+        )
+    }
+}
+
+impl Error for TooLarge {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::fast_doubling;
+    use crate::biguint::BigUint;
+
+    #[test]
+    fn the_limit_fits_and_the_next_term_does_not() {
+        for integer_type in IntegerType::ALL {
+            let max: u128 = integer_type.max_value().parse().unwrap();
+            let max = BigUint::from(max);
+            let limit = integer_type.max_index();
+
+            assert!(
+                fast_doubling(limit) <= max,
+                "F({limit}) fits {integer_type}"
+            );
+            assert!(
+                fast_doubling(limit + 1) > max,
+                "F({}) overflows {integer_type}",
+                limit + 1
+            );
+            assert_eq!(integer_type.max_term(), fast_doubling(limit).to_string());
+        }
+    }
+
+    #[test]
+    fn matches_the_well_known_limits() {
+        let limits: Vec<(&str, u64)> = IntegerType::ALL
+            .into_iter()
+            .map(|integer_type| (integer_type.name(), integer_type.max_index()))
+            .collect();
+        assert_eq!(
+            limits,
+            [
+                ("u8", 13),
+                ("u16", 24),
+                ("u32", 47),
+                ("u64", 93),
+                ("u128", 186),
+                ("i8", 11),
+                ("i16", 23),
+                ("i32", 46),
+                ("i64", 92),
+                ("i128", 184),
+            ]
+        );
+    }
+
+    #[test]
+    fn names_the_limit() {
+        assert_eq!(IntegerType::U64.check(93), Ok(()));
+        let error = IntegerType::U64.check(94).unwrap_err();
+        assert_eq!(error.limit, 93);
+        assert_eq!(
+            error.to_string(),
+            "F(94) doesn't fit in u64, which goes up to F(93)"
+        );
+    }
+
+    #[test]
+    fn parses_names() {
+        for integer_type in IntegerType::ALL {
+            assert_eq!(integer_type.name().parse(), Ok(integer_type));
+        }
+        assert_eq!("U128".parse(), Ok(IntegerType::U128));
+        assert_eq!(
+            "f64".parse::<IntegerType>(),
+            Err(UnknownType("f64".to_string()))
+        );
+    }
+}
@@ -1,44 +1,118 @@
-use std::io;
+use fibonacci::cli::{self, Command};
+use fibonacci::limits::IntegerType;
+use fibonacci::modular::{fib_mod, pisano};
+use fibonacci::{Algorithm, Fibonacci};
+use std::time::{Duration, Instant};
+use std::{env, io, process};
+
+/// The `n` the timing table goes through.
+const TIMED: [u64; 6] = [10, 100, 1_000, 10_000, 100_000, 1_000_000];
+
+/// The linear loop takes minutes beyond this, so the table leaves it out.
+const LINEAR_TIMING_LIMIT: u64 = 100_000;
 
 fn main() {
-    let fibo_n: i32 = loop {
+    match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Compute {
+            n,
+            algorithm,
+            integer_type,
+        }) => {
+            let n = n.unwrap_or_else(read_n);
+            if let Some(Err(error)) = integer_type.map(|integer_type| integer_type.check(n)) {
+                eprintln!("error: {error}");
+                process::exit(1);
+            }
+            println!("The {n}th fibonacci number is: {}", algorithm.compute(n));
+        }
+        Ok(Command::Terms(range)) => {
+            for (n, term) in range.clone().zip(Fibonacci::starting_at(range.start)) {
+                println!("F({n}) = {term}");
+            }
+        }
+        Ok(Command::Mod { n, m }) => println!("F({n}) mod {m} = {}", fib_mod(n, m)),
+        Ok(Command::Pisano(m)) => println!("The Pisano period of {m} is {}", pisano(m)),
+        Ok(Command::Limits) => print_limits(),
+        Ok(Command::Timings) => print_timings(),
+        Ok(Command::Help) => print!("{}", cli::USAGE),
+        Err(error) => {
+            eprintln!("error: {error}\n\n{}", cli::USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn read_n() -> u64 {
+    loop {
         println!("Enter n:");
         let mut n = String::new();
 
-        io::stdin().read_line(&mut n).expect("Enter a valid number");
+        if io::stdin().read_line(&mut n).expect("Enter a valid number") == 0 {
+            process::exit(1);
+        }
+
+        match cli::parse_n(&n) {
+            Ok(n) => return n,
+            Err(_) if n.trim().starts_with('-') => println!("Enter a non-negative integer"),
+            Err(_) => println!("Enter a valid number"),
+        }
+    }
+}
+
+fn print_limits() {
+    println!(
+        "{:<6}  {:>9}  {:>39}  {:>39}",
+        "type", "largest n", "F(n)", "largest value"
+    );
+    for integer_type in IntegerType::ALL {
+        println!(
+            "{:<6}  {:>9}  {:>39}  {:>39}",
+            integer_type,
+            integer_type.max_index(),
+            integer_type.max_term(),
+            integer_type.max_value()
+        );
+    }
+}
 
-        let n: i32 = match n.trim().parse() {
-            Err(_) => {
-                println!("Enter a valid number");
+fn print_timings() {
+    print!("{:>9}", "n");
+    for algorithm in Algorithm::ALL {
+        print!("  {algorithm:>12}");
+    }
+    println!();
+
+    for n in TIMED {
+        print!("{n:>9}");
+        let mut expected = None;
+        for algorithm in Algorithm::ALL {
+            if algorithm == Algorithm::Linear && n > LINEAR_TIMING_LIMIT {
+                print!("  {:>12}", "-");
                 continue;
             }
-            Ok(num) => num,
-        };
 
-        if n < 0 {
-            println!("Enter a non-negative integer");
-        } else {
-            break n;
+            let started = Instant::now();
+            let value = algorithm.compute(n);
+            let elapsed = started.elapsed();
+            // a table of wrong answers would be quick to make
+            assert!(
+                expected.get_or_insert_with(|| value.clone()) == &value,
+                "{algorithm} disagrees about F({n})"
+            );
+            print!("  {:>12}", format_duration(elapsed));
         }
-    };
+        println!();
+    }
+    println!("\n- the linear loop is left out above n = {LINEAR_TIMING_LIMIT}");
+}
 
-    if fibo_n == 0 {
-        println!("{fibo_n}th fibonacci number is 0");
-    } else if fibo_n == 1 {
-        println!("{fibo_n}th fibonacci number is 1");
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1e6;
+    if micros < 1000.0 {
+        format!("{micros:.1} µs")
+    } else if micros < 1e6 {
+        format!("{:.2} ms", micros / 1000.0)
     } else {
-        let mut a1: u32 = 0;
-        let mut a2: u32 = 1;
-        let mut count: i32 = fibo_n - 2;
-        let mut temp: u32 = 0;
-
-        while count > 0 {
-            temp = a2;
-            a2 = a1 + a2;
-            a1 = temp;
-            count -= 1;
-        }
-
-        println!("The {fibo_n}th fibonacci number is: {a2}");
+        format!("{:.2} s", micros / 1e6)
     }
 }
@@ -0,0 +1,357 @@
+//! Fibonacci numbers modulo `m`, and the Pisano period they repeat with.
+//!
+//! `F(n) mod m` never needs `F(n)` itself: fast doubling works just as well
+//! on remainders, so `n` can be as large as `u64::MAX`. The remainders repeat
+//! with a period `π(m)`, the Pisano period, which [`pisano`] finds without
+//! walking through it:
+//!
+//! - `π` of a product of coprime numbers is the lcm of their periods, so it's
+//!   enough to know `π(p^k)` for the prime powers of `m`;
+//! - `π(p^k) = p^(k-1) π(p)`, which fails only for Wall-Sun-Sun primes; none
+//!   is known, and the searches went far beyond the `2^32` that a square
+//!   factor of a `u64` can reach;
+//! - `π(p)` divides `p - 1` when `p` ends in 1 or 9, and `2 (p + 1)` when it
+//!   ends in 3 or 7, so it's found by dividing out the factors of those for
+//!   as long as what's left is still a period.
+
+/// `F(n) mod m`.
+///
+/// # Panics
+///
+/// If `m` is zero.
+pub fn fib_mod(n: u64, m: u64) -> u64 {
+    assert!(m > 0, "the modulus must be positive");
+    fib_pair_mod(u128::from(n), m).0
+}
+
+/// `(F(n) mod m, F(n + 1) mod m)` by fast doubling, for `n` past `u64` too.
+fn fib_pair_mod(n: u128, m: u64) -> (u64, u64) {
+    let m = u128::from(m);
+    // (F(k), F(k + 1)) mod m for k the bits of n seen so far; every product
+    // is of two remainders below 2^64, so it fits a u128
+    let mut current = 0;
+    let mut next = 1 % m;
+
+    for bit in (0..u128::BITS - n.leading_zeros()).rev() {
+        let doubled = current * ((2 * next + m - current) % m) % m;
+        let doubled_next = (current * current % m + next * next % m) % m;
+        if n >> bit & 1 == 1 {
+            current = doubled_next;
+            next = (doubled + doubled_next) % m;
+        } else {
+            current = doubled;
+            next = doubled_next;
+        }
+    }
+    (current as u64, next as u64)
+}
+
+/// The Pisano period `π(m)`: the length of the cycle `F(n) mod m` repeats.
+/// It can be up to `6 m`, which is past `u64` for the largest `m`.
+///
+/// # Panics
+///
+/// If `m` is zero.
+pub fn pisano(m: u64) -> u128 {
+    assert!(m > 0, "the modulus must be positive");
+    factorize(m)
+        .into_iter()
+        .map(|(p, k)| pisano_prime(p) * u128::from(p).pow(k - 1))
+        .fold(1, lcm)
+}
+
+fn pisano_prime(p: u64) -> u128 {
+    let (multiple, factors) = match p {
+        2 => return 3,
+        5 => return 20,
+        _ if p % 5 == 1 || p % 5 == 4 => (u128::from(p - 1), factorize(p - 1)),
+        // p is odd, so p + 1 is even and 2 (p + 1) has one more factor of 2
+        _ => {
+            let mut factors = factorize(p + 1);
+            factors[0].1 += 1;
+            (2 * u128::from(p + 1), factors)
+        }
+    };
+
+    let is_period = |length: u128| fib_pair_mod(length, p) == (0, 1);
+    let mut period = multiple;
+    for (q, exponent) in factors {
+        for _ in 0..exponent {
+            let shorter = period / u128::from(q);
+            if !is_period(shorter) {
+                break;
+            }
+            period = shorter;
+        }
+    }
+    period
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn lcm(a: u128, b: u128) -> u128 {
+    a / gcd(a, b) * b
+}
+
+/// The prime factors of `n` with their exponents, smallest first; none for 1.
+///
+/// # Panics
+///
+/// If `n` is zero.
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    assert!(n > 0, "only positive numbers have prime factors");
+    let mut primes = Vec::new();
+    let mut rest = n;
+    // small factors are quickest found the plain way
+    for p in 2..1000 {
+        while rest.is_multiple_of(p) {
+            primes.push(p);
+            rest /= p;
+        }
+    }
+    split(rest, &mut primes);
+    primes.sort_unstable();
+
+    let mut factors: Vec<(u64, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exponent)) if *last == p => *exponent += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+/// Adds the prime factors of `n`, which has none below 1000, to `primes`.
+fn split(n: u64, primes: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        primes.push(n);
+        return;
+    }
+    let divisor = pollard_rho(n);
+    split(divisor, primes);
+    split(n / divisor, primes);
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(m)) as u64
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Miller-Rabin with the bases that make it exact for every `u64`.
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    if let Some(&p) = BASES.iter().find(|&&p| n.is_multiple_of(p)) {
+        return n == p;
+    }
+
+    let odd = (n - 1) >> (n - 1).trailing_zeros();
+    BASES.iter().all(|&base| {
+        let mut x = pow_mod(base, odd, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        let mut exponent = odd;
+        while exponent < n - 1 {
+            x = mul_mod(x, x, n);
+            exponent <<= 1;
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// A proper divisor of the odd composite `n`, by Pollard's rho.
+fn pollard_rho(n: u64) -> u64 {
+    for increment in 1.. {
+        let step = |x: u64| ((u128::from(x) * u128::from(x) + increment) % u128::from(n)) as u64;
+        let (mut tortoise, mut hare) = (2, 2);
+        let divisor = loop {
+            tortoise = step(tortoise);
+            hare = step(step(hare));
+            let divisor = gcd(u128::from(tortoise.abs_diff(hare)), u128::from(n)) as u64;
+            if divisor != 1 {
+                break divisor;
+            }
+        };
+        // the walk met itself before finding a factor, try another one
+        if divisor != n {
+            return divisor;
+        }
+    }
+    unreachable!("every composite number has a factor rho finds")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// OEIS A001175, the Pisano periods of 1 to 60. Only these are copied
+    /// from the published table; from 61 to 1000 the periods are checked
+    /// against Wall's theorems in `follows_wall_up_to_1000` and against the
+    /// definition in `matches_the_definition_up_to_1000`.
+    const PUBLISHED: [u128; 60] = [
+        1, 3, 8, 6, 20, 24, 16, 12, 24, 60, 10, 24, 28, 48, 40, 24, 36, 24, 18, 60, 16, 30, 48, 24,
+        100, 84, 72, 48, 14, 120, 30, 48, 40, 36, 80, 24, 76, 18, 56, 60, 40, 48, 88, 30, 120, 48,
+        32, 24, 112, 300, 72, 84, 108, 72, 20, 48, 72, 42, 58, 120,
+    ];
+
+    /// The period found the slow way, waiting for 0, 1 to come round again.
+    fn walked_period(m: u64) -> u128 {
+        let (mut current, mut next) = (0, 1 % m);
+        let mut length = 0;
+        loop {
+            (current, next) = (next, (current + next) % m);
+            length += 1;
+            if (current, next) == (0, 1 % m) {
+                return length;
+            }
+        }
+    }
+
+    #[test]
+    fn matches_the_published_periods_up_to_60() {
+        for (m, &period) in (1..).zip(PUBLISHED.iter()) {
+            assert_eq!(pisano(m), period, "π({m})");
+        }
+    }
+
+    #[test]
+    fn matches_the_definition_up_to_1000() {
+        for m in 1..=1000 {
+            assert_eq!(pisano(m), walked_period(m), "π({m})");
+        }
+    }
+
+    /// Periods up to 1000 from the published results of D. D. Wall,
+    /// "Fibonacci series modulo m" (1960), that don't need the table.
+    #[test]
+    fn follows_wall_up_to_1000() {
+        // π(p^k) = p^(k - 1) π(p) for every prime power checked so far
+        for (p, period) in [(2, 3), (3, 8), (5, 20), (7, 16), (11, 10), (13, 28)] {
+            let mut power = p;
+            while power <= 1000 {
+                assert_eq!(pisano(power), u128::from(power / p) * period, "π({power})");
+                power *= p;
+            }
+        }
+
+        // π(p) divides p - 1 for a prime p ≡ ±1 (mod 10), and divides
+        // 2 (p + 1) but not p + 1 for p ≡ ±3 (mod 10)
+        let primes =
+            (7..=1000u64).filter(|&n| (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0));
+        for p in primes {
+            let period = pisano(p) as u64;
+            match p % 10 {
+                1 | 9 => assert!((p - 1).is_multiple_of(period), "π({p}) = {period}"),
+                _ => {
+                    assert!((2 * (p + 1)).is_multiple_of(period), "π({p}) = {period}");
+                    assert!(!(p + 1).is_multiple_of(period), "π({p}) = {period}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn knows_the_famous_periods() {
+        // π(10^k) = 15 10^(k - 1) from k = 3 on, and 6m is reached at m = 2 5^k
+        assert_eq!(pisano(1000), 1500);
+        assert_eq!(pisano(1_000_000_000_000_000_000), 1_500_000_000_000_000_000);
+        assert_eq!(pisano(2 * 5u64.pow(20)), 6 * 2 * 5u128.pow(20));
+        assert_eq!(pisano(1_000_000_007), 2_000_000_016);
+        assert_eq!(pisano(u64::MAX), 3_021_228_124_801_920);
+        for m in 1..=1000 {
+            assert!(pisano(m) <= 6 * u128::from(m));
+        }
+    }
+
+    #[test]
+    fn remainders_follow_the_sequence() {
+        for m in [1, 2, 7, 10, 1000, 1_000_000_007, u64::MAX] {
+            let (mut current, mut next) = (0, 1 % m);
+            for n in 0..=2000 {
+                assert_eq!(fib_mod(n, m), current, "F({n}) mod {m}");
+                (current, next) = (
+                    next,
+                    ((u128::from(current) + u128::from(next)) % u128::from(m)) as u64,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reaches_the_largest_n() {
+        assert_eq!(fib_mod(u64::MAX, 1_000_000_007), 683_972_503);
+        assert_eq!(fib_mod(u64::MAX, u64::MAX), 4_093_298_358_055_684_510);
+        assert_eq!(fib_mod(u64::MAX, 1 << 32), 1_070_270_178);
+        assert_eq!(
+            fib_mod(1_000_000_000_000_000_000, 1_000_000_007),
+            209_783_453
+        );
+
+        // the remainders repeat with the period
+        for m in [10, 999, 65_537] {
+            let period = pisano(m) as u64;
+            assert_eq!(fib_mod(u64::MAX, m), fib_mod(u64::MAX % period, m));
+        }
+    }
+
+    #[test]
+    fn factors_numbers() {
+        assert_eq!(factorize(1), []);
+        assert_eq!(factorize(360), [(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(
+            factorize(u64::MAX),
+            [
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65_537, 1),
+                (6_700_417, 1)
+            ]
+        );
+        // a square of a prime past the trial divisions, and a large prime
+        assert_eq!(
+            factorize(4_294_967_291 * 4_294_967_291),
+            [(4_294_967_291, 2)]
+        );
+        assert_eq!(
+            factorize(18_446_744_073_709_551_557),
+            [(18_446_744_073_709_551_557, 1)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only positive numbers have prime factors")]
+    fn zero_has_no_factors() {
+        factorize(0);
+    }
+}
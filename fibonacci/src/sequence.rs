@@ -0,0 +1,186 @@
+//! The sequence itself, as an iterator over any type numbers can be added in.
+//!
+//! Machine integers end the sequence at the last term that fits them rather
+//! than overflowing: `Fibonacci::<u8>::new()` yields `F(0)` to `F(13) = 233`
+//! and then `None`. For the wrap-around of release builds ask for it with
+//! [`Wrapping`], and for every term exactly use [`BigUint`], with which the
+//! sequence never ends.
+
+use crate::algorithm::fast_doubling_pair;
+use crate::biguint::BigUint;
+use std::iter::FusedIterator;
+use std::num::Wrapping;
+
+/// What [`Fibonacci`] can count in.
+pub trait FibNum: Clone {
+    fn zero() -> Self;
+    fn one() -> Self;
+    /// `self + other`, or `None` if the sum doesn't fit the type, which ends
+    /// the sequence. Types whose sums always fit, like [`Wrapping`] and
+    /// [`BigUint`], never return `None`.
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+
+macro_rules! fib_num {
+    ($($t:ty),*) => {
+        $(impl FibNum for $t {
+            fn zero() -> $t {
+                0
+            }
+
+            fn one() -> $t {
+                1
+            }
+
+            fn checked_add(&self, other: &$t) -> Option<$t> {
+                <$t>::checked_add(*self, *other)
+            }
+        }
+
+        /// Wrapping arithmetic never overflows, so the sum always fits and a
+        /// sequence of `Wrapping` numbers never ends.
+        impl FibNum for Wrapping<$t> {
+            fn zero() -> Wrapping<$t> {
+                Wrapping(0)
+            }
+
+            fn one() -> Wrapping<$t> {
+                Wrapping(1)
+            }
+
+            fn checked_add(&self, other: &Wrapping<$t>) -> Option<Wrapping<$t>> {
+                Some(self + other)
+            }
+        })*
+    };
+}
+
+fib_num!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl FibNum for BigUint {
+    fn zero() -> BigUint {
+        BigUint::zero()
+    }
+
+    fn one() -> BigUint {
+        BigUint::one()
+    }
+
+    fn checked_add(&self, other: &BigUint) -> Option<BigUint> {
+        Some(self + other)
+    }
+}
+
+/// `F(0)`, `F(1)`, `F(2)` and on, for as long as the terms fit `T`.
+#[derive(Debug, Clone)]
+pub struct Fibonacci<T> {
+    current: Option<T>,
+    next: Option<T>,
+}
+
+impl<T: FibNum> Fibonacci<T> {
+    pub fn new() -> Fibonacci<T> {
+        Fibonacci {
+            current: Some(T::zero()),
+            next: Some(T::one()),
+        }
+    }
+}
+
+impl<T: FibNum> Default for Fibonacci<T> {
+    fn default() -> Fibonacci<T> {
+        Fibonacci::new()
+    }
+}
+
+impl Fibonacci<BigUint> {
+    /// The sequence from `F(n)` on, jumping there by fast doubling rather
+    /// than adding up the terms before it.
+    pub fn starting_at(n: u64) -> Fibonacci<BigUint> {
+        let (current, next) = fast_doubling_pair(n);
+        Fibonacci {
+            current: Some(current),
+            next: Some(next),
+        }
+    }
+}
+
+impl<T: FibNum> Iterator for Fibonacci<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let current = self.current.take()?;
+        self.current = self.next.take();
+        self.next = self
+            .current
+            .as_ref()
+            .and_then(|next| next.checked_add(&current));
+        Some(current)
+    }
+}
+
+impl<T: FibNum> FusedIterator for Fibonacci<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_the_sequence() {
+        let first: Vec<u32> = Fibonacci::new().take(12).collect();
+        assert_eq!(first, [0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89]);
+    }
+
+    #[test]
+    fn stops_at_the_last_term_that_fits() {
+        assert_eq!(Fibonacci::<u8>::new().last(), Some(233));
+        assert_eq!(Fibonacci::<u8>::new().count(), 14);
+        assert_eq!(Fibonacci::<i8>::new().last(), Some(89));
+        assert_eq!(Fibonacci::<u32>::new().last(), Some(2_971_215_073));
+        assert_eq!(Fibonacci::<i32>::new().count(), 47);
+        assert_eq!(
+            Fibonacci::<u64>::new().last(),
+            Some(12_200_160_415_121_876_738)
+        );
+        assert_eq!(Fibonacci::<u128>::new().count(), 187);
+
+        let mut terms = Fibonacci::<u8>::new().skip(13);
+        assert_eq!(terms.next(), Some(233));
+        assert_eq!(terms.next(), None);
+        assert_eq!(terms.next(), None);
+    }
+
+    #[test]
+    fn wraps_when_asked_to() {
+        let wrapped: Vec<u8> = Fibonacci::<Wrapping<u8>>::new()
+            .skip(12)
+            .take(4)
+            .map(|term| term.0)
+            .collect();
+        // 377 and 610 wrap to 121 and 98
+        assert_eq!(wrapped, [144, 233, 121, 98]);
+    }
+
+    #[test]
+    fn machine_types_agree_with_big_integers() {
+        let big: Vec<String> = Fibonacci::<BigUint>::new()
+            .take(187)
+            .map(|term| term.to_string())
+            .collect();
+        let small: Vec<String> = Fibonacci::<u128>::new()
+            .map(|term| term.to_string())
+            .collect();
+        assert_eq!(big, small);
+    }
+
+    #[test]
+    fn starts_anywhere() {
+        let from_start: Vec<BigUint> = Fibonacci::new().skip(100).take(20).collect();
+        let jumped: Vec<BigUint> = Fibonacci::starting_at(100).take(20).collect();
+        assert_eq!(jumped, from_start);
+        assert_eq!(
+            Fibonacci::starting_at(0).take(3).collect::<Vec<_>>(),
+            [BigUint::zero(), BigUint::one(), BigUint::one()]
+        );
+    }
+}
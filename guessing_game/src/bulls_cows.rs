@@ -0,0 +1,651 @@
+//! Bulls and Cows: guess a secret code of distinct digits. Every guess is
+//! answered with its bulls, right digits in the right place, and its cows,
+//! right digits in the wrong place.
+//!
+//! The game runs on the same [`session`](crate::session) loop, attempt limits
+//! and seeded RNG as the number game.
+
+use crate::session::{self, Console, Frontend, Input, Outcome, Puzzle};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// The longest code there is, one of each digit.
+pub const MAX_DIGITS: usize = 10;
+
+pub const HELP: &str = "\
+Type a code to guess it, or one of these commands:
+  hint     count the codes still possible and suggest a guess
+  history  list your guesses so far
+  giveup   reveal the secret code and end the game
+  quit     leave without revealing the secret code
+  help     show this message";
+
+/// A sequence of distinct decimal digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Code {
+    digits: [u8; MAX_DIGITS],
+    len: u8,
+    /// Bit `d` is set when the code holds digit `d`, which makes counting
+    /// the digits two codes share a single `count_ones`.
+    mask: u16,
+}
+
+impl Code {
+    /// Returns `None` unless `digits` are 1 to 10 distinct digits from 0 to 9.
+    pub fn new(digits: &[u8]) -> Option<Code> {
+        if digits.is_empty() || digits.len() > MAX_DIGITS {
+            return None;
+        }
+
+        let mut code = Code {
+            digits: [0; MAX_DIGITS],
+            len: digits.len() as u8,
+            mask: 0,
+        };
+        for (i, &digit) in digits.iter().enumerate() {
+            if digit > 9 || code.mask & (1 << digit) != 0 {
+                return None;
+            }
+            code.digits[i] = digit;
+            code.mask |= 1 << digit;
+        }
+        Some(code)
+    }
+
+    /// A code of `len` distinct digits drawn uniformly at random.
+    pub fn random<R: Rng + ?Sized>(len: usize, rng: &mut R) -> Code {
+        let mut digits: Vec<u8> = (0..10).collect();
+        digits.shuffle(rng);
+        Code::new(&digits[..len]).expect("a prefix of a shuffled 0..10 has distinct digits")
+    }
+
+    /// Every code of `len` digits, in increasing order.
+    pub fn all(len: usize) -> Vec<Code> {
+        assert!(
+            (1..=MAX_DIGITS).contains(&len),
+            "codes have 1 to {MAX_DIGITS} digits"
+        );
+
+        // `used` has bit `d` set when `prefix` holds digit `d`
+        fn extend(prefix: &mut Vec<u8>, used: u16, len: usize, codes: &mut Vec<Code>) {
+            if prefix.len() == len {
+                codes.push(Code::new(prefix).expect("only unused digits are added"));
+                return;
+            }
+            for digit in 0..10 {
+                if used & (1 << digit) == 0 {
+                    prefix.push(digit);
+                    extend(prefix, used | 1 << digit, len, codes);
+                    prefix.pop();
+                }
+            }
+        }
+
+        let mut codes = Vec::new();
+        extend(&mut Vec::with_capacity(len), 0, len, &mut codes);
+        codes
+    }
+
+    pub fn digits(&self) -> &[u8] {
+        &self.digits[..self.len()]
+    }
+
+    pub fn len(&self) -> usize {
+        usize::from(self.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The bulls and cows of this guess against `secret`, both the same length.
+    pub fn score(&self, secret: &Code) -> Score {
+        debug_assert_eq!(
+            self.len, secret.len,
+            "only codes of one length can be compared"
+        );
+
+        let bulls = self
+            .digits()
+            .iter()
+            .zip(secret.digits())
+            .filter(|(a, b)| a == b)
+            .count() as u8;
+        let shared = (self.mask & secret.mask).count_ones() as u8;
+        Score {
+            bulls,
+            cows: shared - bulls,
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for digit in self.digits() {
+            write!(f, "{digit}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Code {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Code, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("Please type a code, or `help` to see the commands.".to_string());
+        }
+
+        let mut digits = Vec::new();
+        for c in s.chars() {
+            let Some(digit) = c.to_digit(10) else {
+                return Err(format!(
+                    "`{s}` is neither a code nor a command, type `help` to see the commands."
+                ));
+            };
+            if digits.contains(&(digit as u8)) {
+                return Err(format!("`{s}` repeats the digit {c}, codes never do."));
+            }
+            digits.push(digit as u8);
+        }
+
+        Code::new(&digits).ok_or_else(|| format!("`{s}` is longer than any code."))
+    }
+}
+
+/// The answer to a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Score {
+    pub bulls: u8,
+    pub cows: u8,
+}
+
+impl Score {
+    /// A distinct small number for every possible score, to count by.
+    fn index(self) -> usize {
+        usize::from(self.bulls) * (MAX_DIGITS + 1) + usize::from(self.cows)
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = |n: u8| if n == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} bull{}, {} cow{}",
+            self.bulls,
+            plural(self.bulls),
+            self.cows,
+            plural(self.cows)
+        )
+    }
+}
+
+/// What the game has to say about a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    /// The guess was scored. It wins when every digit is a bull.
+    Scored(Score),
+    /// The guess doesn't have as many digits as the secret.
+    WrongLength,
+    AlreadyGuessed,
+    GameOver,
+}
+
+/// One round of Bulls and Cows.
+#[derive(Debug, Clone)]
+pub struct BullsCows {
+    secret: Code,
+    history: Vec<(Code, Score)>,
+    max_attempts: Option<u32>,
+    won: bool,
+}
+
+impl BullsCows {
+    pub fn new(secret: Code) -> BullsCows {
+        BullsCows {
+            secret,
+            history: Vec::new(),
+            max_attempts: None,
+            won: false,
+        }
+    }
+
+    /// Starts a game with a secret of `digits` digits drawn from `rng`.
+    pub fn with_rng<R: Rng + ?Sized>(digits: usize, rng: &mut R) -> BullsCows {
+        BullsCows::new(Code::random(digits, rng))
+    }
+
+    /// Limits the game to `max_attempts` scored guesses, `None` meaning unlimited.
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> BullsCows {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn guess(&mut self, guess: Code) -> Reply {
+        if self.is_over() {
+            return Reply::GameOver;
+        }
+        if guess.len() != self.secret.len() {
+            return Reply::WrongLength;
+        }
+        if self.history.iter().any(|(earlier, _)| *earlier == guess) {
+            return Reply::AlreadyGuessed;
+        }
+
+        let score = guess.score(&self.secret);
+        self.history.push((guess, score));
+        if usize::from(score.bulls) == self.secret.len() {
+            self.won = true;
+        }
+        Reply::Scored(score)
+    }
+
+    pub fn secret(&self) -> Code {
+        self.secret
+    }
+
+    /// How many digits the secret has.
+    pub fn digits(&self) -> usize {
+        self.secret.len()
+    }
+
+    pub fn history(&self) -> &[(Code, Score)] {
+        &self.history
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn max_attempts(&self) -> Option<u32> {
+        self.max_attempts
+    }
+
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.max_attempts
+            .map(|max| max.saturating_sub(self.attempts() as u32))
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.won
+    }
+
+    pub fn is_lost(&self) -> bool {
+        !self.won && self.attempts_left() == Some(0)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+}
+
+impl Puzzle for BullsCows {
+    type Guess = Code;
+    type Reply = Reply;
+
+    fn guess(&mut self, guess: Code) -> Reply {
+        BullsCows::guess(self, guess)
+    }
+
+    fn is_won(&self) -> bool {
+        BullsCows::is_won(self)
+    }
+
+    fn is_over(&self) -> bool {
+        BullsCows::is_over(self)
+    }
+}
+
+/// Knuth's minimax solver: every guess is the code whose worst answer leaves
+/// the fewest candidates, preferring codes that could still be the secret.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    all: Vec<Code>,
+    /// The codes consistent with every answer so far, in increasing order.
+    candidates: Vec<Code>,
+}
+
+impl Solver {
+    pub fn new(digits: usize) -> Solver {
+        let all = Code::all(digits);
+        Solver {
+            candidates: all.clone(),
+            all,
+        }
+    }
+
+    /// A solver that already knows the answers of `history`.
+    pub fn from_history(digits: usize, history: &[(Code, Score)]) -> Solver {
+        let mut solver = Solver::new(digits);
+        for &(guess, score) in history {
+            solver.learn(guess, score);
+        }
+        solver
+    }
+
+    /// Drops every candidate that wouldn't have given `score` to `guess`.
+    pub fn learn(&mut self, guess: Code, score: Score) {
+        self.candidates
+            .retain(|candidate| guess.score(candidate) == score);
+    }
+
+    pub fn candidates(&self) -> &[Code] {
+        &self.candidates
+    }
+
+    /// The next guess, `None` once the answers contradict each other.
+    pub fn next_guess(&self) -> Option<Code> {
+        best_guess(&self.all, &self.candidates)
+    }
+}
+
+/// How many scores [`best_guess`] may work out for one guess: enough to try
+/// every code against every candidate for up to four digits.
+const SCORE_BUDGET: usize = 8_000_000;
+
+fn best_guess(all: &[Code], candidates: &[Code]) -> Option<Code> {
+    match candidates {
+        [] => return None,
+        [only] => return Some(*only),
+        // before any answer every code is as good as any other
+        _ if candidates.len() == all.len() => return Some(all[0]),
+        _ => {}
+    }
+
+    // past the budget only the candidates are tried, and once even that is
+    // too much an evenly spread sample of them
+    let guesses = if all.len() * candidates.len() <= SCORE_BUDGET {
+        all
+    } else {
+        candidates
+    };
+    let step = (guesses.len() * candidates.len()).div_ceil(SCORE_BUDGET);
+
+    let mut best: Option<(usize, bool, Code)> = None;
+    'guesses: for &guess in guesses.iter().step_by(step) {
+        let mut sizes = [0usize; (MAX_DIGITS + 1) * (MAX_DIGITS + 1)];
+        let mut worst = 0;
+        for candidate in candidates {
+            let size = &mut sizes[guess.score(candidate).index()];
+            *size += 1;
+            worst = worst.max(*size);
+            // no better than the best so far, whatever the other candidates do
+            if best.is_some_and(|(best_worst, ..)| worst > best_worst) {
+                continue 'guesses;
+            }
+        }
+
+        let impossible = candidates.binary_search(&guess).is_err();
+        let better = match best {
+            None => true,
+            Some((best_worst, best_impossible, _)) => {
+                (worst, impossible).cmp(&(best_worst, best_impossible)) == Ordering::Less
+            }
+        };
+        if better {
+            best = Some((worst, impossible, guess));
+        }
+    }
+    best.map(|(.., guess)| guess)
+}
+
+/// Lets the [`Solver`] play against `secret` and returns every guess it made.
+pub fn solve(secret: Code) -> Vec<(Code, Score)> {
+    let mut solver = Solver::new(secret.len());
+    let mut history = Vec::new();
+
+    loop {
+        let guess = solver
+            .next_guess()
+            .expect("honest answers never contradict");
+        let score = guess.score(&secret);
+        history.push((guess, score));
+        if usize::from(score.bulls) == secret.len() {
+            return history;
+        }
+        solver.learn(guess, score);
+    }
+}
+
+/// The most guesses the [`Solver`] ever needs for codes of `digits` digits.
+///
+/// Walks the solver's whole decision tree once instead of playing every
+/// secret, which shares the work of all games that start out alike.
+pub fn worst_case(digits: usize) -> usize {
+    fn depth(all: &[Code], candidates: &[Code]) -> usize {
+        let guess = best_guess(all, candidates).expect("the candidates are never empty");
+
+        let mut answers: Vec<(Score, Vec<Code>)> = Vec::new();
+        for &candidate in candidates.iter().filter(|&&candidate| candidate != guess) {
+            let score = guess.score(&candidate);
+            match answers.iter_mut().find(|(answer, _)| *answer == score) {
+                Some((_, codes)) => codes.push(candidate),
+                None => answers.push((score, vec![candidate])),
+            }
+        }
+
+        1 + answers
+            .iter()
+            .map(|(_, codes)| depth(all, codes))
+            .max()
+            .unwrap_or(0)
+    }
+
+    let all = Code::all(digits);
+    depth(&all, &all)
+}
+
+impl<R: BufRead, W: Write> Frontend<BullsCows> for Console<R, W> {
+    fn read(&mut self) -> io::Result<Input<Code>> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(Input::End);
+        }
+
+        Ok(match session::parse_command(&line) {
+            Some(action) => Input::Action(action),
+            None => match line.parse() {
+                Ok(code) => Input::Action(session::Action::Guess(code)),
+                Err(message) => Input::Invalid(message),
+            },
+        })
+    }
+
+    fn prompt(&mut self, game: &BullsCows) -> io::Result<()> {
+        writeln!(self.output, "Please input a {}-digit code!", game.digits())
+    }
+
+    fn invalid(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.output, "{message}")
+    }
+
+    fn feedback(&mut self, game: &BullsCows, guess: Code, reply: Reply) -> io::Result<()> {
+        writeln!(self.output, "You guessed: {guess}")?;
+
+        match reply {
+            Reply::Scored(_) if game.is_won() => writeln!(self.output, "You win!"),
+            Reply::Scored(score) => writeln!(self.output, "{score}"),
+            Reply::WrongLength => {
+                writeln!(self.output, "The secret code has {} digits!", game.digits())
+            }
+            Reply::AlreadyGuessed => writeln!(self.output, "You already guessed {guess}!"),
+            Reply::GameOver => Ok(()),
+        }
+    }
+
+    fn hint(&mut self, game: &BullsCows) -> io::Result<()> {
+        let solver = Solver::from_history(game.digits(), game.history());
+        let left = solver.candidates().len();
+        let suggestion = solver
+            .next_guess()
+            .expect("the secret is always a candidate");
+
+        writeln!(
+            self.output,
+            "{left} code{} still possible, the solver would guess {suggestion}.",
+            if left == 1 { " is" } else { "s are" }
+        )
+    }
+
+    fn history(&mut self, game: &BullsCows) -> io::Result<()> {
+        if game.history().is_empty() {
+            return writeln!(self.output, "No guesses yet.");
+        }
+        for (attempt, (guess, score)) in game.history().iter().enumerate() {
+            writeln!(self.output, "{:>3}. {guess} {score}", attempt + 1)?;
+        }
+        Ok(())
+    }
+
+    fn help(&mut self) -> io::Result<()> {
+        writeln!(self.output, "{HELP}")
+    }
+
+    fn finish(&mut self, game: &BullsCows, outcome: Outcome) -> io::Result<()> {
+        match outcome {
+            Outcome::GaveUp => writeln!(self.output, "The secret code was {}.", game.secret()),
+            Outcome::Quit => writeln!(self.output, "Bye!"),
+            Outcome::EndOfInput => writeln!(self.output, "No more input, the game ends here."),
+            Outcome::Won | Outcome::Lost => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::seeded_rng;
+
+    fn code(s: &str) -> Code {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn scores_bulls_and_cows() {
+        assert_eq!(
+            code("1234").score(&code("1234")),
+            Score { bulls: 4, cows: 0 }
+        );
+        assert_eq!(
+            code("1243").score(&code("1234")),
+            Score { bulls: 2, cows: 2 }
+        );
+        assert_eq!(
+            code("5678").score(&code("1234")),
+            Score { bulls: 0, cows: 0 }
+        );
+        assert_eq!(
+            code("0219").score(&code("9210")),
+            Score { bulls: 2, cows: 2 }
+        );
+        assert_eq!(Score { bulls: 1, cows: 2 }.to_string(), "1 bull, 2 cows");
+    }
+
+    #[test]
+    fn parses_codes() {
+        assert_eq!(code("0123").digits(), &[0, 1, 2, 3]);
+        assert!("1123"
+            .parse::<Code>()
+            .unwrap_err()
+            .contains("repeats the digit 1"));
+        assert!("12a4".parse::<Code>().is_err());
+        assert!("".parse::<Code>().is_err());
+        assert_eq!(Code::all(4).len(), 5040);
+        assert_eq!(Code::all(1).len(), 10);
+    }
+
+    #[test]
+    fn plays_with_attempt_limits() {
+        let mut game = BullsCows::new(code("4071")).with_max_attempts(Some(2));
+
+        assert_eq!(game.guess(code("123")), Reply::WrongLength);
+        assert_eq!(
+            game.guess(code("1234")),
+            Reply::Scored(Score { bulls: 0, cows: 2 })
+        );
+        assert_eq!(game.guess(code("1234")), Reply::AlreadyGuessed);
+        assert_eq!(
+            game.guess(code("4071")),
+            Reply::Scored(Score { bulls: 4, cows: 0 })
+        );
+        assert!(game.is_won());
+        assert_eq!(game.guess(code("4170")), Reply::GameOver);
+    }
+
+    #[test]
+    fn seeding_picks_the_same_secret() {
+        let secret = |seed| BullsCows::with_rng(4, &mut seeded_rng(seed)).secret();
+        assert_eq!(secret(3), secret(3));
+        assert_eq!(secret(3).len(), 4);
+    }
+
+    #[test]
+    fn solver_wins_every_three_digit_game_quickly() {
+        let worst = worst_case(3);
+        assert!(worst <= 6, "needed {worst} guesses");
+
+        // the decision tree covers every secret, so a few games are enough here
+        for secret in Code::all(3).into_iter().step_by(97) {
+            let history = solve(secret);
+            assert!(history.len() <= worst);
+            assert_eq!(history.last().unwrap().0, secret);
+        }
+    }
+
+    #[test]
+    fn solver_wins_four_digit_games_within_seven_guesses() {
+        let mut rng = seeded_rng(11);
+        for _ in 0..3 {
+            let secret = Code::random(4, &mut rng);
+            assert!(solve(secret).len() <= 7, "{secret}");
+        }
+    }
+
+    #[test]
+    #[ignore = "walks the whole four digit decision tree, run with --ignored"]
+    fn solver_worst_case_for_four_digits() {
+        assert_eq!(worst_case(4), 7);
+    }
+
+    #[test]
+    fn suggests_a_guess_past_the_budget() {
+        // far too many codes to try each against every candidate
+        let secret = code("987654");
+        let mut solver = Solver::new(6);
+        for guess in [code("012345"), code("102354")] {
+            solver.learn(guess, guess.score(&secret));
+        }
+        assert!(Code::all(6).len() * solver.candidates().len() > SCORE_BUDGET);
+
+        let suggestion = solver.next_guess().unwrap();
+        assert!(solver.candidates().contains(&suggestion));
+    }
+
+    #[test]
+    fn plays_on_the_console() {
+        let mut game = BullsCows::new(code("4071"));
+        let mut output = Vec::new();
+        let outcome = session::run(
+            &mut game,
+            &mut Console {
+                input: "1234\n11\nhint\nhistory\n4071\n".as_bytes(),
+                output: &mut output,
+            },
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(outcome, Outcome::Won);
+        assert!(output.contains("You guessed: 1234\n0 bulls, 2 cows\n"));
+        assert!(output.contains("`11` repeats the digit 1"));
+        assert!(output.contains("codes are still possible, the solver would guess"));
+        assert!(output.contains("  1. 1234 0 bulls, 2 cows\n"));
+        assert!(output.ends_with("You win!\n"));
+    }
+}
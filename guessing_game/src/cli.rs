@@ -0,0 +1,751 @@
+use crate::bulls_cows::MAX_DIGITS;
+use crate::commit::{self, Commitment, Salt};
+use crate::config::{Config, ConfigError, Difficulty};
+use crate::date::Date;
+use crate::domain::BIG_LIMIT;
+use crate::policy::FeedbackPolicy;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+Usage: guessing_game [OPTIONS]
+       guessing_game verify <COMMITMENT> <SECRET> <SALT>
+
+Options:
+  -d, --difficulty <NAME>  easy (1..=10), normal (1..=100) or hard (1..=1000, 10 attempts)
+      --min <N>            smallest number the secret can be
+      --max <N>            largest number the secret can be
+      --attempts <N>       number of guesses before the game is lost
+      --feedback <POLICY>  classic (too small/too big), warmer (warmer/colder only)
+                           or heat (warmer/colder and how close you are)
+      --coach              grade every guess by the information it gains
+      --seed <N>           draw the secret from this seed to replay a game
+      --resume <FILE>      carry on with a game written by the `save` command
+      --record <FILE>      write every line of the game and when it came to FILE
+      --replay <FILE>      play a recorded game again, failing if anything changed
+      --daily              play today's challenge, the same for everyone (UTC)
+      --date <YYYY-MM-DD>  play the daily challenge of another day instead
+      --machine            talk in JSON lines, for bots and scripts
+      --bulls-cows         guess a code of distinct digits from bulls and cows
+      --digits <N>         how many digits the code has, 1 to 10 (default 4)
+      --lies <K>           the program may lie about higher or lower up to K times
+      --big <MAX>          guess a number from 1 to MAX, which may be as large as 10^30
+      --decimals <TOL>     guess a decimal number, winning within TOL of the secret
+      --words              guess a word, told whether it comes before or after yours
+      --reverse            you pick the number and the program guesses it
+      --simulate <GAMES>   let every solver bot play GAMES games and compare them
+      --tournament <FILE>  take turns at this terminal in the tournament set up in FILE
+      --host <PORT>        hold a race over the network, listening on PORT
+      --join <ADDRESS>     race in the game held at ADDRESS, like 192.168.1.20:7878
+      --serve <PORT>       play in a browser at http://localhost:PORT
+      --name <NAME>        your name on the leaderboard and to other players
+      --leaderboard        print the best scores of every difficulty
+  -h, --help               print this message
+
+`verify` checks the secret and salt revealed at the end of a game against the
+commitment printed at its start.
+";
+
+/// How many digits a Bulls and Cows code has unless `--digits` says otherwise.
+pub const DEFAULT_DIGITS: usize = 4;
+
+/// What the player asked the binary to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Play(Config),
+    /// Carry on with the game saved in this file.
+    Resume(Config, PathBuf),
+    /// Check the engine against the [`crate::transcript`] in this file.
+    Replay(PathBuf),
+    /// Play the puzzle of [`crate::daily`] for this day, or today if `None`.
+    Daily(Config, Option<Date>),
+    /// Play with the JSON lines interface of [`crate::machine`].
+    Machine(Config),
+    /// Let the bots of [`crate::strategy`] play this many games each.
+    Simulate(Config, usize),
+    /// Play the [`crate::tournament`] set up in this file.
+    Tournament(Config, PathBuf),
+    /// The player picks the secret and the program guesses it.
+    Reverse(Config),
+    /// Play Bulls and Cows of [`crate::bulls_cows`] with a code of this many digits.
+    BullsCows(Config, usize),
+    /// Play against an oracle of [`crate::liar`] that may lie this many times.
+    Lies(Config, u32),
+    /// Guess a [`crate::domain::Integers`] `u128` from 1 to this maximum.
+    Big(Config, u128),
+    /// Guess a [`crate::domain::Decimals`] to within this tolerance.
+    Decimals(Config, f64),
+    /// Guess a word of the [`crate::domain::Dictionary`].
+    Words(Config),
+    /// Hold a race for players on the network, listening on this port.
+    Host(Config, u16),
+    /// Serve the page and JSON API of [`crate::web`] on this port of localhost.
+    Serve(Config, u16),
+    /// Take part in a race held by another player.
+    Join {
+        address: String,
+        name: Option<String>,
+    },
+    /// Check a revealed secret and salt against the commitment of a game.
+    Verify {
+        commitment: Commitment,
+        secret: u32,
+        salt: Salt,
+    },
+    /// Print the best scores of [`crate::leaderboard`].
+    Leaderboard,
+    Help,
+}
+
+/// Parses the command line, without the program name.
+///
+/// `--min`, `--max` and `--attempts` override the matching parts of the
+/// chosen difficulty, whatever order they're given in. None of them go with
+/// `--daily`, whose puzzle is the same for every player.
+pub fn parse_args<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut difficulty = None;
+    let mut min = None;
+    let mut max = None;
+    let mut attempts = None;
+    let mut seed = None;
+    let mut feedback = None;
+    let mut name = None;
+    let mut digits = None;
+    let mut coach = false;
+    let mut record = None;
+    let mut date = None;
+    let mut mode = None;
+
+    let mut args = args.into_iter().peekable();
+    if args.peek().map(String::as_str) == Some("verify") {
+        args.next();
+        return parse_verify(args);
+    }
+
+    while let Some(arg) = args.next() {
+        // both `--min 5` and `--min=5` are accepted
+        let (option, inline_value) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => {
+                (option.to_string(), Some(value.to_string()))
+            }
+            _ => (arg, None),
+        };
+        let mut value = |option: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| CliError::MissingValue(option.to_string()))
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-d" | "--difficulty" => {
+                difficulty = Some(value("--difficulty")?.parse::<Difficulty>()?)
+            }
+            "--min" => min = Some(parse_number("--min", &value("--min")?)?),
+            "--max" => max = Some(parse_number("--max", &value("--max")?)?),
+            "--attempts" => attempts = Some(parse_number("--attempts", &value("--attempts")?)?),
+            "--seed" => seed = Some(parse_number("--seed", &value("--seed")?)?),
+            "--feedback" => feedback = Some(value("--feedback")?.parse()?),
+            "--coach" => coach = true,
+            "--name" => name = Some(value("--name")?),
+            "--machine" => set_mode(&mut mode, "--machine", Mode::Machine)?,
+            "--leaderboard" => set_mode(&mut mode, "--leaderboard", Mode::Leaderboard)?,
+            "--resume" => {
+                let file = value("--resume")?;
+                set_mode(&mut mode, "--resume", Mode::Resume(file.into()))?;
+            }
+            "--record" => record = Some(PathBuf::from(value("--record")?)),
+            "--replay" => {
+                let file = value("--replay")?;
+                set_mode(&mut mode, "--replay", Mode::Replay(file.into()))?;
+            }
+            "--daily" => set_mode(&mut mode, "--daily", Mode::Daily)?,
+            "--date" => {
+                let day = value("--date")?;
+                date = Some(day.parse().map_err(|_| CliError::InvalidDate(day))?);
+            }
+            "--reverse" => set_mode(&mut mode, "--reverse", Mode::Reverse)?,
+            "--bulls-cows" => set_mode(&mut mode, "--bulls-cows", Mode::BullsCows)?,
+            "--digits" => digits = Some(parse_number("--digits", &value("--digits")?)?),
+            "--lies" => {
+                let lies = parse_number("--lies", &value("--lies")?)?;
+                set_mode(&mut mode, "--lies", Mode::Lies(lies))?;
+            }
+            "--big" => {
+                let max = parse_number("--big", &value("--big")?)?;
+                set_mode(&mut mode, "--big", Mode::Big(max))?;
+            }
+            "--decimals" => {
+                let tolerance = parse_number("--decimals", &value("--decimals")?)?;
+                set_mode(&mut mode, "--decimals", Mode::Decimals(tolerance))?;
+            }
+            "--words" => set_mode(&mut mode, "--words", Mode::Words)?,
+            "--simulate" => {
+                let games = parse_number("--simulate", &value("--simulate")?)?;
+                set_mode(&mut mode, "--simulate", Mode::Simulate(games))?;
+            }
+            "--tournament" => {
+                let file = value("--tournament")?;
+                set_mode(&mut mode, "--tournament", Mode::Tournament(file.into()))?;
+            }
+            "--host" => {
+                let port = parse_number("--host", &value("--host")?)?;
+                set_mode(&mut mode, "--host", Mode::Host(port))?;
+            }
+            "--serve" => {
+                let port = parse_number("--serve", &value("--serve")?)?;
+                set_mode(&mut mode, "--serve", Mode::Serve(port))?;
+            }
+            "--join" => {
+                let address = value("--join")?;
+                set_mode(&mut mode, "--join", Mode::Join(address))?;
+            }
+            _ => return Err(CliError::UnknownOption(option)),
+        }
+    }
+
+    // the coach reasons about intervals, which only plain feedback gives away
+    if coach {
+        if let Some((option, _)) = mode
+            .as_ref()
+            .filter(|(_, mode)| !matches!(mode, Mode::Resume(_) | Mode::Daily))
+        {
+            return Err(CliError::Conflict(option, "--coach"));
+        }
+        if feedback.is_some_and(|feedback| feedback != FeedbackPolicy::Classic) {
+            return Err(CliError::Conflict("--feedback", "--coach"));
+        }
+    }
+
+    // only the plain console is recorded, the other modes talk in their own ways
+    if record.is_some() {
+        if let Some((option, _)) = mode
+            .as_ref()
+            .filter(|(_, mode)| !matches!(mode, Mode::Resume(_) | Mode::Daily))
+        {
+            return Err(CliError::Conflict(option, "--record"));
+        }
+    }
+
+    if matches!(mode, Some((_, Mode::Daily))) {
+        let puzzle_options = [
+            ("--difficulty", difficulty.is_some()),
+            ("--min", min.is_some()),
+            ("--max", max.is_some()),
+            ("--attempts", attempts.is_some()),
+            ("--seed", seed.is_some()),
+            ("--feedback", feedback.is_some()),
+        ];
+        if let Some((option, _)) = puzzle_options.into_iter().find(|&(_, given)| given) {
+            return Err(CliError::Conflict(option, "--daily"));
+        }
+    } else if date.is_some() {
+        return Err(CliError::Requires("--date", "--daily"));
+    }
+
+    let difficulty = difficulty.unwrap_or_default();
+    let range = difficulty.range();
+    let mut config = Config::new(
+        min.unwrap_or(*range.start()),
+        max.unwrap_or(*range.end()),
+        attempts.or(difficulty.max_attempts()),
+    )?;
+    if let Some(seed) = seed {
+        config = config.with_seed(seed);
+    }
+    if let Some(feedback) = feedback {
+        config = config.with_feedback(feedback);
+    }
+    config = config.with_coach(coach);
+    if let Some(record) = record {
+        config = config.with_record(record);
+    }
+    if let Some(name) = &name {
+        config = config.with_player(name.clone());
+    }
+
+    Ok(match mode {
+        None => Command::Play(config),
+        Some((_, Mode::Resume(file))) => Command::Resume(config, file),
+        Some((_, Mode::Replay(file))) => Command::Replay(file),
+        Some((_, Mode::Daily)) => Command::Daily(config, date),
+        Some((_, Mode::Machine)) => Command::Machine(config),
+        Some((_, Mode::Leaderboard)) => Command::Leaderboard,
+        Some((_, Mode::Reverse)) => Command::Reverse(config),
+        Some((_, Mode::BullsCows)) => match digits.unwrap_or(DEFAULT_DIGITS) {
+            digits @ 1..=MAX_DIGITS => Command::BullsCows(config, digits),
+            digits => return Err(CliError::InvalidDigits(digits)),
+        },
+        Some((_, Mode::Lies(lies))) => Command::Lies(config, lies),
+        Some((_, Mode::Big(max @ 1..=BIG_LIMIT))) => Command::Big(config, max),
+        Some((_, Mode::Big(max))) => return Err(CliError::InvalidBig(max)),
+        Some((_, Mode::Decimals(tolerance))) if tolerance > 0.0 && tolerance.is_finite() => {
+            Command::Decimals(config, tolerance)
+        }
+        Some((_, Mode::Decimals(tolerance))) => {
+            return Err(CliError::InvalidNumber {
+                option: "--decimals".to_string(),
+                value: tolerance.to_string(),
+            })
+        }
+        Some((_, Mode::Words)) => Command::Words(config),
+        Some((_, Mode::Simulate(0))) => return Err(CliError::NoGames),
+        Some((_, Mode::Simulate(games))) => Command::Simulate(config, games),
+        Some((_, Mode::Tournament(file))) => Command::Tournament(config, file),
+        Some((_, Mode::Host(port))) => Command::Host(config, port),
+        Some((_, Mode::Serve(port))) => Command::Serve(config, port),
+        Some((_, Mode::Join(address))) => Command::Join { address, name },
+    })
+}
+
+fn parse_verify(mut args: impl Iterator<Item = String>) -> Result<Command, CliError> {
+    let mut next = |what| args.next().ok_or(CliError::MissingArgument(what));
+    let commitment = next("commitment")?.parse()?;
+    let secret = parse_number("verify", &next("secret")?)?;
+    let salt = next("salt")?.parse()?;
+
+    match args.next() {
+        Some(extra) => Err(CliError::UnknownOption(extra)),
+        None => Ok(Command::Verify {
+            commitment,
+            secret,
+            salt,
+        }),
+    }
+}
+
+/// The options that pick something other than a normal game.
+enum Mode {
+    Resume(PathBuf),
+    Replay(PathBuf),
+    Daily,
+    Machine,
+    Leaderboard,
+    Reverse,
+    BullsCows,
+    Lies(u32),
+    Big(u128),
+    Decimals(f64),
+    Words,
+    Simulate(usize),
+    Tournament(PathBuf),
+    Host(u16),
+    Serve(u16),
+    Join(String),
+}
+
+fn set_mode(
+    mode: &mut Option<(&'static str, Mode)>,
+    option: &'static str,
+    new: Mode,
+) -> Result<(), CliError> {
+    match mode {
+        Some((earlier, _)) => Err(CliError::Conflict(earlier, option)),
+        None => {
+            *mode = Some((option, new));
+            Ok(())
+        }
+    }
+}
+
+fn parse_number<T: FromStr>(option: &str, value: &str) -> Result<T, CliError> {
+    value.parse().map_err(|_| CliError::InvalidNumber {
+        option: option.to_string(),
+        value: value.to_string(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidNumber {
+        option: String,
+        value: String,
+    },
+    /// `verify` is missing this argument.
+    MissingArgument(&'static str),
+    NoGames,
+    /// Not a date like `2024-02-29`.
+    InvalidDate(String),
+    /// A Bulls and Cows code can't have this many digits.
+    InvalidDigits(usize),
+    /// `--big` can't go up to this number.
+    InvalidBig(u128),
+    /// Two options that select different modes.
+    Conflict(&'static str, &'static str),
+    /// The first option only means something together with the second.
+    Requires(&'static str, &'static str),
+    Config(ConfigError),
+    Commit(commit::ParseError),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "unknown option `{option}`"),
+            CliError::MissingValue(option) => write!(f, "`{option}` needs a value"),
+            CliError::InvalidNumber { option, value } => {
+                write!(f, "`{value}` is not a valid number for `{option}`")
+            }
+            CliError::MissingArgument(what) => write!(f, "`verify` needs the {what}"),
+            CliError::NoGames => write!(f, "`--simulate` needs at least one game"),
+            CliError::InvalidDate(date) => {
+                write!(f, "`{date}` is not a date like 2024-02-29 for `--date`")
+            }
+            CliError::InvalidDigits(digits) => {
+                write!(f, "codes have 1 to {MAX_DIGITS} digits, not {digits}")
+            }
+            CliError::InvalidBig(max) => {
+                write!(f, "`--big` needs a maximum from 1 to 10^30, not {max}")
+            }
+            CliError::Conflict(first, second) => {
+                write!(f, "`{first}` and `{second}` can't be used together")
+            }
+            CliError::Requires(option, needs) => {
+                write!(f, "`{option}` only works together with `{needs}`")
+            }
+            CliError::Config(error) => error.fmt(f),
+            CliError::Commit(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CliError::Config(error) => Some(error),
+            CliError::Commit(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for CliError {
+    fn from(error: ConfigError) -> CliError {
+        CliError::Config(error)
+    }
+}
+
+impl From<commit::ParseError> for CliError {
+    fn from(error: commit::ParseError) -> CliError {
+        CliError::Commit(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults_to_easy() {
+        assert_eq!(parse(&[]), Ok(Command::Play(Difficulty::Easy.config())));
+    }
+
+    #[test]
+    fn options_override_the_preset() {
+        let expected = Config::new(1, 50, Some(10)).unwrap();
+        assert_eq!(
+            parse(&["--max", "50", "--difficulty", "hard"]),
+            Ok(Command::Play(expected.clone()))
+        );
+        assert_eq!(
+            parse(&["--difficulty=hard", "--max=50"]),
+            Ok(Command::Play(expected))
+        );
+    }
+
+    #[test]
+    fn accepts_a_seed() {
+        let expected = Difficulty::Normal.config().with_seed(u64::MAX);
+        assert_eq!(
+            parse(&["--seed", "18446744073709551615", "-d", "normal"]),
+            Ok(Command::Play(expected))
+        );
+    }
+
+    #[test]
+    fn selects_the_simulation() {
+        let expected = Difficulty::Hard.config().with_seed(1);
+        assert_eq!(
+            parse(&["--simulate", "500", "-d", "hard", "--seed", "1"]),
+            Ok(Command::Simulate(expected, 500))
+        );
+        assert_eq!(parse(&["--simulate", "0"]), Err(CliError::NoGames));
+        assert_eq!(
+            parse(&["--tournament", "cup.json", "--seed", "4"]),
+            Ok(Command::Tournament(
+                Difficulty::Easy.config().with_seed(4),
+                PathBuf::from("cup.json")
+            ))
+        );
+        assert_eq!(
+            parse(&["--reverse", "--simulate", "5"]),
+            Err(CliError::Conflict("--reverse", "--simulate"))
+        );
+    }
+
+    #[test]
+    fn selects_the_network_modes() {
+        assert_eq!(
+            parse(&["--host", "7878", "-d", "normal"]),
+            Ok(Command::Host(Difficulty::Normal.config(), 7878))
+        );
+        assert_eq!(
+            parse(&["--name", "ferris", "--join", "localhost:7878"]),
+            Ok(Command::Join {
+                address: "localhost:7878".to_string(),
+                name: Some("ferris".to_string())
+            })
+        );
+        assert_eq!(
+            parse(&["--serve=8080", "--feedback", "heat"]),
+            Ok(Command::Serve(
+                Difficulty::Easy
+                    .config()
+                    .with_feedback(FeedbackPolicy::Heat),
+                8080
+            ))
+        );
+        assert!(matches!(
+            parse(&["--host", "70000"]),
+            Err(CliError::InvalidNumber { .. })
+        ));
+    }
+
+    #[test]
+    fn selects_bulls_and_cows() {
+        let expected = Difficulty::Hard.config().with_seed(9);
+        assert_eq!(
+            parse(&["--bulls-cows", "-d", "hard", "--seed", "9"]),
+            Ok(Command::BullsCows(expected, DEFAULT_DIGITS))
+        );
+        assert_eq!(
+            parse(&["--digits=3", "--bulls-cows"]),
+            Ok(Command::BullsCows(Difficulty::Easy.config(), 3))
+        );
+        assert_eq!(
+            parse(&["--bulls-cows", "--digits", "11"]),
+            Err(CliError::InvalidDigits(11))
+        );
+    }
+
+    #[test]
+    fn selects_the_lying_oracle() {
+        assert_eq!(
+            parse(&["--lies", "2", "-d", "normal"]),
+            Ok(Command::Lies(Difficulty::Normal.config(), 2))
+        );
+        assert_eq!(
+            parse(&["--lies=1", "--bulls-cows"]),
+            Err(CliError::Conflict("--lies", "--bulls-cows"))
+        );
+    }
+
+    #[test]
+    fn selects_other_secret_types() {
+        assert_eq!(
+            parse(&["--big", "1000000000000000000000000000000"]),
+            Ok(Command::Big(Difficulty::Easy.config(), BIG_LIMIT))
+        );
+        assert_eq!(parse(&["--big", "0"]), Err(CliError::InvalidBig(0)));
+        assert_eq!(
+            parse(&["--big", "1000000000000000000000000000001"]),
+            Err(CliError::InvalidBig(BIG_LIMIT + 1))
+        );
+        assert_eq!(
+            parse(&["--decimals=0.01", "-d", "normal"]),
+            Ok(Command::Decimals(Difficulty::Normal.config(), 0.01))
+        );
+        for tolerance in ["0", "-1", "inf", "NaN", "abc"] {
+            assert!(matches!(
+                parse(&["--decimals", tolerance]),
+                Err(CliError::InvalidNumber { .. })
+            ));
+        }
+        assert_eq!(
+            parse(&["--words", "--attempts", "8"]),
+            Ok(Command::Words(Config::new(1, 10, Some(8)).unwrap()))
+        );
+        assert_eq!(
+            parse(&["--words", "--big", "5"]),
+            Err(CliError::Conflict("--words", "--big"))
+        );
+    }
+
+    #[test]
+    fn parses_verify() {
+        let commitment = "0b8335e513511534b02a81ad47c65449ac96fad25e5fbc4f2b703f4d380ae567";
+        let salt = "000102030405060708090a0b0c0d0e0f";
+        assert_eq!(
+            parse(&["verify", commitment, "7", salt]),
+            Ok(Command::Verify {
+                commitment: commitment.parse().unwrap(),
+                secret: 7,
+                salt: salt.parse().unwrap(),
+            })
+        );
+        assert_eq!(
+            parse(&["verify", commitment, "7"]),
+            Err(CliError::MissingArgument("salt"))
+        );
+        assert!(matches!(
+            parse(&["verify", "00ff", "7", salt]),
+            Err(CliError::Commit(_))
+        ));
+        assert_eq!(
+            parse(&["verify", commitment, "7", salt, "extra"]),
+            Err(CliError::UnknownOption("extra".to_string()))
+        );
+    }
+
+    #[test]
+    fn turns_on_the_coach() {
+        let expected = Difficulty::Normal.config().with_coach(true);
+        assert_eq!(
+            parse(&["--coach", "-d", "normal", "--feedback", "classic"]),
+            Ok(Command::Play(expected))
+        );
+        assert_eq!(
+            parse(&["--coach", "--feedback", "heat"]),
+            Err(CliError::Conflict("--feedback", "--coach"))
+        );
+        assert_eq!(
+            parse(&["--machine", "--coach"]),
+            Err(CliError::Conflict("--machine", "--coach"))
+        );
+    }
+
+    #[test]
+    fn records_and_replays_transcripts() {
+        let expected = Difficulty::Easy
+            .config()
+            .with_record(PathBuf::from("game.jsonl"));
+        assert_eq!(
+            parse(&["--record", "game.jsonl"]),
+            Ok(Command::Play(expected.clone()))
+        );
+        assert_eq!(
+            parse(&["--daily", "--record=game.jsonl"]),
+            Ok(Command::Daily(expected, None))
+        );
+        assert_eq!(
+            parse(&["--record", "game.jsonl", "--bulls-cows"]),
+            Err(CliError::Conflict("--bulls-cows", "--record"))
+        );
+        assert_eq!(
+            parse(&["--replay", "game.jsonl"]),
+            Ok(Command::Replay(PathBuf::from("game.jsonl")))
+        );
+        assert_eq!(
+            parse(&["--replay", "game.jsonl", "--resume", "a.save"]),
+            Err(CliError::Conflict("--replay", "--resume"))
+        );
+        assert_eq!(
+            parse(&["--replay"]),
+            Err(CliError::MissingValue("--replay".to_string()))
+        );
+    }
+
+    #[test]
+    fn resumes_a_saved_game() {
+        let expected = Difficulty::Easy.config().with_coach(true);
+        assert_eq!(
+            parse(&["--resume", "my game.save", "--coach"]),
+            Ok(Command::Resume(expected, PathBuf::from("my game.save")))
+        );
+        assert_eq!(
+            parse(&["--resume=a.save", "--reverse"]),
+            Err(CliError::Conflict("--resume", "--reverse"))
+        );
+    }
+
+    #[test]
+    fn selects_the_daily_challenge() {
+        let expected = Difficulty::Easy.config().with_coach(true);
+        assert_eq!(
+            parse(&["--daily", "--coach"]),
+            Ok(Command::Daily(expected, None))
+        );
+        assert_eq!(
+            parse(&["--date=2024-02-29", "--daily"]),
+            Ok(Command::Daily(
+                Difficulty::Easy.config(),
+                Date::new(2024, 2, 29)
+            ))
+        );
+        assert_eq!(
+            parse(&["--daily", "--date", "2023-02-29"]),
+            Err(CliError::InvalidDate("2023-02-29".to_string()))
+        );
+        assert_eq!(
+            parse(&["--date", "2024-02-29"]),
+            Err(CliError::Requires("--date", "--daily"))
+        );
+        assert_eq!(
+            parse(&["--daily", "-d", "hard"]),
+            Err(CliError::Conflict("--difficulty", "--daily"))
+        );
+        assert_eq!(
+            parse(&["--seed", "3", "--daily"]),
+            Err(CliError::Conflict("--seed", "--daily"))
+        );
+    }
+
+    #[test]
+    fn names_the_player() {
+        let expected = Difficulty::Easy
+            .config()
+            .with_player("Ada Lovelace".to_string());
+        assert_eq!(
+            parse(&["--name", "Ada Lovelace"]),
+            Ok(Command::Play(expected))
+        );
+        assert_eq!(parse(&["--leaderboard"]), Ok(Command::Leaderboard));
+    }
+
+    #[test]
+    fn picks_a_feedback_policy() {
+        let expected = Difficulty::Easy
+            .config()
+            .with_feedback(FeedbackPolicy::Heat);
+        assert_eq!(parse(&["--feedback", "heat"]), Ok(Command::Play(expected)));
+        assert_eq!(
+            parse(&["--feedback", "tepid"]),
+            Err(ConfigError::UnknownFeedback("tepid".to_string()).into())
+        );
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        assert_eq!(
+            parse(&["--min"]),
+            Err(CliError::MissingValue("--min".to_string()))
+        );
+        assert_eq!(
+            parse(&["--attempts", "-3"]),
+            Err(CliError::InvalidNumber {
+                option: "--attempts".to_string(),
+                value: "-3".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["--min", "20"]),
+            Err(ConfigError::MinAboveMax { min: 20, max: 10 }.into())
+        );
+        assert_eq!(
+            parse(&["--fast"]),
+            Err(CliError::UnknownOption("--fast".to_string()))
+        );
+    }
+}
@@ -0,0 +1,478 @@
+//! A coach that grades every guess by the information it gains, to teach the
+//! binary search intuition the game is about.
+//!
+//! Before a guess the secret can be any of the `n` numbers still consistent
+//! with earlier feedback. A guess splits them into the numbers below it, the
+//! guess itself and the numbers above it, and the feedback says which part
+//! the secret is in. On average that tells the player the entropy of the split
+//! in bits, which is largest for a guess in the middle: a little over one bit,
+//! up to `log2(3)` with three numbers left. The coach compares every guess
+//! with that best one rather than with its lucky or unlucky outcome.
+
+use crate::game::{Feedback, Game};
+use crate::session::{Console, Frontend, Input, Outcome};
+use crate::strategy::optimal_worst_case;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// What the coach thinks of one guess.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Grade {
+    /// A guess inside the numbers still possible.
+    Useful {
+        /// The bits the guess was worth on average.
+        expected: f64,
+        /// The bits the best guess was worth on average.
+        best: f64,
+        best_guess: u32,
+        /// The bits the feedback actually gave.
+        gained: f64,
+    },
+    /// A guess the earlier feedback had already ruled out.
+    OutsideBounds {
+        guess: u32,
+        bounds: RangeInclusive<u32>,
+    },
+    Repeated(u32),
+    /// A guess outside the range of the game, which isn't even counted.
+    OutOfRange(u32),
+}
+
+impl Grade {
+    pub fn is_wasted(&self) -> bool {
+        !matches!(self, Grade::Useful { .. })
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Grade::Useful {
+                expected,
+                best,
+                best_guess,
+                gained,
+            } => {
+                if expected + 1e-9 >= *best {
+                    write!(f, "Coach: a perfect guess, worth {best:.2} bits on average.")?;
+                } else {
+                    write!(
+                        f,
+                        "Coach: worth {expected:.2} bits on average, {best_guess} would have been worth {best:.2}."
+                    )?;
+                }
+                write!(f, " This time it told you {gained:.2} bits.")
+            }
+            Grade::OutsideBounds { guess, bounds } => write!(
+                f,
+                "Coach: wasted, the feedback so far already put the secret between {} and {}, so {guess} told you nothing.",
+                bounds.start(),
+                bounds.end()
+            ),
+            Grade::Repeated(guess) => {
+                write!(f, "Coach: wasted, you already knew the answer for {guess}.")
+            }
+            Grade::OutOfRange(guess) => {
+                write!(f, "Coach: wasted, {guess} can't be the secret at all.")
+            }
+        }
+    }
+}
+
+/// How many numbers `bounds` holds, as a float for the logarithms.
+fn size(bounds: &RangeInclusive<u32>) -> f64 {
+    f64::from(bounds.end() - bounds.start()) + 1.0
+}
+
+/// The bits a guess at `guess` is worth on average while the secret is
+/// anywhere in `bounds`, the entropy of the three ways it splits them.
+pub fn expected_bits(bounds: &RangeInclusive<u32>, guess: u32) -> f64 {
+    if !bounds.contains(&guess) {
+        return 0.0;
+    }
+
+    let n = size(bounds);
+    let below = f64::from(guess - bounds.start());
+    let above = f64::from(bounds.end() - guess);
+    [below, 1.0, above]
+        .into_iter()
+        .filter(|&part| part > 0.0)
+        .map(|part| part / n * (n / part).log2())
+        .sum()
+}
+
+/// The guess worth the most bits in `bounds`, the middle one.
+pub fn best_guess(bounds: &RangeInclusive<u32>) -> u32 {
+    bounds.start() + (bounds.end() - bounds.start()) / 2
+}
+
+/// Grades the guesses of one game as they come.
+#[derive(Debug, Clone)]
+pub struct Coach {
+    range: RangeInclusive<u32>,
+    bounds: RangeInclusive<u32>,
+    guessed: Vec<u32>,
+    grades: Vec<Grade>,
+    /// The sums of the expected and the best bits over every guess.
+    expected: f64,
+    best: f64,
+}
+
+impl Coach {
+    pub fn new(range: RangeInclusive<u32>) -> Coach {
+        Coach {
+            bounds: range.clone(),
+            range,
+            guessed: Vec::new(),
+            grades: Vec::new(),
+            expected: 0.0,
+            best: 0.0,
+        }
+    }
+
+    /// The numbers the secret can still be, as far as the player knows.
+    pub fn bounds(&self) -> &RangeInclusive<u32> {
+        &self.bounds
+    }
+
+    /// Grades `guess`, which got `feedback`, against what the player knew before it.
+    pub fn grade(&mut self, guess: u32, feedback: Feedback) -> &Grade {
+        let best_guess = best_guess(&self.bounds);
+        let best = expected_bits(&self.bounds, best_guess);
+
+        let grade = if !self.range.contains(&guess) {
+            Grade::OutOfRange(guess)
+        } else if self.guessed.contains(&guess) {
+            Grade::Repeated(guess)
+        } else if !self.bounds.contains(&guess) {
+            Grade::OutsideBounds {
+                guess,
+                bounds: self.bounds.clone(),
+            }
+        } else {
+            let before = size(&self.bounds);
+            let (low, high) = (*self.bounds.start(), *self.bounds.end());
+            self.bounds = match feedback {
+                Feedback::TooSmall => guess + 1..=high,
+                Feedback::TooBig => low..=guess - 1,
+                _ => guess..=guess,
+            };
+            Grade::Useful {
+                expected: expected_bits(&(low..=high), guess),
+                best,
+                best_guess,
+                gained: (before / size(&self.bounds)).log2(),
+            }
+        };
+
+        if !matches!(grade, Grade::OutOfRange(_) | Grade::Repeated(_)) {
+            self.guessed.push(guess);
+        }
+        if let Grade::Useful { expected, .. } = grade {
+            self.expected += expected;
+        }
+        self.best += best;
+        self.grades.push(grade);
+        self.grades.last().expect("a grade was just pushed")
+    }
+
+    pub fn grades(&self) -> &[Grade] {
+        &self.grades
+    }
+
+    /// The report card for the whole game.
+    pub fn report(&self) -> Report {
+        Report {
+            guesses: self.grades.len(),
+            wasted: self.grades.iter().filter(|grade| grade.is_wasted()).count(),
+            efficiency: if self.best > 0.0 {
+                self.expected / self.best
+            } else {
+                1.0
+            },
+            binary_search: optimal_worst_case(u64::from(self.range.end() - self.range.start()) + 1),
+        }
+    }
+}
+
+/// How well a whole game was played.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report {
+    pub guesses: usize,
+    pub wasted: usize,
+    /// The bits the guesses were worth on average over what the best
+    /// guesses would have been worth, from 0 to 1.
+    pub efficiency: f64,
+    /// The most guesses binary search ever needs on the game's range.
+    pub binary_search: u32,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Coach: {:.0}% efficient over {} guess{}",
+            self.efficiency * 100.0,
+            self.guesses,
+            if self.guesses == 1 { "" } else { "es" }
+        )?;
+        if self.wasted > 0 {
+            write!(f, ", {} of them wasted", self.wasted)?;
+        }
+        write!(
+            f,
+            ". Always guessing the middle finds any secret in this range within {} guesses.",
+            self.binary_search
+        )
+    }
+}
+
+/// Why `game` can't be coached, if it can't. The coach grades guesses by the
+/// numbers the feedback leaves, so under a policy that hides which way the
+/// guesses were off its grades would give exactly that away.
+pub fn refusal(game: &Game) -> Option<String> {
+    (!game.policy().reveals_direction()).then(|| {
+        format!(
+            "the coach can't help with {} feedback, it would tell which way the guesses were off",
+            game.policy()
+        )
+    })
+}
+
+/// The console with a [`Coach`] speaking up after every guess.
+pub struct Coached<R, W> {
+    pub console: Console<R, W>,
+    pub coach: Coach,
+}
+
+impl<R: BufRead, W: Write> Coached<R, W> {
+    /// Coaches `game` on `console`, grading the guesses it already has
+    /// first, as when it was resumed from a save.
+    ///
+    /// # Panics
+    ///
+    /// If [`refusal`] has a reason not to coach `game`.
+    pub fn new(console: Console<R, W>, game: &Game) -> Coached<R, W> {
+        if let Some(reason) = refusal(game) {
+            panic!("{reason}");
+        }
+        let mut coach = Coach::new(game.range().clone());
+        for &guess in game.history() {
+            coach.grade(guess, Feedback::from(guess.cmp(&game.secret())));
+        }
+        Coached { console, coach }
+    }
+
+    /// The console as the front-end of a number game, which it is for more than one game.
+    fn inner(&mut self) -> &mut dyn Frontend {
+        &mut self.console
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for Coached<R, W> {
+    fn read(&mut self) -> io::Result<Input> {
+        self.inner().read()
+    }
+
+    fn prompt(&mut self, game: &Game) -> io::Result<()> {
+        self.inner().prompt(game)
+    }
+
+    fn invalid(&mut self, message: &str) -> io::Result<()> {
+        self.inner().invalid(message)
+    }
+
+    fn feedback(&mut self, game: &Game, guess: u32, feedback: Feedback) -> io::Result<()> {
+        self.inner().feedback(game, guess, feedback)?;
+        if feedback == Feedback::GameOver {
+            return Ok(());
+        }
+        let grade = self.coach.grade(guess, feedback);
+        writeln!(self.console.output, "{grade}")
+    }
+
+    fn hint(&mut self, game: &Game) -> io::Result<()> {
+        self.inner().hint(game)
+    }
+
+    fn history(&mut self, game: &Game) -> io::Result<()> {
+        self.inner().history(game)
+    }
+
+    fn help(&mut self) -> io::Result<()> {
+        self.inner().help()
+    }
+
+    fn save(&mut self, game: &Game, path: &Path) -> io::Result<()> {
+        self.inner().save(game, path)
+    }
+
+    fn finish(&mut self, game: &Game, outcome: Outcome) -> io::Result<()> {
+        self.inner().finish(game, outcome)?;
+        if matches!(outcome, Outcome::Won | Outcome::Lost) {
+            writeln!(self.console.output, "{}", self.coach.report())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::FeedbackPolicy;
+    use crate::save::SavedGame;
+    use crate::session;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn the_middle_is_worth_the_most() {
+        let bounds = 1..=100;
+        let best = expected_bits(&bounds, best_guess(&bounds));
+        for guess in bounds.clone() {
+            assert!(expected_bits(&bounds, guess) <= best + 1e-12);
+        }
+        assert!(close(expected_bits(&(1..=3), 2), 3f64.log2()));
+        assert!(close(expected_bits(&(1..=2), 1), 1.0));
+        assert!(close(expected_bits(&(5..=5), 5), 0.0));
+        assert!(close(expected_bits(&(1..=10), 11), 0.0));
+    }
+
+    #[test]
+    fn grades_guesses_against_the_best() {
+        let mut coach = Coach::new(1..=7);
+
+        let grade = coach.grade(4, Feedback::TooSmall);
+        assert!(matches!(grade, Grade::Useful { expected, best, .. } if close(*expected, *best)));
+        assert_eq!(coach.bounds(), &(5..=7));
+        let grade = coach.grade(5, Feedback::TooSmall).clone();
+        let Grade::Useful {
+            expected,
+            best,
+            best_guess,
+            gained,
+        } = grade
+        else {
+            panic!("{grade:?}");
+        };
+        assert!(expected < best);
+        assert_eq!(best_guess, 6);
+        assert!(close(gained, 3f64.log2() - 1.0));
+    }
+
+    #[test]
+    fn flags_wasted_guesses() {
+        let mut coach = Coach::new(1..=100);
+        coach.grade(50, Feedback::TooBig);
+
+        assert_eq!(
+            coach.grade(70, Feedback::TooBig),
+            &Grade::OutsideBounds {
+                guess: 70,
+                bounds: 1..=49
+            }
+        );
+        assert_eq!(
+            coach.grade(50, Feedback::AlreadyGuessed),
+            &Grade::Repeated(50)
+        );
+        assert_eq!(
+            coach.grade(70, Feedback::AlreadyGuessed),
+            &Grade::Repeated(70)
+        );
+        assert_eq!(coach.grade(0, Feedback::OutOfRange), &Grade::OutOfRange(0));
+
+        let report = coach.report();
+        assert_eq!(report.guesses, 5);
+        assert_eq!(report.wasted, 4);
+        assert!(report.efficiency < 0.25);
+    }
+
+    #[test]
+    fn binary_search_is_fully_efficient() {
+        let mut game = Game::new(37, 1..=100);
+        let mut coach = Coach::new(1..=100);
+        loop {
+            let guess = best_guess(coach.bounds());
+            let feedback = game.guess(guess);
+            coach.grade(guess, feedback);
+            if game.is_won() {
+                break;
+            }
+        }
+
+        let report = coach.report();
+        assert!(close(report.efficiency, 1.0));
+        assert_eq!(report.wasted, 0);
+        assert!(report.guesses as u32 <= report.binary_search);
+    }
+
+    #[test]
+    fn speaks_up_on_the_console() {
+        let mut game = Game::new(3, 1..=10);
+        let mut output = Vec::new();
+        let console = Console {
+            input: "9\n10\n1\n3\n".as_bytes(),
+            output: &mut output,
+        };
+        let mut coached = Coached::new(console, &game);
+        let outcome = session::run(&mut game, &mut coached).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(outcome, Outcome::Won);
+        assert!(output.contains(
+            "Too big!\nCoach: worth 0.92 bits on average, 5 would have been worth 1.36."
+        ));
+        assert!(output
+            .contains("Coach: wasted, the feedback so far already put the secret between 1 and 8"));
+        assert!(output.ends_with(
+            "Coach: 47% efficient over 4 guesses, 1 of them wasted. \
+             Always guessing the middle finds any secret in this range within 4 guesses.\n"
+        ));
+    }
+
+    #[test]
+    fn catches_up_on_a_resumed_game() {
+        let mut game = Game::new(30, 1..=100);
+        game.guess(50);
+        game.guess(20);
+        let console = Console {
+            input: io::empty(),
+            output: io::sink(),
+        };
+
+        let coached = Coached::new(console, &game);
+        assert_eq!(coached.coach.bounds(), &(21..=49));
+        assert_eq!(coached.coach.grades().len(), 2);
+    }
+
+    /// A game with `policy` feedback, saved after a guess and resumed.
+    fn resumed(policy: FeedbackPolicy) -> Game {
+        let mut game = Game::new(4, 1..=10).with_policy(policy).with_seed(1);
+        game.guess(8);
+        let text = SavedGame::new(&game).unwrap().to_json().to_string();
+        SavedGame::parse(&text).unwrap().restore().unwrap()
+    }
+
+    #[test]
+    fn refuses_resumed_games_that_hide_the_direction() {
+        for policy in [FeedbackPolicy::WarmerColder, FeedbackPolicy::Heat] {
+            assert!(refusal(&resumed(policy)).is_some(), "{policy}");
+        }
+        assert_eq!(refusal(&resumed(FeedbackPolicy::Classic)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "the coach can't help with")]
+    fn wont_coach_a_resumed_heat_game() {
+        let console = Console {
+            input: io::empty(),
+            output: io::sink(),
+        };
+        Coached::new(console, &resumed(FeedbackPolicy::Heat));
+    }
+}
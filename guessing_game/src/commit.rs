@@ -0,0 +1,153 @@
+//! Commit-reveal for the secret number, so players can check the game never
+//! changed it mid-game.
+//!
+//! At the start the game prints the SHA-256 of `"{secret}:{salt}"`, the salt
+//! being 16 random bytes in lowercase hex. At the end it reveals both, and
+//! anyone can recompute the hash, with `guessing_game verify` or with
+//! `printf '%s' '42:<salt>' | sha256sum`. The salt keeps the commitment from
+//! being matched against the hashes of every number in the range.
+
+use crate::sha256::{self, from_hex, to_hex};
+use rand::Rng;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The random part of a commitment, only revealed at the end of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Salt([u8; 16]);
+
+impl Salt {
+    /// Draws a salt from `rng`, which shouldn't be the seeded RNG the secret
+    /// comes from, or the seed would give the salt away.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Salt {
+        Salt(rng.gen())
+    }
+
+    pub fn from_bytes(bytes: [u8; 16]) -> Salt {
+        Salt(bytes)
+    }
+
+    pub fn bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Salt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&to_hex(&self.0))
+    }
+}
+
+impl FromStr for Salt {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Salt, ParseError> {
+        parse_hex(s, "salt").map(Salt)
+    }
+}
+
+/// The published hash of a secret and its salt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Commitment([u8; 32]);
+
+impl Commitment {
+    pub fn new(secret: u32, salt: &Salt) -> Commitment {
+        Commitment(sha256::digest(format!("{secret}:{salt}").as_bytes()))
+    }
+
+    /// Whether `secret` and `salt` are what this commitment was made to.
+    pub fn verify(&self, secret: u32, salt: &Salt) -> bool {
+        Commitment::new(secret, salt) == *self
+    }
+
+    pub fn bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&to_hex(&self.0))
+    }
+}
+
+impl FromStr for Commitment {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Commitment, ParseError> {
+        parse_hex(s, "commitment").map(Commitment)
+    }
+}
+
+fn parse_hex<const N: usize>(s: &str, what: &'static str) -> Result<[u8; N], ParseError> {
+    from_hex(s.trim())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ParseError {
+            what,
+            digits: 2 * N,
+            value: s.to_string(),
+        })
+}
+
+/// A commitment or salt that isn't the right number of hex digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    what: &'static str,
+    digits: usize,
+    value: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not a {}, it should be {} hex digits",
+            self.value, self.what, self.digits
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::seeded_rng;
+
+    #[test]
+    fn hashes_the_secret_and_salt() {
+        let salt: Salt = "000102030405060708090a0b0c0d0e0f".parse().unwrap();
+        // printf '%s' '7:000102030405060708090a0b0c0d0e0f' | sha256sum
+        assert_eq!(
+            Commitment::new(7, &salt).to_string(),
+            "0b8335e513511534b02a81ad47c65449ac96fad25e5fbc4f2b703f4d380ae567"
+        );
+    }
+
+    #[test]
+    fn verifies_only_the_committed_pair() {
+        let mut rng = seeded_rng(5);
+        let salt = Salt::random(&mut rng);
+        let other = Salt::random(&mut rng);
+        let commitment = Commitment::new(42, &salt);
+
+        let parsed: Commitment = commitment.to_string().parse().unwrap();
+        assert!(parsed.verify(42, &salt.to_string().parse().unwrap()));
+        assert!(!commitment.verify(43, &salt));
+        assert!(!commitment.verify(42, &other));
+    }
+
+    #[test]
+    fn rejects_malformed_hex() {
+        let error = "abcd".parse::<Commitment>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`abcd` is not a commitment, it should be 64 hex digits"
+        );
+        assert!("000102030405060708090a0b0c0d0e0g".parse::<Salt>().is_err());
+        assert!(" 000102030405060708090A0B0C0D0E0F\n"
+            .parse::<Salt>()
+            .is_ok());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::policy::FeedbackPolicy;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The named presets players can pick instead of spelling out every option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    #[default]
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn range(self) -> RangeInclusive<u32> {
+        match self {
+            Difficulty::Easy => 1..=10,
+            Difficulty::Normal => 1..=100,
+            Difficulty::Hard => 1..=1000,
+        }
+    }
+
+    /// Hard leaves exactly enough attempts for a perfect binary search.
+    pub fn max_attempts(self) -> Option<u32> {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => None,
+            Difficulty::Hard => Some(10),
+        }
+    }
+
+    pub fn config(self) -> Config {
+        let range = self.range();
+        Config::new(*range.start(), *range.end(), self.max_attempts())
+            .expect("the presets are valid configurations")
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Difficulty, ConfigError> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ConfigError::UnknownDifficulty(s.to_string()))
+    }
+}
+
+/// Everything needed to set up a game, checked before the game starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    range: RangeInclusive<u32>,
+    max_attempts: Option<u32>,
+    seed: Option<u64>,
+    player: Option<String>,
+    feedback: FeedbackPolicy,
+    coach: bool,
+    record: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn new(min: u32, max: u32, max_attempts: Option<u32>) -> Result<Config, ConfigError> {
+        if min > max {
+            return Err(ConfigError::MinAboveMax { min, max });
+        }
+        if min == max {
+            return Err(ConfigError::SingleNumber(min));
+        }
+        if max_attempts == Some(0) {
+            return Err(ConfigError::ZeroAttempts);
+        }
+
+        Ok(Config {
+            range: min..=max,
+            max_attempts,
+            seed: None,
+            player: None,
+            feedback: FeedbackPolicy::default(),
+            coach: false,
+            record: None,
+        })
+    }
+
+    /// Fixes the seed the secret is drawn with, so the game can be replayed.
+    pub fn with_seed(mut self, seed: u64) -> Config {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Names the player on the leaderboard and to other players.
+    pub fn with_player(mut self, player: String) -> Config {
+        self.player = Some(player);
+        self
+    }
+
+    /// Picks how missed guesses are described to the player.
+    pub fn with_feedback(mut self, feedback: FeedbackPolicy) -> Config {
+        self.feedback = feedback;
+        self
+    }
+
+    /// Has the [`crate::coach`] grade every guess of a console game.
+    pub fn with_coach(mut self, coach: bool) -> Config {
+        self.coach = coach;
+        self
+    }
+
+    /// Writes a [`crate::transcript`] of a console game to `path`.
+    pub fn with_record(mut self, path: PathBuf) -> Config {
+        self.record = Some(path);
+        self
+    }
+
+    pub fn range(&self) -> &RangeInclusive<u32> {
+        &self.range
+    }
+
+    pub fn max_attempts(&self) -> Option<u32> {
+        self.max_attempts
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn player(&self) -> Option<&str> {
+        self.player.as_deref()
+    }
+
+    pub fn feedback(&self) -> FeedbackPolicy {
+        self.feedback
+    }
+
+    pub fn coach(&self) -> bool {
+        self.coach
+    }
+
+    pub fn record(&self) -> Option<&Path> {
+        self.record.as_deref()
+    }
+}
+
+/// The RNG every game draws its secret from.
+///
+/// `StdRng` only promises to give the same numbers for the same seed within a
+/// release series of `rand`, which is why the dependency stays pinned to 0.8.
+pub fn seeded_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// A fresh seed for games started without `--seed`.
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Difficulty::default().config()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    MinAboveMax {
+        min: u32,
+        max: u32,
+    },
+    /// A range holding one number leaves nothing to guess.
+    SingleNumber(u32),
+    ZeroAttempts,
+    UnknownDifficulty(String),
+    UnknownFeedback(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::MinAboveMax { min, max } => {
+                write!(f, "the minimum {min} is greater than the maximum {max}")
+            }
+            ConfigError::SingleNumber(n) => write!(
+                f,
+                "the range {n}..={n} holds a single number, there would be nothing to guess"
+            ),
+            ConfigError::ZeroAttempts => write!(f, "the attempt limit must be at least 1"),
+            ConfigError::UnknownDifficulty(name) => write!(
+                f,
+                "unknown difficulty `{name}`, expected one of easy, normal or hard"
+            ),
+            ConfigError::UnknownFeedback(name) => write!(
+                f,
+                "unknown feedback `{name}`, expected one of classic, warmer or heat"
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_valid() {
+        assert_eq!(Difficulty::Easy.config().range(), &(1..=10));
+        assert_eq!(Difficulty::Normal.config().range(), &(1..=100));
+        assert_eq!(Difficulty::Hard.config().range(), &(1..=1000));
+        assert_eq!(Difficulty::Hard.config().max_attempts(), Some(10));
+    }
+
+    #[test]
+    fn parses_difficulty_names() {
+        assert_eq!("HARD".parse(), Ok(Difficulty::Hard));
+        assert_eq!(
+            "impossible".parse::<Difficulty>(),
+            Err(ConfigError::UnknownDifficulty("impossible".to_string()))
+        );
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_numbers() {
+        let draw = |seed| {
+            let mut rng = seeded_rng(seed);
+            (0..8)
+                .map(|_| rng.gen_range(1..=1000))
+                .collect::<Vec<u32>>()
+        };
+
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+    }
+
+    #[test]
+    fn rejects_bad_combinations() {
+        assert_eq!(
+            Config::new(10, 1, None),
+            Err(ConfigError::MinAboveMax { min: 10, max: 1 })
+        );
+        assert_eq!(Config::new(4, 4, None), Err(ConfigError::SingleNumber(4)));
+        assert_eq!(Config::new(1, 10, Some(0)), Err(ConfigError::ZeroAttempts));
+    }
+}
@@ -0,0 +1,223 @@
+//! The daily challenge: one puzzle per UTC day, the same for every player.
+//!
+//! The range and the secret come from the SHA-256 of `"guessing_game daily
+//! v1 {date}"` rather than from `rand`, whose `StdRng` may change its
+//! algorithm between releases. Hashing is pinned down by a standard, so a
+//! given day's puzzle never changes, on any platform or in any version; the
+//! golden tests below hold it to that. A new rule for deriving puzzles needs
+//! a new version in the hashed string, never a change to what `v1` means.
+
+use crate::date::Date;
+use crate::game::Game;
+use crate::policy::Heat;
+use crate::sha256;
+use crate::strategy::optimal_worst_case;
+use std::ops::RangeInclusive;
+
+/// The largest numbers a daily range can go up to, the range always starting at 1.
+pub const SIZES: [u32; 4] = [100, 250, 500, 1000];
+
+/// Guesses allowed beyond the fewest that can always find the secret.
+pub const SPARE_ATTEMPTS: u32 = 2;
+
+/// The puzzle of one day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Daily {
+    date: Date,
+    max: u32,
+    secret: u32,
+    seed: u64,
+}
+
+impl Daily {
+    pub fn new(date: Date) -> Daily {
+        let hash = sha256::digest(format!("guessing_game daily v1 {date}").as_bytes());
+        let word = |at: usize| u64::from_be_bytes(hash[at..at + 8].try_into().unwrap());
+
+        // 256 is a multiple of the number of sizes, so every size is as likely;
+        // the modulo bias of the secret is at most 1000 in 2^64
+        let max = SIZES[usize::from(hash[8]) % SIZES.len()];
+        let secret = 1 + (word(16) % u64::from(max)) as u32;
+
+        Daily {
+            date,
+            max,
+            secret,
+            seed: word(0),
+        }
+    }
+
+    /// Today's puzzle, going by UTC.
+    pub fn today() -> Daily {
+        Daily::new(Date::today())
+    }
+
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    pub fn range(&self) -> RangeInclusive<u32> {
+        1..=self.max
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        optimal_worst_case(u64::from(self.max)) + SPARE_ATTEMPTS
+    }
+
+    /// Identifies the puzzle in saved games and on the leaderboard.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A fresh game of this puzzle, with classic feedback.
+    pub fn game(&self) -> Game {
+        Game::new(self.secret, self.range())
+            .with_max_attempts(Some(self.max_attempts()))
+            .with_seed(self.seed)
+    }
+
+    /// A spoiler-free summary of a finished game to paste in a chat, in the
+    /// style of the word games: one square per guess for how close it came.
+    ///
+    /// ```text
+    /// Guessing Game daily 2024-02-29 4/9
+    /// 🟦🟨🟥🟩
+    /// ```
+    ///
+    /// A lost game shows `X` instead of the number of guesses.
+    pub fn share(&self, game: &Game) -> String {
+        let size = u64::from(self.max);
+        let squares: String = game
+            .history()
+            .iter()
+            .map(|&guess| match guess.abs_diff(self.secret) {
+                0 => '🟩',
+                distance => square(Heat::from_distance(distance, size)),
+            })
+            .collect();
+        let score = if game.is_won() {
+            game.attempts().to_string()
+        } else {
+            "X".to_string()
+        };
+
+        format!(
+            "Guessing Game daily {} {score}/{}\n{squares}",
+            self.date,
+            self.max_attempts()
+        )
+    }
+}
+
+fn square(heat: Heat) -> char {
+    match heat {
+        Heat::Freezing => '⬜',
+        Heat::Cold => '🟦',
+        Heat::Cool => '🟪',
+        Heat::Warm => '🟨',
+        Heat::Hot => '🟧',
+        Heat::Burning => '🟥',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(date: &str) -> Daily {
+        Daily::new(date.parse().unwrap())
+    }
+
+    // these values must never change: every player's puzzle for the day
+    // depends on them, whatever version of the game they run
+    #[test]
+    fn golden_puzzles() {
+        let golden = [
+            ("1970-01-01", 500, 33, 4619786806747465289),
+            ("2000-02-29", 100, 25, 6789318731768686895),
+            ("2024-12-31", 100, 45, 12634701308337740789),
+            ("2026-10-17", 100, 29, 8305286245064505285),
+            ("2038-01-19", 250, 230, 4341246488683712056),
+            ("9999-12-31", 100, 85, 13835513450596463835),
+        ];
+
+        for (date, max, secret, seed) in golden {
+            let puzzle = daily(date);
+            assert_eq!(
+                (puzzle.range(), puzzle.secret(), puzzle.seed()),
+                (1..=max, secret, seed),
+                "the puzzle of {date} changed"
+            );
+        }
+    }
+
+    #[test]
+    fn neighbouring_days_differ() {
+        let first = daily("2026-10-17");
+        let second = daily("2026-10-18");
+        assert_ne!(first.seed(), second.seed());
+        assert_eq!(first, daily("2026-10-17"));
+    }
+
+    #[test]
+    fn every_size_comes_up() {
+        let start: Date = "2026-01-01".parse().unwrap();
+        let puzzles: Vec<Daily> = (0..365)
+            .map(|day| Daily::new(Date::from_days(start.days() + day)))
+            .collect();
+
+        for size in SIZES {
+            assert!(puzzles.iter().any(|puzzle| *puzzle.range().end() == size));
+        }
+        assert!(puzzles
+            .iter()
+            .all(|puzzle| puzzle.range().contains(&puzzle.secret())));
+    }
+
+    #[test]
+    fn the_game_plays_the_puzzle() {
+        let puzzle = daily("2024-02-29");
+        let game = puzzle.game();
+        assert_eq!(game.secret(), puzzle.secret());
+        assert_eq!(game.range(), &puzzle.range());
+        assert_eq!(game.max_attempts(), Some(puzzle.max_attempts()));
+        assert_eq!(game.seed(), Some(puzzle.seed()));
+    }
+
+    #[test]
+    fn shares_a_won_game() {
+        // the secret of this day is 29 out of 1..=100
+        let puzzle = daily("2026-10-17");
+        let mut game = puzzle.game();
+        for guess in [100, 50, 28, 29] {
+            game.guess(guess);
+        }
+
+        assert_eq!(
+            puzzle.share(&game),
+            "Guessing Game daily 2026-10-17 4/9\n⬜🟪🟥🟩"
+        );
+    }
+
+    #[test]
+    fn shares_a_lost_game() {
+        let puzzle = daily("2000-02-29");
+        let mut game = puzzle.game();
+        let wrong = (1..).filter(|&guess| guess != puzzle.secret());
+        for guess in wrong.take(puzzle.max_attempts() as usize) {
+            game.guess(guess);
+        }
+
+        assert!(game.is_lost());
+        let share = puzzle.share(&game);
+        assert!(share.starts_with(&format!(
+            "Guessing Game daily 2000-02-29 X/{}\n",
+            puzzle.max_attempts()
+        )));
+        assert!(!share.contains('🟩'));
+    }
+}
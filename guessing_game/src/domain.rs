@@ -0,0 +1,613 @@
+//! Secrets that aren't a `u32`: huge integers, decimals and words.
+//!
+//! A [`Guessing`] game only needs its [`Domain`] to say whether a guess could
+//! be the secret at all and how it compares with the secret, as an
+//! [`Ordering`] whose `Equal` wins. [`Integers`] does that for any `Ord` type
+//! that can be parsed, like `u128` up to [`BIG_LIMIT`]; [`Decimals`] counts
+//! any guess within a tolerance as right, and a [`Dictionary`] compares
+//! words in alphabetical order. All of them are played by the same
+//! [`session::run`] loop as every other game.
+//!
+//! The `u32` [`crate::Game`] is a `Guessing<Integers<u32>>` underneath, with
+//! the feedback policies, coach and save files that do arithmetic on the
+//! secret on top, so the rules themselves are written down only once.
+
+use crate::game::Feedback;
+use crate::session::{self, Action, Frontend, Input, Outcome, Puzzle};
+use rand::distributions::uniform::SampleUniform;
+use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// The largest secret `--big` can draw, 10^30.
+pub const BIG_LIMIT: u128 = 10u128.pow(30);
+
+pub const HELP: &str = "\
+Type a guess, or one of these commands:
+  hint     show what the secret comes after and before
+  history  list your guesses so far
+  giveup   reveal the secret and end the game
+  quit     leave without revealing the secret
+  help     show this message";
+
+/// The values a secret is drawn from and how guesses compare with it.
+pub trait Domain: fmt::Display {
+    /// What a guess and the secret are made of.
+    type Value: Clone + PartialOrd + fmt::Display + FromStr;
+
+    /// Whether `value` could be the secret at all.
+    fn contains(&self, value: &Self::Value) -> bool;
+
+    /// How `guess` compares with `secret`, `Equal` winning the game.
+    fn compare(&self, guess: &Self::Value, secret: &Self::Value) -> Ordering;
+
+    /// What the player is told about a guess that came out `Less` or `Greater`.
+    fn miss(&self, ordering: Ordering) -> &'static str {
+        match ordering {
+            Ordering::Less => "Too small!",
+            _ => "Too big!",
+        }
+    }
+}
+
+/// Whole numbers of any `Ord` type in a range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Integers<T>(pub RangeInclusive<T>);
+
+impl<T: SampleUniform + PartialOrd + Clone> Integers<T> {
+    pub fn random<R: Rng + ?Sized>(&self, rng: &mut R) -> T {
+        rng.gen_range(self.0.clone())
+    }
+}
+
+impl<T: Ord + Clone + fmt::Display + FromStr> Domain for Integers<T> {
+    type Value = T;
+
+    fn contains(&self, value: &T) -> bool {
+        self.0.contains(value)
+    }
+
+    fn compare(&self, guess: &T, secret: &T) -> Ordering {
+        guess.cmp(secret)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Integers<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a number from {} to {}", self.0.start(), self.0.end())
+    }
+}
+
+/// Decimal numbers in a range, any guess within `tolerance` of the secret
+/// counting as right.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decimals {
+    range: RangeInclusive<f64>,
+    tolerance: f64,
+}
+
+impl Decimals {
+    /// Panics unless the range is finite and not empty and the tolerance
+    /// is positive.
+    pub fn new(range: RangeInclusive<f64>, tolerance: f64) -> Decimals {
+        assert!(
+            range.start().is_finite() && range.end().is_finite() && !range.is_empty(),
+            "decimals need a finite range that isn't empty"
+        );
+        assert!(
+            tolerance > 0.0 && tolerance.is_finite(),
+            "the tolerance must be positive"
+        );
+        Decimals { range, tolerance }
+    }
+
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    pub fn random<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        rng.gen_range(self.range.clone())
+    }
+}
+
+impl Domain for Decimals {
+    type Value = f64;
+
+    fn contains(&self, value: &f64) -> bool {
+        // NaN is in no range, and `inf` doesn't get past a finite one
+        self.range.contains(value)
+    }
+
+    fn compare(&self, guess: &f64, secret: &f64) -> Ordering {
+        if (guess - secret).abs() <= self.tolerance {
+            Ordering::Equal
+        } else if guess < secret {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }
+}
+
+impl fmt::Display for Decimals {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a decimal number from {} to {}, give or take {}",
+            self.range.start(),
+            self.range.end(),
+            self.tolerance
+        )
+    }
+}
+
+/// A word of letters only, always in lowercase so that case never matters.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Word(String);
+
+impl Word {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Word {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Word, String> {
+        let word = s.trim();
+        if word.is_empty() || !word.chars().all(char::is_alphabetic) {
+            return Err(format!("`{word}` is not a word of letters"));
+        }
+        Ok(Word(word.to_lowercase()))
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The words a secret word is drawn from, compared in alphabetical order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    /// Sorted and without duplicates.
+    words: Vec<Word>,
+}
+
+impl Dictionary {
+    /// Panics if there are no words.
+    pub fn new<I: IntoIterator<Item = Word>>(words: I) -> Dictionary {
+        let mut words: Vec<Word> = words.into_iter().collect();
+        words.sort();
+        words.dedup();
+        assert!(!words.is_empty(), "a dictionary needs at least one word");
+        Dictionary { words }
+    }
+
+    /// The few hundred everyday words of `words.txt`.
+    pub fn builtin() -> Dictionary {
+        Dictionary::new(
+            include_str!("words.txt")
+                .lines()
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| line.parse().expect("the built-in words are valid")),
+        )
+    }
+
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    pub fn random<R: Rng + ?Sized>(&self, rng: &mut R) -> Word {
+        self.words[rng.gen_range(0..self.words.len())].clone()
+    }
+}
+
+impl Domain for Dictionary {
+    type Value = Word;
+
+    fn contains(&self, word: &Word) -> bool {
+        self.words.binary_search(word).is_ok()
+    }
+
+    fn compare(&self, guess: &Word, secret: &Word) -> Ordering {
+        guess.cmp(secret)
+    }
+
+    fn miss(&self, ordering: Ordering) -> &'static str {
+        match ordering {
+            Ordering::Less => "The secret word comes after that one!",
+            _ => "The secret word comes before that one!",
+        }
+    }
+}
+
+impl fmt::Display for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "one of the {} words I know", self.words.len())
+    }
+}
+
+/// A game of guessing a secret from any [`Domain`].
+#[derive(Debug, Clone)]
+pub struct Guessing<D: Domain> {
+    domain: D,
+    secret: D::Value,
+    history: Vec<D::Value>,
+    /// The same guesses as `history`, to spot a repeat without a scan.
+    guessed: BTreeSet<Made<D::Value>>,
+    max_attempts: Option<u32>,
+    won: bool,
+}
+
+/// A guess already made, ordered for the set of them. Values that don't
+/// compare, like NaN, never get this far since no domain contains them.
+#[derive(Debug, Clone, PartialEq)]
+struct Made<T>(T);
+
+impl<T: PartialOrd> Eq for Made<T> {}
+
+impl<T: PartialOrd> PartialOrd for Made<T> {
+    fn partial_cmp(&self, other: &Made<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PartialOrd> Ord for Made<T> {
+    fn cmp(&self, other: &Made<T>) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl<D: Domain> Guessing<D> {
+    /// Panics if the secret isn't in the domain.
+    pub fn new(domain: D, secret: D::Value) -> Guessing<D> {
+        assert!(
+            domain.contains(&secret),
+            "the secret {secret} is not {domain}"
+        );
+        Guessing {
+            domain,
+            secret,
+            history: Vec::new(),
+            guessed: BTreeSet::new(),
+            max_attempts: None,
+            won: false,
+        }
+    }
+
+    /// Limits the game to `max_attempts` accepted guesses, `None` meaning unlimited.
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Guessing<D> {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn guess(&mut self, guess: D::Value) -> Feedback {
+        if self.is_over() {
+            return Feedback::GameOver;
+        }
+        if !self.domain.contains(&guess) {
+            return Feedback::OutOfRange;
+        }
+        if !self.guessed.insert(Made(guess.clone())) {
+            return Feedback::AlreadyGuessed;
+        }
+
+        let ordering = self.domain.compare(&guess, &self.secret);
+        self.history.push(guess);
+        self.won = ordering == Ordering::Equal;
+        Feedback::from(ordering)
+    }
+
+    pub fn domain(&self) -> &D {
+        &self.domain
+    }
+
+    pub fn secret(&self) -> &D::Value {
+        &self.secret
+    }
+
+    pub fn history(&self) -> &[D::Value] {
+        &self.history
+    }
+
+    /// How the `index`th guess compared with the secret.
+    pub fn ordering(&self, index: usize) -> Ordering {
+        self.domain.compare(&self.history[index], &self.secret)
+    }
+
+    /// The largest guess below the secret and the smallest one above it, so
+    /// far: the secret lies strictly between them.
+    pub fn bounds(&self) -> (Option<&D::Value>, Option<&D::Value>) {
+        let mut below: Option<&D::Value> = None;
+        let mut above: Option<&D::Value> = None;
+        for (index, guess) in self.history.iter().enumerate() {
+            match self.ordering(index) {
+                Ordering::Less if below.is_none_or(|b| self.domain.compare(guess, b).is_gt()) => {
+                    below = Some(guess)
+                }
+                Ordering::Greater
+                    if above.is_none_or(|a| self.domain.compare(guess, a).is_lt()) =>
+                {
+                    above = Some(guess)
+                }
+                _ => {}
+            }
+        }
+        (below, above)
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn max_attempts(&self) -> Option<u32> {
+        self.max_attempts
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.won
+    }
+
+    pub fn is_lost(&self) -> bool {
+        !self.won
+            && self
+                .max_attempts
+                .is_some_and(|max| self.history.len() >= max as usize)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+}
+
+impl<D: Domain> Puzzle for Guessing<D> {
+    type Guess = D::Value;
+    type Reply = Feedback;
+
+    fn guess(&mut self, guess: D::Value) -> Feedback {
+        Guessing::guess(self, guess)
+    }
+
+    fn is_won(&self) -> bool {
+        Guessing::is_won(self)
+    }
+
+    fn is_over(&self) -> bool {
+        Guessing::is_over(self)
+    }
+}
+
+impl<D, R, W> Frontend<Guessing<D>> for session::Console<R, W>
+where
+    D: Domain,
+    <D::Value as FromStr>::Err: fmt::Display,
+    R: BufRead,
+    W: Write,
+{
+    fn read(&mut self) -> io::Result<Input<D::Value>> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(Input::End);
+        }
+
+        let input = line.trim();
+        Ok(match session::parse_command(input) {
+            Some(action) => Input::Action(action),
+            None if input.is_empty() => {
+                Input::Invalid("Please type a guess, or `help` to see the commands.".to_string())
+            }
+            None => match input.parse() {
+                Ok(guess) => Input::Action(Action::Guess(guess)),
+                Err(error) => Input::Invalid(format!(
+                    "`{input}` is neither a guess nor a command ({error}), type `help` to see the commands."
+                )),
+            },
+        })
+    }
+
+    fn prompt(&mut self, _: &Guessing<D>) -> io::Result<()> {
+        writeln!(self.output, "Please input your guess!")
+    }
+
+    fn invalid(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.output, "{message}")
+    }
+
+    fn feedback(&mut self, game: &Guessing<D>, guess: D::Value, reply: Feedback) -> io::Result<()> {
+        writeln!(self.output, "You guessed: {guess}")?;
+
+        match reply {
+            Feedback::TooSmall => writeln!(self.output, "{}", game.domain().miss(Ordering::Less)),
+            Feedback::TooBig => writeln!(self.output, "{}", game.domain().miss(Ordering::Greater)),
+            Feedback::OutOfRange => {
+                writeln!(self.output, "The secret is {}!", game.domain())
+            }
+            Feedback::AlreadyGuessed => writeln!(self.output, "You already guessed {guess}!"),
+            Feedback::Win => writeln!(self.output, "You win!"),
+            Feedback::GameOver => Ok(()),
+        }
+    }
+
+    fn hint(&mut self, game: &Guessing<D>) -> io::Result<()> {
+        match game.bounds() {
+            (None, None) => writeln!(self.output, "The secret is {}.", game.domain()),
+            (Some(below), None) => writeln!(self.output, "The secret comes after {below}."),
+            (None, Some(above)) => writeln!(self.output, "The secret comes before {above}."),
+            (Some(below), Some(above)) => writeln!(
+                self.output,
+                "The secret comes after {below} and before {above}."
+            ),
+        }
+    }
+
+    fn history(&mut self, game: &Guessing<D>) -> io::Result<()> {
+        if game.history().is_empty() {
+            return writeln!(self.output, "No guesses yet.");
+        }
+
+        for (index, guess) in game.history().iter().enumerate() {
+            let verdict = match game.ordering(index) {
+                Ordering::Equal => "was right",
+                Ordering::Less => "came before the secret",
+                Ordering::Greater => "came after the secret",
+            };
+            writeln!(self.output, "{:>3}. {guess} {verdict}", index + 1)?;
+        }
+        Ok(())
+    }
+
+    fn help(&mut self) -> io::Result<()> {
+        writeln!(self.output, "{HELP}")
+    }
+
+    fn finish(&mut self, game: &Guessing<D>, outcome: Outcome) -> io::Result<()> {
+        match outcome {
+            Outcome::GaveUp => writeln!(self.output, "The secret was {}.", game.secret()),
+            Outcome::Quit => writeln!(self.output, "Bye!"),
+            Outcome::EndOfInput => writeln!(self.output, "No more input, the game ends here."),
+            Outcome::Won | Outcome::Lost => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::seeded_rng;
+    use crate::session::Console;
+
+    fn play<D>(game: &mut Guessing<D>, input: &str) -> (Outcome, String)
+    where
+        D: Domain,
+        <D::Value as FromStr>::Err: fmt::Display,
+    {
+        let mut console = Console {
+            input: input.as_bytes(),
+            output: Vec::new(),
+        };
+        let outcome = session::run(game, &mut console).unwrap();
+        (outcome, String::from_utf8(console.output).unwrap())
+    }
+
+    #[test]
+    fn plays_huge_integers() {
+        let secret = 123_456_789_012_345_678_901_234_567_890;
+        let mut game = Guessing::new(Integers(1..=BIG_LIMIT), secret);
+
+        assert_eq!(game.guess(BIG_LIMIT + 1), Feedback::OutOfRange);
+        assert_eq!(game.guess(BIG_LIMIT / 2), Feedback::TooBig);
+        assert_eq!(game.guess(BIG_LIMIT / 2), Feedback::AlreadyGuessed);
+        assert_eq!(game.guess(secret), Feedback::Win);
+        assert_eq!(game.guess(1), Feedback::GameOver);
+        assert_eq!(game.attempts(), 2);
+    }
+
+    #[test]
+    fn binary_search_finds_a_u128_in_a_hundred_guesses() {
+        let domain = Integers(1..=BIG_LIMIT);
+        let secret = domain.random(&mut seeded_rng(5));
+        let mut game = Guessing::new(domain, secret).with_max_attempts(Some(100));
+
+        let (mut low, mut high) = (1, BIG_LIMIT);
+        while !game.is_over() {
+            let guess = low + (high - low) / 2;
+            match game.guess(guess) {
+                Feedback::TooSmall => low = guess + 1,
+                Feedback::TooBig => high = guess - 1,
+                _ => {}
+            }
+        }
+        assert!(game.is_won());
+    }
+
+    #[test]
+    fn decimals_win_within_the_tolerance() {
+        let mut game = Guessing::new(Decimals::new(0.0..=10.0, 0.05), 3.125);
+
+        assert_eq!(game.guess(f64::NAN), Feedback::OutOfRange);
+        assert_eq!(game.guess(f64::INFINITY), Feedback::OutOfRange);
+        assert_eq!(game.guess(3.0), Feedback::TooSmall);
+        assert_eq!(game.guess(3.2), Feedback::TooBig);
+        assert_eq!(game.guess(3.08), Feedback::Win);
+    }
+
+    #[test]
+    fn words_compare_alphabetically() {
+        let dictionary = Dictionary::builtin();
+        let mut game = Guessing::new(dictionary, "melon".parse().unwrap());
+
+        assert_eq!(game.guess("Apple".parse().unwrap()), Feedback::TooSmall);
+        assert_eq!(game.guess("zebra".parse().unwrap()), Feedback::TooBig);
+        assert_eq!(game.guess("qwxyz".parse().unwrap()), Feedback::OutOfRange);
+        assert_eq!(game.guess("MELON".parse().unwrap()), Feedback::Win);
+        assert!("two words".parse::<Word>().is_err());
+        assert!("".parse::<Word>().is_err());
+    }
+
+    #[test]
+    fn the_builtin_dictionary_is_clean() {
+        let dictionary = Dictionary::builtin();
+        assert!(dictionary.words().len() >= 200);
+        assert!(dictionary
+            .words()
+            .iter()
+            .all(|word| word.as_str().chars().all(|c| c.is_ascii_lowercase())));
+
+        let word = dictionary.random(&mut seeded_rng(1));
+        assert!(dictionary.contains(&word));
+    }
+
+    #[test]
+    fn tracks_the_bounds() {
+        let mut game = Guessing::new(Integers(1..=100u128), 40);
+        assert_eq!(game.bounds(), (None, None));
+
+        for guess in [50, 20, 30, 45, 10] {
+            game.guess(guess);
+        }
+        assert_eq!(game.bounds(), (Some(&30), Some(&45)));
+    }
+
+    #[test]
+    fn plays_words_on_the_console() {
+        let secret: Word = "melon".parse().unwrap();
+        let mut game = Guessing::new(Dictionary::builtin(), secret);
+
+        let (outcome, output) = play(
+            &mut game,
+            "apple\nhint\nzz9\nqwxyz\nzebra\nhistory\nmelon\n",
+        );
+        assert_eq!(outcome, Outcome::Won);
+        assert!(output.contains("The secret word comes after that one!"));
+        assert!(output.contains("The secret comes after apple."));
+        assert!(output.contains("`zz9` is neither a guess nor a command"));
+        assert!(output.contains("The secret is one of the 220 words I know!"));
+        assert!(output.contains("The secret word comes before that one!"));
+        assert!(output.contains("  2. zebra came after the secret"));
+        assert!(output.ends_with("You win!\n"));
+    }
+
+    #[test]
+    fn plays_decimals_on_the_console() {
+        let mut game =
+            Guessing::new(Decimals::new(1.0..=100.0, 0.5), 42.25).with_max_attempts(Some(2));
+
+        let (outcome, output) = play(&mut game, "42\ngiveup\n");
+        assert_eq!(outcome, Outcome::Won);
+        assert!(output.contains("You guessed: 42\nYou win!"));
+
+        let mut game =
+            Guessing::new(Decimals::new(1.0..=100.0, 0.5), 42.25).with_max_attempts(Some(2));
+        let (outcome, output) = play(&mut game, "40\nsave\n50.5\n");
+        assert_eq!(outcome, Outcome::Lost);
+        assert!(output.contains("This game can't be saved."));
+        assert!(output.contains("Too big!"));
+    }
+}
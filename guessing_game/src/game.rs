@@ -0,0 +1,296 @@
+use crate::commit::Salt;
+use crate::config::Config;
+use crate::domain::{Guessing, Integers};
+use crate::policy::{Clue, FeedbackPolicy};
+use rand::Rng;
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+/// What the game has to say about a single guess.
+///
+/// `TooSmall`, `TooBig` and `Win` are the three arms of comparing the guess
+/// with the secret; the other two reject a guess before it is compared at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feedback {
+    TooSmall,
+    TooBig,
+    Win,
+    /// The guess lies outside the range the secret was drawn from.
+    OutOfRange,
+    /// The guess was already made earlier in this game.
+    AlreadyGuessed,
+    /// The game was already won or lost before this guess.
+    GameOver,
+}
+
+impl From<Ordering> for Feedback {
+    fn from(ordering: Ordering) -> Feedback {
+        match ordering {
+            Ordering::Less => Feedback::TooSmall,
+            Ordering::Greater => Feedback::TooBig,
+            Ordering::Equal => Feedback::Win,
+        }
+    }
+}
+
+/// One round of the guessing game: a secret, the range it was drawn from and
+/// every guess made against it so far.
+///
+/// The rules themselves are those of a [`Guessing`] game over [`Integers`];
+/// what `Game` adds is what only makes sense for a `u32`, like feedback
+/// policies, the seed a saved game is replayed from and the salt of the
+/// commitment to its secret.
+#[derive(Debug, Clone)]
+pub struct Game {
+    rules: Guessing<Integers<u32>>,
+    policy: FeedbackPolicy,
+    seed: Option<u64>,
+    salt: Option<Salt>,
+}
+
+impl Game {
+    /// Starts a game with a known secret.
+    ///
+    /// Panics if the range is empty or the secret lies outside of it.
+    pub fn new(secret: u32, range: RangeInclusive<u32>) -> Game {
+        assert!(!range.is_empty(), "the range of a game can't be empty");
+        assert!(
+            range.contains(&secret),
+            "the secret {secret} is outside of {}..={}",
+            range.start(),
+            range.end()
+        );
+
+        Game {
+            rules: Guessing::new(Integers(range), secret),
+            policy: FeedbackPolicy::default(),
+            seed: None,
+            salt: None,
+        }
+    }
+
+    /// Starts a game whose secret is drawn uniformly from `range`.
+    pub fn with_rng<R: Rng + ?Sized>(range: RangeInclusive<u32>, rng: &mut R) -> Game {
+        let secret = rng.gen_range(range.clone());
+        Game::new(secret, range)
+    }
+
+    /// Starts a game with the range and attempt limit of `config`.
+    pub fn from_config<R: Rng + ?Sized>(config: &Config, rng: &mut R) -> Game {
+        Game::with_rng(config.range().clone(), rng)
+            .with_max_attempts(config.max_attempts())
+            .with_policy(config.feedback())
+    }
+
+    /// Limits the game to `max_attempts` accepted guesses, `None` meaning unlimited.
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Game {
+        self.rules = self.rules.with_max_attempts(max_attempts);
+        self
+    }
+
+    /// Describes missed guesses to the player the way `policy` does.
+    pub fn with_policy(mut self, policy: FeedbackPolicy) -> Game {
+        self.policy = policy;
+        self
+    }
+
+    /// Remembers the seed the secret was drawn with, so a saved game can
+    /// still be replayed.
+    pub fn with_seed(mut self, seed: u64) -> Game {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Remembers the salt the secret was committed to with, so a saved game
+    /// commits to it in the same words when it's resumed.
+    pub fn with_salt(mut self, salt: Salt) -> Game {
+        self.salt = Some(salt);
+        self
+    }
+
+    /// Compares `guess` with the secret.
+    ///
+    /// Guesses outside the range and repeated guesses are rejected without
+    /// being recorded, so they never count as an attempt.
+    pub fn guess(&mut self, guess: u32) -> Feedback {
+        self.rules.guess(guess)
+    }
+
+    pub fn secret(&self) -> u32 {
+        *self.rules.secret()
+    }
+
+    pub fn range(&self) -> &RangeInclusive<u32> {
+        &self.rules.domain().0
+    }
+
+    /// Every accepted guess, oldest first.
+    pub fn history(&self) -> &[u32] {
+        self.rules.history()
+    }
+
+    pub fn policy(&self) -> FeedbackPolicy {
+        self.policy
+    }
+
+    /// What the player gets told about the accepted guess at `index` of the history.
+    pub fn clue(&self, index: usize) -> Clue {
+        let history = self.history();
+        let previous = index.checked_sub(1).map(|previous| history[previous]);
+        self.policy
+            .clue(self.range(), self.secret(), history[index], previous)
+    }
+
+    /// The narrowest range still consistent with the feedback given so far.
+    pub fn bounds(&self) -> RangeInclusive<u32> {
+        if self.is_won() {
+            return self.secret()..=self.secret();
+        }
+
+        let (below, above) = self.rules.bounds();
+        let low = below.map_or(*self.range().start(), |guess| guess + 1);
+        let high = above.map_or(*self.range().end(), |guess| guess - 1);
+        low..=high
+    }
+
+    /// What a hint may show without giving away more than the feedback did:
+    /// the [`Game::bounds`], or `None` when the policy hides which way the
+    /// guesses were off, since the bounds would reveal exactly that.
+    pub fn hint(&self) -> Option<RangeInclusive<u32>> {
+        self.policy.reveals_direction().then(|| self.bounds())
+    }
+
+    /// The number of accepted guesses so far.
+    pub fn attempts(&self) -> usize {
+        self.rules.attempts()
+    }
+
+    pub fn max_attempts(&self) -> Option<u32> {
+        self.rules.max_attempts()
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn salt(&self) -> Option<Salt> {
+        self.salt
+    }
+
+    /// How many guesses are left before the game is lost, `None` if unlimited.
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.max_attempts()
+            .map(|max| max.saturating_sub(self.attempts() as u32))
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.rules.is_won()
+    }
+
+    /// True once every allowed attempt was used without finding the secret.
+    pub fn is_lost(&self) -> bool {
+        self.rules.is_lost()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.rules.is_over()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn compares_guesses_with_the_secret() {
+        let mut game = Game::new(7, 1..=10);
+
+        assert_eq!(game.guess(3), Feedback::TooSmall);
+        assert_eq!(game.guess(9), Feedback::TooBig);
+        assert!(!game.is_won());
+        assert_eq!(game.guess(7), Feedback::Win);
+        assert!(game.is_won());
+        assert_eq!(game.history(), &[3, 9, 7]);
+    }
+
+    #[test]
+    fn rejects_guesses_outside_the_range() {
+        let mut game = Game::new(5, 1..=10);
+
+        assert_eq!(game.guess(0), Feedback::OutOfRange);
+        assert_eq!(game.guess(11), Feedback::OutOfRange);
+        assert_eq!(game.attempts(), 0);
+    }
+
+    #[test]
+    fn rejects_repeated_guesses() {
+        let mut game = Game::new(5, 1..=10);
+
+        assert_eq!(game.guess(2), Feedback::TooSmall);
+        assert_eq!(game.guess(2), Feedback::AlreadyGuessed);
+        assert_eq!(game.attempts(), 1);
+    }
+
+    #[test]
+    fn loses_when_the_attempts_run_out() {
+        let mut game = Game::new(5, 1..=10).with_max_attempts(Some(2));
+
+        assert_eq!(game.guess(1), Feedback::TooSmall);
+        assert_eq!(game.attempts_left(), Some(1));
+        assert!(!game.is_over());
+        assert_eq!(game.guess(9), Feedback::TooBig);
+        assert!(game.is_lost());
+        assert_eq!(game.guess(5), Feedback::GameOver);
+    }
+
+    #[test]
+    fn winning_on_the_last_attempt_is_not_a_loss() {
+        let mut game = Game::new(5, 1..=10).with_max_attempts(Some(1));
+
+        assert_eq!(game.guess(5), Feedback::Win);
+        assert!(game.is_won());
+        assert!(!game.is_lost());
+        assert_eq!(game.guess(4), Feedback::GameOver);
+    }
+
+    #[test]
+    fn narrows_the_bounds() {
+        let mut game = Game::new(6, 1..=10);
+        assert_eq!(game.bounds(), 1..=10);
+
+        game.guess(3);
+        game.guess(9);
+        game.guess(2);
+        game.guess(8);
+        assert_eq!(game.bounds(), 4..=7);
+
+        game.guess(6);
+        assert_eq!(game.bounds(), 6..=6);
+    }
+
+    #[test]
+    fn hints_only_when_the_policy_reveals_the_direction() {
+        for policy in FeedbackPolicy::ALL {
+            let mut game = Game::new(6, 1..=10).with_policy(policy);
+            game.guess(3);
+            let expected = policy.reveals_direction().then_some(4..=10);
+            assert_eq!(game.hint(), expected, "{policy}");
+        }
+    }
+
+    #[test]
+    fn draws_the_secret_from_the_range() {
+        let mut rng = StepRng::new(0, 1 << 40);
+        for _ in 0..100 {
+            let game = Game::with_rng(3..=6, &mut rng);
+            assert!((3..=6).contains(&game.secret()));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn secret_must_be_in_range() {
+        Game::new(11, 1..=10);
+    }
+}
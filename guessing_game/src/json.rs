@@ -0,0 +1,513 @@
+//! Just enough JSON for the machine interface and the files the game writes.
+//!
+//! Numbers keep the text they were written with, so a `u64` seed survives a
+//! round trip that an `f64` would round.
+
+use std::error::Error;
+use std::fmt::{self, Write};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    /// The literal as it appears in the document, already checked to be valid.
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    /// Members keep the order they were written or inserted in.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn parse(text: &str) -> Result<Value, ParseError> {
+        let mut parser = Parser { text, position: 0 };
+        parser.skip_whitespace();
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.position < text.len() {
+            return Err(parser.error("unexpected text after the value"));
+        }
+        Ok(value)
+    }
+
+    pub fn object<'a, I>(members: I) -> Value
+    where
+        I: IntoIterator<Item = (&'a str, Value)>,
+    {
+        Value::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// The member called `key`, if this is an object that has one.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_u64().and_then(|n| n.try_into().ok())
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+}
+
+/// Writes the value on a single line, the way the machine interface needs it.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Number(n) => f.write_str(n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(values) => {
+                f.write_char('[')?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(members) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+macro_rules! from_integer {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Value {
+            fn from(n: $t) -> Value {
+                Value::Number(n.to_string())
+            }
+        })*
+    };
+}
+
+from_integer!(u8, u32, u64, usize, i32, i64);
+
+impl From<f64> for Value {
+    /// JSON has no infinities or NaN, those become `null`.
+    fn from(n: f64) -> Value {
+        if n.is_finite() {
+            Value::Number(n.to_string())
+        } else {
+            Value::Null
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Value {
+        option.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Value {
+        Value::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: &'static str,
+    /// Byte offset into the text where parsing gave up.
+    position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid JSON at byte {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Deeper nesting than this is refused instead of overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError {
+            message,
+            position: self.position,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), ParseError> {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, ParseError> {
+        if self.text[self.position..].starts_with(word) {
+            self.position += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("unknown literal"))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, ParseError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+
+        match self.peek() {
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => self.array(depth),
+            Some(b'{') => self.object(depth),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of text")),
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, ParseError> {
+        self.position += 1;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Value::Array(values));
+        }
+        loop {
+            self.skip_whitespace();
+            values.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Value::Array(values));
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, ParseError> {
+        self.position += 1;
+        let mut members = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a member name"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':', "expected `:`")?;
+            self.skip_whitespace();
+            members.push((key, self.value(depth + 1)?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.position;
+        let digits = |parser: &mut Parser| {
+            let before = parser.position;
+            while matches!(parser.peek(), Some(b'0'..=b'9')) {
+                parser.position += 1;
+            }
+            parser.position - before
+        };
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        match digits(self) {
+            0 => return Err(self.error("expected a digit")),
+            n if n > 1 && self.text.as_bytes()[self.position - n] == b'0' => {
+                return Err(self.error("numbers can't have leading zeros"))
+            }
+            _ => {}
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            if digits(self) == 0 {
+                return Err(self.error("expected a digit after `.`"));
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            if digits(self) == 0 {
+                return Err(self.error("expected a digit in the exponent"));
+            }
+        }
+
+        Ok(Value::Number(self.text[start..self.position].to_string()))
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.position += 1;
+        let mut string = String::new();
+
+        loop {
+            let rest = &self.text[self.position..];
+            let Some(c) = rest.chars().next() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.position += c.len_utf8();
+
+            match c {
+                '"' => return Ok(string),
+                '\\' => string.push(self.escape()?),
+                c if c < ' ' => return Err(self.error("control character in string")),
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, ParseError> {
+        let Some(byte) = self.peek() else {
+            return Err(self.error("unterminated string"));
+        };
+        self.position += 1;
+
+        Ok(match byte {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex()?;
+                if !(0xd800..0xdc00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| self.error("lone low surrogate"));
+                }
+                // characters outside the basic plane come as a surrogate pair
+                if !self.text[self.position..].starts_with("\\u") {
+                    return Err(self.error("lone high surrogate"));
+                }
+                self.position += 2;
+                let low = self.hex()?;
+                if !(0xdc00..0xe000).contains(&low) {
+                    return Err(self.error("invalid low surrogate"));
+                }
+                char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+                    .expect("a surrogate pair always makes a valid char")
+            }
+            _ => return Err(self.error("unknown escape")),
+        })
+    }
+
+    fn hex(&mut self) -> Result<u32, ParseError> {
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("expected four hex digits"))?;
+        self.position += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("checked to be hex digits"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_documents() {
+        let value = Value::parse(
+            r#" {"guess": 42, "list": [true, null, -1.5e3], "s": "a\"\u00e9\ud83e\udd80"} "#,
+        )
+        .unwrap();
+
+        assert_eq!(value.get("guess").and_then(Value::as_u32), Some(42));
+        assert_eq!(
+            value.get("list"),
+            Some(&Value::Array(vec![
+                Value::Bool(true),
+                Value::Null,
+                Value::Number("-1.5e3".to_string())
+            ]))
+        );
+        assert_eq!(value.get("s").and_then(Value::as_str), Some("a\"é🦀"));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn keeps_large_integers_exact() {
+        let value = Value::parse(&Value::from(u64::MAX).to_string()).unwrap();
+        assert_eq!(value.as_u64(), Some(u64::MAX));
+        assert_eq!(Value::parse("4294967296").unwrap().as_u32(), None);
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let value = Value::object([
+            ("type", "feedback".into()),
+            ("text", "tab\tquote\"\u{1}".into()),
+            ("left", Value::from(None::<u32>)),
+            ("guesses", vec![1u32, 2, 3].into()),
+            ("ratio", 0.25.into()),
+        ]);
+        let text = value.to_string();
+
+        assert_eq!(
+            text,
+            r#"{"type":"feedback","text":"tab\tquote\"\u0001","left":null,"guesses":[1,2,3],"ratio":0.25}"#
+        );
+        assert_eq!(Value::parse(&text), Ok(value));
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        for text in [
+            "",
+            "{",
+            "[1,]",
+            "{\"a\" 1}",
+            "01",
+            "1.",
+            "-",
+            "\"\\x\"",
+            "\"\\ud800\"",
+            "tru",
+            "{} {}",
+            "\"line\nbreak\"",
+        ] {
+            assert!(Value::parse(text).is_err(), "{text:?} should be rejected");
+        }
+        assert!(Value::parse(&"[".repeat(100)).is_err());
+    }
+}
@@ -0,0 +1,420 @@
+//! Finished games, kept in a JSON file under the user's data directory.
+//!
+//! The file looks like `{"version":1,"scores":[...]}`. A file that can't be
+//! read, is corrupt or was written by a newer version is never overwritten:
+//! recording a score then fails with an error the game shows as a warning,
+//! and whatever was in the file stays there.
+
+use crate::config::Difficulty;
+use crate::date::Date;
+use crate::json::Value;
+use std::error::Error;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use std::{env, fmt, thread};
+
+/// The version of the file format this build writes.
+pub const VERSION: u64 = 1;
+
+/// Set this to keep the leaderboard somewhere other than the data directory.
+pub const DATA_DIR_VARIABLE: &str = "GUESSING_GAME_DATA_DIR";
+
+/// One finished game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub player: String,
+    pub range: RangeInclusive<u32>,
+    pub max_attempts: Option<u32>,
+    pub won: bool,
+    pub attempts: u32,
+    pub elapsed: Duration,
+    pub seed: u64,
+    pub date: Date,
+}
+
+impl Score {
+    /// The preset the game was played at, or `custom` with its range.
+    pub fn category(&self) -> String {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| {
+                difficulty.range() == self.range && difficulty.max_attempts() == self.max_attempts
+            })
+            .map(|difficulty| difficulty.to_string())
+            .unwrap_or_else(|| format!("custom {}..={}", self.range.start(), self.range.end()))
+    }
+
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("player", self.player.as_str().into()),
+            ("min", (*self.range.start()).into()),
+            ("max", (*self.range.end()).into()),
+            ("max_attempts", self.max_attempts.into()),
+            ("won", self.won.into()),
+            ("attempts", self.attempts.into()),
+            ("elapsed_ms", (self.elapsed.as_millis() as u64).into()),
+            ("seed", self.seed.into()),
+            ("date", self.date.to_string().into()),
+        ])
+    }
+
+    fn from_json(value: &Value) -> Option<Score> {
+        let max_attempts = match value.get("max_attempts")? {
+            Value::Null => None,
+            limit => Some(limit.as_u32()?),
+        };
+
+        Some(Score {
+            player: value.get("player")?.as_str()?.to_string(),
+            range: value.get("min")?.as_u32()?..=value.get("max")?.as_u32()?,
+            max_attempts,
+            won: value.get("won")?.as_bool()?,
+            attempts: value.get("attempts")?.as_u32()?,
+            elapsed: Duration::from_millis(value.get("elapsed_ms")?.as_u64()?),
+            seed: value.get("seed")?.as_u64()?,
+            date: value.get("date")?.as_str()?.parse().ok()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    scores: Vec<Score>,
+}
+
+impl Leaderboard {
+    /// Where the leaderboard lives unless [`DATA_DIR_VARIABLE`] says otherwise.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = match env::var_os(DATA_DIR_VARIABLE) {
+            Some(dir) => PathBuf::from(dir),
+            None => data_dir()?.join("guessing_game"),
+        };
+        Some(dir.join("leaderboard.json"))
+    }
+
+    /// Reads the leaderboard at `path`, a missing file being an empty one.
+    pub fn load(path: &Path) -> Result<Leaderboard, LeaderboardError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Leaderboard::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+            Err(error) => Err(LeaderboardError::Io(error)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Leaderboard, LeaderboardError> {
+        let corrupt = |reason: &str| LeaderboardError::Corrupt(reason.to_string());
+
+        let document =
+            Value::parse(text).map_err(|error| LeaderboardError::Corrupt(error.to_string()))?;
+        match document.get("version").and_then(Value::as_u64) {
+            Some(VERSION) => {}
+            Some(version) => return Err(LeaderboardError::UnsupportedVersion(version)),
+            None => return Err(corrupt("no version number")),
+        }
+
+        let scores = document
+            .get("scores")
+            .and_then(Value::as_array)
+            .ok_or_else(|| corrupt("no list of scores"))?
+            .iter()
+            .map(|score| {
+                Score::from_json(score).ok_or_else(|| corrupt(&format!("unreadable score {score}")))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Leaderboard { scores })
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::object([
+            ("version", VERSION.into()),
+            (
+                "scores",
+                Value::Array(self.scores.iter().map(Score::to_json).collect()),
+            ),
+        ])
+    }
+
+    /// Adds `score` to the leaderboard file at `path`.
+    ///
+    /// The file is locked while it's read and rewritten, so two games ending
+    /// at once can't drop each other's score, and the new contents replace
+    /// the old ones in a single rename.
+    pub fn record(path: &Path, score: Score) -> Result<(), LeaderboardError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let _lock = Lock::acquire(path)?;
+
+        let mut leaderboard = Leaderboard::load(path)?;
+        leaderboard.scores.push(score);
+
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+
+        let mut file = File::create(&temporary)?;
+        writeln!(file, "{}", leaderboard.to_json())?;
+        file.sync_all()?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    pub fn scores(&self) -> &[Score] {
+        &self.scores
+    }
+
+    /// Every category with at least one win, presets first in order of difficulty.
+    pub fn categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = Difficulty::ALL.iter().map(ToString::to_string).collect();
+        let mut custom: Vec<String> = self
+            .scores
+            .iter()
+            .map(Score::category)
+            .filter(|category| !categories.contains(category))
+            .collect();
+        custom.sort();
+        custom.dedup();
+        categories.extend(custom);
+
+        categories.retain(|category| self.top(category, 1).len() == 1);
+        categories
+    }
+
+    /// The `count` best wins of a category: fewest attempts, then fastest, then oldest.
+    pub fn top(&self, category: &str, count: usize) -> Vec<&Score> {
+        let mut wins: Vec<&Score> = self
+            .scores
+            .iter()
+            .filter(|score| score.won && score.category() == category)
+            .collect();
+        wins.sort_by_key(|score| (score.attempts, score.elapsed, score.date));
+        wins.truncate(count);
+        wins
+    }
+}
+
+/// The platform's directory for per-user application data.
+fn data_dir() -> Option<PathBuf> {
+    let home = || {
+        env::var_os("HOME")
+            .filter(|home| !home.is_empty())
+            .map(PathBuf::from)
+    };
+
+    if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".local").join("share")))
+    }
+}
+
+/// A lock file next to the leaderboard, removed again when dropped.
+struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    const ATTEMPTS: u32 = 20;
+    const RETRY_DELAY: Duration = Duration::from_millis(25);
+    /// A lock this old was left behind by a game that crashed.
+    const STALE_AFTER: Duration = Duration::from_secs(30);
+
+    fn acquire(leaderboard: &Path) -> Result<Lock, LeaderboardError> {
+        let mut path: OsString = leaderboard.as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+
+        for _ in 0..Lock::ATTEMPTS {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Lock { path }),
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                    if Lock::is_stale(&path) {
+                        let _ = fs::remove_file(&path);
+                    } else {
+                        thread::sleep(Lock::RETRY_DELAY);
+                    }
+                }
+                Err(error) => return Err(LeaderboardError::Io(error)),
+            }
+        }
+        Err(LeaderboardError::Locked(path))
+    }
+
+    fn is_stale(path: &Path) -> bool {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > Lock::STALE_AFTER)
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Debug)]
+pub enum LeaderboardError {
+    Io(io::Error),
+    Corrupt(String),
+    /// The file was written by a newer version of the game.
+    UnsupportedVersion(u64),
+    /// Another game held the lock file for too long.
+    Locked(PathBuf),
+}
+
+impl fmt::Display for LeaderboardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeaderboardError::Io(error) => write!(f, "{error}"),
+            LeaderboardError::Corrupt(reason) => write!(f, "the file is corrupt ({reason})"),
+            LeaderboardError::UnsupportedVersion(version) => write!(
+                f,
+                "the file has format version {version}, this game only knows version {VERSION}"
+            ),
+            LeaderboardError::Locked(lock) => write!(
+                f,
+                "another game is using it, delete {} if that isn't true",
+                lock.display()
+            ),
+        }
+    }
+}
+
+impl Error for LeaderboardError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LeaderboardError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LeaderboardError {
+    fn from(error: io::Error) -> LeaderboardError {
+        LeaderboardError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test to write its leaderboard in.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("guessing_game-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("leaderboard.json")
+    }
+
+    fn score(player: &str, range: RangeInclusive<u32>, attempts: u32, seconds: u64) -> Score {
+        Score {
+            player: player.to_string(),
+            range,
+            max_attempts: None,
+            won: true,
+            attempts,
+            elapsed: Duration::from_secs(seconds),
+            seed: u64::MAX - 1,
+            date: Date::new(2024, 5, 17).unwrap(),
+        }
+    }
+
+    #[test]
+    fn records_scores_across_games() {
+        let path = scratch("records");
+        Leaderboard::record(&path, score("ada", 1..=10, 3, 9)).unwrap();
+        Leaderboard::record(&path, score("bob", 1..=100, 7, 20)).unwrap();
+
+        let leaderboard = Leaderboard::load(&path).unwrap();
+        assert_eq!(leaderboard.scores().len(), 2);
+        assert_eq!(leaderboard.scores()[0], score("ada", 1..=10, 3, 9));
+        assert!(!path.with_extension("json.lock").exists());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn ranks_wins_per_category() {
+        let mut lost = score("carl", 1..=10, 1, 1);
+        lost.won = false;
+        let leaderboard = Leaderboard {
+            scores: vec![
+                score("ada", 1..=10, 4, 9),
+                score("bob", 1..=10, 3, 30),
+                score("dee", 1..=10, 3, 12),
+                lost,
+                score("eve", 5..=50, 5, 5),
+            ],
+        };
+
+        let top: Vec<&str> = leaderboard
+            .top("easy", 10)
+            .iter()
+            .map(|s| s.player.as_str())
+            .collect();
+        assert_eq!(top, ["dee", "bob", "ada"]);
+        assert_eq!(leaderboard.categories(), ["easy", "custom 5..=50"]);
+    }
+
+    #[test]
+    fn never_overwrites_a_corrupt_file() {
+        let path = scratch("corrupt");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "{\"version\":1,\"scores\":[{\"player\":").unwrap();
+
+        let error = Leaderboard::record(&path, score("ada", 1..=10, 3, 9)).unwrap_err();
+        assert!(matches!(error, LeaderboardError::Corrupt(_)), "{error}");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"version\":1,\"scores\":[{\"player\":"
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn refuses_newer_versions() {
+        assert!(matches!(
+            Leaderboard::parse("{\"version\":2,\"scores\":[]}"),
+            Err(LeaderboardError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            Leaderboard::parse("{\"scores\":[]}"),
+            Err(LeaderboardError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn gives_up_on_a_held_lock() {
+        let path = scratch("locked");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let lock = Lock::acquire(&path).unwrap();
+
+        let error = Leaderboard::record(&path, score("ada", 1..=10, 3, 9)).unwrap_err();
+        assert!(matches!(error, LeaderboardError::Locked(_)), "{error}");
+        assert!(!path.exists());
+
+        drop(lock);
+        Leaderboard::record(&path, score("ada", 1..=10, 3, 9)).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn names_the_presets() {
+        let mut hard = score("ada", 1..=1000, 9, 60);
+        assert_eq!(hard.category(), "custom 1..=1000");
+        hard.max_attempts = Some(10);
+        assert_eq!(hard.category(), "hard");
+    }
+}
@@ -0,0 +1,1070 @@
+//! Ulam's game: the program answers higher or lower like the normal game but
+//! may lie up to `k` times, and the player has to find the secret anyway.
+//!
+//! Only the direction of an answer can be a lie. Guessing the secret itself is
+//! always answered truthfully, otherwise no game could ever be won.
+
+use crate::config::Config;
+use crate::reverse::Answer;
+use crate::session::{self, Console, Frontend, Input, Outcome, Puzzle};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+pub const HELP: &str = "\
+Type a number to guess it, or one of these commands:
+  hint     count the numbers still possible and suggest a guess
+  history  list your guesses so far
+  giveup   reveal the secret number and the answers that were lies
+  quit     leave without revealing the secret number
+  help     show this message";
+
+/// The side of the game that knows the secret and may lie about it.
+#[derive(Debug, Clone)]
+pub struct Oracle {
+    secret: u32,
+    lies: u32,
+    rng: StdRng,
+    transcript: Vec<(u32, Answer)>,
+    /// The attempts, counting from 1, whose answers were lies.
+    lied_at: Vec<usize>,
+}
+
+impl Oracle {
+    /// The chance that the oracle lies about a guess while it still may.
+    const LIE_ONE_IN: u32 = 3;
+
+    /// An oracle for `secret` that tells at most `lies` lies, deciding when
+    /// to tell them with `rng`.
+    pub fn new(secret: u32, lies: u32, rng: StdRng) -> Oracle {
+        Oracle {
+            secret,
+            lies,
+            rng,
+            transcript: Vec::new(),
+            lied_at: Vec::new(),
+        }
+    }
+
+    pub fn answer(&mut self, guess: u32) -> Answer {
+        let truth = match guess.cmp(&self.secret) {
+            std::cmp::Ordering::Less => Answer::Higher,
+            std::cmp::Ordering::Greater => Answer::Lower,
+            std::cmp::Ordering::Equal => Answer::Correct,
+        };
+        let lie = truth != Answer::Correct
+            && (self.lied_at.len() as u32) < self.lies
+            && self.rng.gen_ratio(1, Oracle::LIE_ONE_IN);
+
+        let answer = match truth {
+            Answer::Higher if lie => Answer::Lower,
+            Answer::Lower if lie => Answer::Higher,
+            truth => truth,
+        };
+        self.transcript.push((guess, answer));
+        if lie {
+            self.lied_at.push(self.transcript.len());
+        }
+        answer
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    /// How many lies the oracle may tell in a game.
+    pub fn lies(&self) -> u32 {
+        self.lies
+    }
+
+    /// Every guess the oracle answered, with its answer.
+    pub fn transcript(&self) -> &[(u32, Answer)] {
+        &self.transcript
+    }
+
+    /// The attempts, counting from 1, the oracle lied about.
+    pub fn lied_at(&self) -> &[usize] {
+        &self.lied_at
+    }
+}
+
+/// What the game has to say about a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    /// The oracle's answer, which may be a lie unless it's [`Answer::Correct`].
+    Answered(Answer),
+    OutOfRange,
+    GameOver,
+}
+
+/// One game against a lying [`Oracle`].
+///
+/// Unlike [`crate::Game`] it accepts a number twice, since asking again is
+/// how a player catches a lie.
+#[derive(Debug, Clone)]
+pub struct LyingGame {
+    oracle: Oracle,
+    range: RangeInclusive<u32>,
+    max_attempts: Option<u32>,
+}
+
+impl LyingGame {
+    pub fn new(oracle: Oracle, range: RangeInclusive<u32>) -> LyingGame {
+        assert!(
+            range.contains(&oracle.secret()),
+            "the secret must be in the range of the game"
+        );
+
+        LyingGame {
+            oracle,
+            range,
+            max_attempts: None,
+        }
+    }
+
+    /// Starts a game with the range and attempt limit of `config`, drawing
+    /// the secret and the oracle's lies from `rng`.
+    pub fn from_config<R: Rng + ?Sized>(config: &Config, lies: u32, rng: &mut R) -> LyingGame {
+        let secret = rng.gen_range(config.range().clone());
+        let oracle = Oracle::new(secret, lies, StdRng::seed_from_u64(rng.gen()));
+        LyingGame::new(oracle, config.range().clone()).with_max_attempts(config.max_attempts())
+    }
+
+    /// Limits the game to `max_attempts` answered guesses, `None` meaning unlimited.
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> LyingGame {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn guess(&mut self, guess: u32) -> Reply {
+        if self.is_over() {
+            return Reply::GameOver;
+        }
+        if !self.range.contains(&guess) {
+            return Reply::OutOfRange;
+        }
+        Reply::Answered(self.oracle.answer(guess))
+    }
+
+    pub fn oracle(&self) -> &Oracle {
+        &self.oracle
+    }
+
+    pub fn range(&self) -> &RangeInclusive<u32> {
+        &self.range
+    }
+
+    pub fn history(&self) -> &[(u32, Answer)] {
+        self.oracle.transcript()
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.history().len()
+    }
+
+    pub fn max_attempts(&self) -> Option<u32> {
+        self.max_attempts
+    }
+
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.max_attempts
+            .map(|max| max.saturating_sub(self.attempts() as u32))
+    }
+
+    pub fn is_won(&self) -> bool {
+        matches!(self.history().last(), Some((_, Answer::Correct)))
+    }
+
+    pub fn is_lost(&self) -> bool {
+        !self.is_won() && self.attempts_left() == Some(0)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+}
+
+impl Puzzle for LyingGame {
+    type Guess = u32;
+    type Reply = Reply;
+
+    fn guess(&mut self, guess: u32) -> Reply {
+        LyingGame::guess(self, guess)
+    }
+
+    fn is_won(&self) -> bool {
+        LyingGame::is_won(self)
+    }
+
+    fn is_over(&self) -> bool {
+        LyingGame::is_over(self)
+    }
+}
+
+/// Why a transcript can't have come from an oracle that keeps to its rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// More answers were lies than the oracle may tell, listed by attempt.
+    TooManyLies { allowed: u32, lied_at: Vec<usize> },
+    /// A guess other than the secret was answered correct.
+    FalseCorrect { attempt: usize, guess: u32 },
+    /// The secret was guessed but not answered correct.
+    DeniedSecret { attempt: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::TooManyLies { allowed, lied_at } => {
+                let attempts: Vec<String> = lied_at.iter().map(|a| format!("#{a}")).collect();
+                write!(
+                    f,
+                    "{} answers were lies ({}), only {allowed} are allowed",
+                    lied_at.len(),
+                    attempts.join(", ")
+                )
+            }
+            Violation::FalseCorrect { attempt, guess } => {
+                write!(
+                    f,
+                    "answer #{attempt} called {guess} correct but it isn't the secret"
+                )
+            }
+            Violation::DeniedSecret { attempt } => {
+                write!(f, "answer #{attempt} denied the secret was guessed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// Checks `transcript` against the secret it was answered for and returns the
+/// attempts, counting from 1, whose answers were lies.
+pub fn verify(
+    secret: u32,
+    lies: u32,
+    transcript: &[(u32, Answer)],
+) -> Result<Vec<usize>, Violation> {
+    let mut lied_at = Vec::new();
+
+    for (index, &(guess, answer)) in transcript.iter().enumerate() {
+        let attempt = index + 1;
+        match answer {
+            Answer::Correct if guess != secret => {
+                return Err(Violation::FalseCorrect { attempt, guess })
+            }
+            _ if guess == secret && answer != Answer::Correct => {
+                return Err(Violation::DeniedSecret { attempt })
+            }
+            Answer::Higher if secret < guess => lied_at.push(attempt),
+            Answer::Lower if secret > guess => lied_at.push(attempt),
+            _ => {}
+        }
+    }
+
+    if lied_at.len() as u32 > lies {
+        return Err(Violation::TooManyLies {
+            allowed: lies,
+            lied_at,
+        });
+    }
+    Ok(lied_at)
+}
+
+/// Numbers next to each other that every answer so far treats alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    start: u32,
+    end: u32,
+    /// How many answers would have been lies if the secret were in here.
+    lies: u32,
+}
+
+impl Segment {
+    /// How many of the segment's numbers are below and above `guess`.
+    fn split(&self, guess: u32) -> (u64, u64) {
+        let len = |start: u32, end: u32| {
+            if start > end {
+                0
+            } else {
+                u64::from(end - start) + 1
+            }
+        };
+        let below = if guess == 0 {
+            0
+        } else {
+            len(self.start, self.end.min(guess - 1))
+        };
+        let above = if guess == u32::MAX {
+            0
+        } else {
+            len(self.start.max(guess + 1), self.end)
+        };
+        (below, above)
+    }
+}
+
+/// A bot that finds the secret however the oracle spends its lies.
+///
+/// For every number it tracks how many answers would have been lies if that
+/// number were the secret, dropping numbers once that exceeds `k`. Once at
+/// most [`EXACT_CANDIDATES`] numbers are left it searches every way the game
+/// can go on and picks a guess that wins in the fewest guesses the oracle
+/// can force, so from there on it plays optimally.
+///
+/// On larger states that search would never end, so the solver weighs each
+/// state by how many ways the guesses still to come could be answered from
+/// it, Berlekamp's volume, and splits the total weight as evenly as the two
+/// possible answers allow. With no lies this is binary search.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    lies: u32,
+    segments: Vec<Segment>,
+    /// Shared by clones, since what the search finds holds for every game.
+    search: Rc<RefCell<Search>>,
+}
+
+/// The most numbers [`Solver`] searches exhaustively for an optimal guess.
+pub const EXACT_CANDIDATES: u64 = 24;
+
+/// How many new shapes one search for a guess may work out before it gives
+/// up and leaves the guess to the weights.
+const SEARCH_BUDGET: usize = 20_000;
+
+impl Solver {
+    pub fn new(range: RangeInclusive<u32>, lies: u32) -> Solver {
+        assert!(!range.is_empty(), "the range of a game can't be empty");
+
+        Solver {
+            lies,
+            segments: vec![Segment {
+                start: *range.start(),
+                end: *range.end(),
+                lies: 0,
+            }],
+            search: Rc::default(),
+        }
+    }
+
+    /// A solver that already knows the answers in `transcript`.
+    pub fn from_transcript(
+        range: RangeInclusive<u32>,
+        lies: u32,
+        transcript: &[(u32, Answer)],
+    ) -> Solver {
+        let mut solver = Solver::new(range, lies);
+        for &(guess, answer) in transcript {
+            solver.learn(guess, answer);
+        }
+        solver
+    }
+
+    /// How many numbers could still be the secret.
+    pub fn candidates(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| u64::from(segment.end - segment.start) + 1)
+            .sum()
+    }
+
+    /// The numbers that could still be the secret, each with the fewest lies
+    /// the oracle must have told if it is, as `(start, end, lies)` runs.
+    pub fn states(&self) -> impl Iterator<Item = (RangeInclusive<u32>, u32)> + '_ {
+        self.segments
+            .iter()
+            .map(|segment| (segment.start..=segment.end, segment.lies))
+    }
+
+    /// Takes in the oracle's answer to `guess`.
+    pub fn learn(&mut self, guess: u32, answer: Answer) {
+        if answer == Answer::Correct {
+            self.segments = vec![Segment {
+                start: guess,
+                end: guess,
+                lies: 0,
+            }];
+            return;
+        }
+
+        let mut segments = Vec::with_capacity(self.segments.len() + 1);
+        for segment in &self.segments {
+            // the guess isn't the secret, or it would have been answered correct
+            let (below, above) = segment.split(guess);
+            let pieces = [
+                (below > 0).then(|| {
+                    (
+                        segment.start,
+                        segment.end.min(guess - 1),
+                        answer == Answer::Higher,
+                    )
+                }),
+                (above > 0).then(|| {
+                    (
+                        segment.start.max(guess + 1),
+                        segment.end,
+                        answer == Answer::Lower,
+                    )
+                }),
+            ];
+            for (start, end, contradicted) in pieces.into_iter().flatten() {
+                let lies = segment.lies + u32::from(contradicted);
+                if lies <= self.lies {
+                    segments.push(Segment { start, end, lies });
+                }
+            }
+        }
+        self.segments = segments;
+    }
+
+    /// The next guess, `None` once no number is consistent with at most `k` lies.
+    pub fn next_guess(&self) -> Option<u32> {
+        let first = self.segments.first()?;
+        let candidates = self.candidates();
+        if candidates == 1 {
+            return Some(first.start);
+        }
+
+        if candidates <= EXACT_CANDIDATES {
+            let shape = self.shape();
+            if let Some(index) = self.search.borrow_mut().best_guess(&shape) {
+                return Some(self.candidate(index));
+            }
+        }
+        Some(self.balanced_guess())
+    }
+
+    /// What the exhaustive search needs to know of the state.
+    fn shape(&self) -> Shape {
+        canonical(
+            self.segments
+                .iter()
+                .map(|segment| (segment.end - segment.start + 1, self.lies - segment.lies))
+                .collect(),
+        )
+    }
+
+    /// The candidate at `index`, counting from the smallest.
+    fn candidate(&self, mut index: u32) -> u32 {
+        for segment in &self.segments {
+            let len = segment.end - segment.start + 1;
+            if index < len {
+                return segment.start + index;
+            }
+            index -= len;
+        }
+        unreachable!("the search only picks candidates")
+    }
+
+    /// The guess that splits the weight of the states most evenly.
+    fn balanced_guess(&self) -> u32 {
+        let first = self.segments[0];
+        let last = self.segments[self.segments.len() - 1];
+
+        // weigh every state by the questions a perfect player would still need
+        let questions = self.questions_left().saturating_sub(1);
+        let weight = |lies: u32| weight(questions, self.lies.saturating_sub(lies));
+        let weights = |guess: u32| {
+            let (mut lower, mut higher) = (0u128, 0u128);
+            for segment in &self.segments {
+                let (below, above) = segment.split(guess);
+                let (same, more) = (
+                    weight(segment.lies),
+                    if segment.lies < self.lies {
+                        weight(segment.lies + 1)
+                    } else {
+                        0
+                    },
+                );
+                // a lower answer says the secret is below the guess, so numbers above it need one more lie
+                lower = lower
+                    .saturating_add(u128::from(below).saturating_mul(same))
+                    .saturating_add(u128::from(above).saturating_mul(more));
+                higher = higher
+                    .saturating_add(u128::from(above).saturating_mul(same))
+                    .saturating_add(u128::from(below).saturating_mul(more));
+            }
+            (lower, higher)
+        };
+
+        // the weight of a lower answer only grows with the guess, so search for where it
+        // overtakes the weight of a higher answer and take the better of the two neighbours
+        let (mut low, mut high) = (first.start, last.end);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let (lower, higher) = weights(middle);
+            if lower >= higher {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+
+        let cost = |guess: u32| {
+            let (lower, higher) = weights(guess);
+            lower.max(higher)
+        };
+        if low > first.start && cost(low - 1) <= cost(low) {
+            low - 1
+        } else {
+            low
+        }
+    }
+
+    /// The fewest guesses that could find the secret from here, as far as
+    /// the weights can tell: the first `q` for which the states fit in the
+    /// `2^q - 1` places a guess can be answered correct in `q` guesses.
+    fn questions_left(&self) -> u32 {
+        (1..127)
+            .find(|&questions| {
+                let total = self.segments.iter().fold(0u128, |total, segment| {
+                    let count = u128::from(segment.end - segment.start) + 1;
+                    total.saturating_add(
+                        count.saturating_mul(weight(questions, self.lies - segment.lies)),
+                    )
+                });
+                total < 1u128 << questions
+            })
+            .unwrap_or(127)
+    }
+}
+
+/// The weight of a number that may still be lied about `lies` times with
+/// `questions` guesses to go. The last guess has to hit the secret, so only
+/// the answers before it can be lies.
+fn weight(questions: u32, lies: u32) -> u128 {
+    volume(questions.saturating_sub(1), lies)
+}
+
+/// How many ways `questions` answers can hold at most `lies` lies, the sum of
+/// `C(questions, i)` for `i` up to `lies`, saturating at `u128::MAX`.
+fn volume(questions: u32, lies: u32) -> u128 {
+    let mut total = 0u128;
+    let mut binomial = 1u128;
+    for i in 0..=lies.min(questions) {
+        total = total.saturating_add(binomial);
+        binomial = match binomial.checked_mul(u128::from(questions - i)) {
+            Some(product) => product / u128::from(i + 1),
+            None => u128::MAX,
+        };
+    }
+    total
+}
+
+/// What the exhaustive search needs to know of a state: how many numbers
+/// each run of candidates holds and how many more lies the oracle may tell
+/// about them, smallest numbers first. Neither where the runs lie nor the
+/// gaps between them matter, since guessing a candidate next to a gap is
+/// never worse than guessing in the gap, so neighbouring runs with the same
+/// lies left are merged.
+type Shape = Vec<(u32, u32)>;
+
+fn canonical(shape: Shape) -> Shape {
+    let mut merged: Shape = Vec::with_capacity(shape.len());
+    for (count, lies) in shape.into_iter().filter(|&(count, _)| count > 0) {
+        match merged.last_mut() {
+            Some((last, last_lies)) if *last_lies == lies => *last += count,
+            _ => merged.push((count, lies)),
+        }
+    }
+    merged
+}
+
+fn count(shape: &Shape) -> u32 {
+    shape.iter().map(|&(count, _)| count).sum()
+}
+
+/// The shapes left after the oracle answers lower and after it answers
+/// higher to the candidate at `index`.
+fn split(shape: &Shape, index: u32) -> (Shape, Shape) {
+    let (mut lower, mut higher) = (Vec::new(), Vec::new());
+    let mut before = 0;
+    for &(count, lies) in shape {
+        let below = index.saturating_sub(before).min(count);
+        let above = (before + count).saturating_sub(index + 1).min(count);
+        before += count;
+
+        // a lower answer is the truth below the guess and a lie above it
+        lower.push((below, lies));
+        if lies > 0 {
+            lower.push((above, lies - 1));
+            higher.push((below, lies - 1));
+        }
+        higher.push((above, lies));
+    }
+    (canonical(lower), canonical(higher))
+}
+
+/// A number of guesses no player can do better than from `shape`.
+///
+/// With `q` guesses to go, give every candidate with `r` lies left the
+/// [`weight`] `w(q, r)`. A guess hands each other candidate's weight on to
+/// the two answers whole, `w(q, r) = w(q - 1, r) + w(q - 1, r - 1)`, and
+/// only the guessed candidate's is used up. So a state that can be won in
+/// `q` guesses holds at most `c(q) = 2 c(q - 1) + w(q, k)`, `k` the most
+/// lies left anywhere, and the first `q` it fits is a bound.
+fn lower_bound(shape: &Shape) -> u32 {
+    let candidates = count(shape);
+    if candidates == 0 {
+        return 0;
+    }
+    let most = shape.iter().map(|&(_, lies)| lies).max().unwrap_or(0);
+
+    let mut capacity = 0u128;
+    let by_weight = (1..)
+        .find(|&questions| {
+            capacity = capacity
+                .saturating_mul(2)
+                .saturating_add(weight(questions, most));
+            let total = shape.iter().fold(0u128, |total, &(count, lies)| {
+                total.saturating_add(u128::from(count).saturating_mul(weight(questions, lies)))
+            });
+            total <= capacity
+        })
+        .expect("the capacity saturates eventually");
+    // and however the answers go, every candidate needs a guess of its own
+    by_weight.max(u32::BITS - candidates.leading_zeros())
+}
+
+/// The fewest guesses that win from each shape searched so far, whatever
+/// the oracle answers, found by trying every guess.
+#[derive(Debug, Default)]
+struct Search {
+    fewest: HashMap<Shape, u32>,
+    /// Shapes whose search ran out of budget, not worth trying again.
+    too_large: HashSet<Shape>,
+}
+
+impl Search {
+    /// The index among the candidates of a guess that wins in the fewest
+    /// guesses, `None` if that takes more than [`SEARCH_BUDGET`] new shapes.
+    fn best_guess(&mut self, shape: &Shape) -> Option<u32> {
+        if self.too_large.contains(shape) {
+            return None;
+        }
+        let mut budget = SEARCH_BUDGET;
+        let best = self.best(shape, &mut budget);
+        if best.is_none() {
+            self.too_large.insert(shape.clone());
+        }
+        best.map(|(_, index)| index)
+    }
+
+    /// The fewest guesses that win from `shape` and a first guess that does.
+    fn best(&mut self, shape: &Shape, budget: &mut usize) -> Option<(u32, u32)> {
+        let floor = lower_bound(shape);
+        // the guesses with the lowest bounds first, they are the likeliest best
+        let mut guesses: Vec<(u32, u32, Shape, Shape)> = (0..count(shape))
+            .map(|index| {
+                let (lower, higher) = split(shape, index);
+                let bound = lower_bound(&lower).max(lower_bound(&higher));
+                (bound, index, lower, higher)
+            })
+            .collect();
+        guesses.sort_by_key(|&(bound, ..)| bound);
+
+        let mut best: Option<(u32, u32)> = None;
+        let beaten = |best: Option<(u32, u32)>, guesses: u32| {
+            best.is_some_and(|(fewest, _)| guesses >= fewest)
+        };
+        for (bound, index, lower, higher) in guesses {
+            if beaten(best, 1 + bound) {
+                break;
+            }
+            let after_lower = self.fewest(&lower, budget)?;
+            if beaten(best, 1 + after_lower) {
+                continue;
+            }
+            let after_higher = self.fewest(&higher, budget)?;
+            let guesses = 1 + after_lower.max(after_higher);
+            if !beaten(best, guesses) {
+                best = Some((guesses, index));
+            }
+            if guesses == floor {
+                break;
+            }
+        }
+        best
+    }
+
+    fn fewest(&mut self, shape: &Shape, budget: &mut usize) -> Option<u32> {
+        if count(shape) <= 1 {
+            return Some(count(shape));
+        }
+        // a shape and its mirror image take as many guesses
+        let mirrored: Shape = shape.iter().rev().copied().collect();
+        let key = mirrored.min(shape.clone());
+        if let Some(&fewest) = self.fewest.get(&key) {
+            return Some(fewest);
+        }
+
+        *budget = budget.checked_sub(1)?;
+        let (fewest, _) = self.best(shape, budget)?;
+        self.fewest.insert(key, fewest);
+        Some(fewest)
+    }
+}
+
+/// Lets the [`Solver`] play against `oracle` and returns how many guesses it took.
+///
+/// Panics if the oracle contradicts itself more than it may, which an
+/// [`Oracle`] never does.
+pub fn solve(oracle: &mut Oracle, range: RangeInclusive<u32>) -> usize {
+    let mut solver = Solver::new(range, oracle.lies());
+    loop {
+        let guess = solver
+            .next_guess()
+            .expect("the oracle told more lies than it may");
+        let answer = oracle.answer(guess);
+        if answer == Answer::Correct {
+            return oracle.transcript().len();
+        }
+        solver.learn(guess, answer);
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend<LyingGame> for Console<R, W> {
+    fn read(&mut self) -> io::Result<Input> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(Input::End);
+        }
+
+        Ok(match session::parse_action(&line) {
+            Ok(action) => Input::Action(action),
+            Err(error) => Input::Invalid(error.to_string()),
+        })
+    }
+
+    fn prompt(&mut self, _: &LyingGame) -> io::Result<()> {
+        writeln!(self.output, "Please input your guess!")
+    }
+
+    fn invalid(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.output, "{message}")
+    }
+
+    fn feedback(&mut self, game: &LyingGame, guess: u32, reply: Reply) -> io::Result<()> {
+        writeln!(self.output, "You guessed: {guess}")?;
+
+        match reply {
+            Reply::Answered(Answer::Higher) => writeln!(self.output, "Higher... or am I lying?"),
+            Reply::Answered(Answer::Lower) => writeln!(self.output, "Lower... or am I lying?"),
+            Reply::Answered(Answer::Correct) => writeln!(self.output, "You win!"),
+            Reply::OutOfRange => writeln!(
+                self.output,
+                "The secret number is between {} and {}!",
+                game.range().start(),
+                game.range().end()
+            ),
+            Reply::GameOver => Ok(()),
+        }
+    }
+
+    fn hint(&mut self, game: &LyingGame) -> io::Result<()> {
+        let lies = game.oracle().lies();
+        let solver = Solver::from_transcript(game.range().clone(), lies, game.history());
+        let left = solver.candidates();
+        let suggestion = solver
+            .next_guess()
+            .expect("the secret is always a candidate");
+
+        writeln!(
+            self.output,
+            "{left} number{} still possible with at most {lies} lie{}, the solver would guess {suggestion}.",
+            if left == 1 { " is" } else { "s are" },
+            if lies == 1 { "" } else { "s" }
+        )
+    }
+
+    fn history(&mut self, game: &LyingGame) -> io::Result<()> {
+        if game.history().is_empty() {
+            return writeln!(self.output, "No guesses yet.");
+        }
+        for (index, (guess, answer)) in game.history().iter().enumerate() {
+            writeln!(self.output, "{:>3}. {guess} {answer}", index + 1)?;
+        }
+        Ok(())
+    }
+
+    fn help(&mut self) -> io::Result<()> {
+        writeln!(self.output, "{HELP}")
+    }
+
+    fn finish(&mut self, game: &LyingGame, outcome: Outcome) -> io::Result<()> {
+        match outcome {
+            Outcome::GaveUp => {
+                writeln!(
+                    self.output,
+                    "The secret number was {}.",
+                    game.oracle().secret()
+                )?;
+                writeln!(self.output, "{}", describe_lies(game.oracle().lied_at()))
+            }
+            Outcome::Quit => writeln!(self.output, "Bye!"),
+            Outcome::EndOfInput => writeln!(self.output, "No more input, the game ends here."),
+            Outcome::Won | Outcome::Lost => Ok(()),
+        }
+    }
+}
+
+/// A sentence owning up to the lies told at `lied_at`.
+pub fn describe_lies(lied_at: &[usize]) -> String {
+    if lied_at.is_empty() {
+        return "I never lied.".to_string();
+    }
+    let attempts: Vec<String> = lied_at
+        .iter()
+        .map(|attempt| format!("#{attempt}"))
+        .collect();
+    format!("I lied about guess {}.", attempts.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::seeded_rng;
+    use crate::strategy::optimal_worst_case;
+    use std::collections::HashMap;
+
+    /// The most guesses the solver needs on `range` when the oracle picks
+    /// the secret and every lie to make the game as long as possible.
+    fn adversarial_worst_case(range: RangeInclusive<u32>, lies: u32) -> usize {
+        fn explore(
+            solver: &Solver,
+            secret: u32,
+            lies_left: u32,
+            transcript: &mut Vec<(u32, Answer)>,
+            lies: u32,
+        ) -> usize {
+            assert!(
+                transcript.len() < 64,
+                "the solver is going round in circles"
+            );
+            let guess = solver
+                .next_guess()
+                .expect("the secret is always a candidate");
+            let truth = match guess.cmp(&secret) {
+                std::cmp::Ordering::Less => Answer::Higher,
+                std::cmp::Ordering::Greater => Answer::Lower,
+                std::cmp::Ordering::Equal => {
+                    transcript.push((guess, Answer::Correct));
+                    assert!(verify(secret, lies, transcript).is_ok());
+                    transcript.pop();
+                    return transcript.len() + 1;
+                }
+            };
+            let lie = if truth == Answer::Higher {
+                Answer::Lower
+            } else {
+                Answer::Higher
+            };
+
+            let mut worst = 0;
+            for (answer, cost) in [(truth, 0), (lie, 1)] {
+                if cost > lies_left {
+                    continue;
+                }
+                let mut next = solver.clone();
+                next.learn(guess, answer);
+                transcript.push((guess, answer));
+                worst = worst.max(explore(&next, secret, lies_left - cost, transcript, lies));
+                transcript.pop();
+            }
+            worst
+        }
+
+        let solver = Solver::new(range.clone(), lies);
+        range
+            .map(|secret| explore(&solver, secret, lies, &mut Vec::new(), lies))
+            .max()
+            .unwrap()
+    }
+
+    fn oracle(secret: u32, lies: u32, seed: u64) -> Oracle {
+        Oracle::new(secret, lies, seeded_rng(seed))
+    }
+
+    #[test]
+    fn counts_volumes() {
+        assert_eq!(volume(5, 0), 1);
+        assert_eq!(volume(5, 1), 6);
+        assert_eq!(volume(5, 2), 16);
+        assert_eq!(volume(3, 9), 8);
+        assert_eq!(volume(200, 3), 1 + 200 + 19900 + 1313400);
+    }
+
+    #[test]
+    fn is_binary_search_without_lies() {
+        for n in 1..=64 {
+            assert_eq!(
+                adversarial_worst_case(1..=n, 0) as u32,
+                optimal_worst_case(u64::from(n)),
+                "n = {n}"
+            );
+        }
+    }
+
+    #[test]
+    fn wins_within_the_volume_bound() {
+        for (n, lies) in [
+            (1, 3),
+            (2, 1),
+            (10, 1),
+            (20, 1),
+            (100, 1),
+            (16, 2),
+            (30, 2),
+            (12, 3),
+        ] {
+            let worst = adversarial_worst_case(1..=n, lies);
+            let bound = Solver::new(1..=n, lies).questions_left() as usize;
+            assert!(
+                worst <= bound,
+                "n = {n}, k = {lies}: {worst} guesses, bound {bound}"
+            );
+        }
+    }
+
+    /// The fewest guesses any player needs in the worst case, by trying them
+    /// all. `state` holds how many lies each number needs, `None` once it's
+    /// out.
+    fn optimum(
+        state: &[Option<u32>],
+        lies: u32,
+        memo: &mut HashMap<Vec<Option<u32>>, usize>,
+    ) -> usize {
+        let alive: Vec<usize> = (0..state.len()).filter(|&i| state[i].is_some()).collect();
+        match alive[..] {
+            [] => return 0,
+            [_] => return 1,
+            _ => {}
+        }
+        if let Some(&known) = memo.get(state) {
+            return known;
+        }
+
+        let mut best = usize::MAX;
+        for guess in alive[0]..=alive[alive.len() - 1] {
+            let mut worst = 1;
+            for lower in [true, false] {
+                let next: Vec<Option<u32>> = (0..state.len())
+                    .map(|i| {
+                        let contradicted = if lower { i > guess } else { i < guess };
+                        state[i]
+                            .filter(|_| i != guess)
+                            .map(|needed| needed + u32::from(contradicted))
+                            .filter(|&needed| needed <= lies)
+                    })
+                    .collect();
+                if next == state {
+                    worst = usize::MAX;
+                    break;
+                }
+                worst = worst.max(1 + optimum(&next, lies, memo));
+            }
+            best = best.min(worst);
+        }
+        memo.insert(state.to_vec(), best);
+        best
+    }
+
+    #[test]
+    fn matches_the_optimum() {
+        let cases = (1..=12).map(|n| (n, 1)).chain((1..=8).map(|n| (n, 2)));
+        for (n, lies) in cases {
+            let optimum = optimum(&vec![Some(0); n as usize], lies, &mut HashMap::new());
+            let worst = adversarial_worst_case(1..=n, lies);
+            assert_eq!(worst, optimum, "n = {n}, k = {lies}");
+        }
+        assert_eq!(adversarial_worst_case(1..=8, 1), 5);
+        assert_eq!(adversarial_worst_case(1..=10, 2), 7);
+    }
+
+    #[test]
+    fn oracle_never_lies_more_than_allowed() {
+        for seed in 0..200 {
+            let lies = (seed % 4) as u32;
+            let mut rng = seeded_rng(seed);
+            let secret = rng.gen_range(1..=1000);
+            let mut oracle = oracle(secret, lies, seed);
+
+            solve(&mut oracle, 1..=1000);
+            let lied_at = verify(secret, lies, oracle.transcript()).unwrap();
+            assert_eq!(lied_at, oracle.lied_at());
+        }
+    }
+
+    #[test]
+    fn verifier_catches_broken_oracles() {
+        let transcript = [
+            (50, Answer::Lower),
+            (25, Answer::Lower),
+            (12, Answer::Higher),
+        ];
+        assert_eq!(verify(30, 2, &transcript), Ok(vec![2]));
+        assert_eq!(
+            verify(60, 1, &transcript),
+            Err(Violation::TooManyLies {
+                allowed: 1,
+                lied_at: vec![1, 2]
+            })
+        );
+        assert_eq!(
+            verify(7, 3, &[(7, Answer::Lower)]),
+            Err(Violation::DeniedSecret { attempt: 1 })
+        );
+        assert_eq!(
+            verify(7, 3, &[(3, Answer::Higher), (5, Answer::Correct)]),
+            Err(Violation::FalseCorrect {
+                attempt: 2,
+                guess: 5
+            })
+        );
+    }
+
+    #[test]
+    fn solver_drops_numbers_needing_too_many_lies() {
+        let mut solver = Solver::new(1..=10, 1);
+        solver.learn(5, Answer::Higher);
+        solver.learn(8, Answer::Higher);
+        let states: Vec<_> = solver.states().collect();
+        assert_eq!(states, [(6..=7, 1), (9..=10, 0)]);
+
+        solver.learn(9, Answer::Lower);
+        let states: Vec<_> = solver.states().collect();
+        assert_eq!(states, [(6..=7, 1), (10..=10, 1)]);
+
+        solver.learn(7, Answer::Lower);
+        assert_eq!(solver.candidates(), 1);
+        assert_eq!(solver.next_guess(), Some(6));
+    }
+
+    #[test]
+    fn plays_on_the_console() {
+        let mut game = LyingGame::new(oracle(3, 0, 1), 1..=10).with_max_attempts(Some(5));
+        let mut output = Vec::new();
+        let outcome = session::run(
+            &mut game,
+            &mut Console {
+                input: "5\n5\n11\nhint\nhistory\n3\n".as_bytes(),
+                output: &mut output,
+            },
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(outcome, Outcome::Won);
+        assert!(output.contains("You guessed: 5\nLower... or am I lying?\n"));
+        assert!(output.contains("The secret number is between 1 and 10!"));
+        assert!(output.contains("4 numbers are still possible with at most 0 lies"));
+        assert!(output.contains("  2. 5 lower\n"));
+        assert!(output.ends_with("You win!\n"));
+        assert_eq!(game.attempts(), 3);
+    }
+}
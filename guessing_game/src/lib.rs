@@ -0,0 +1,34 @@
+//! The rules of the guessing game, kept apart from any particular front-end.
+//!
+//! The binary in `main.rs` only reads lines from stdin and prints whatever
+//! [`Feedback`] the [`Game`] hands back, so everything in here can be driven
+//! from tests, bots or other front-ends just as well.
+
+pub mod bulls_cows;
+pub mod cli;
+pub mod coach;
+pub mod commit;
+mod config;
+pub mod daily;
+mod date;
+pub mod domain;
+mod game;
+pub mod json;
+pub mod leaderboard;
+pub mod liar;
+pub mod machine;
+pub mod net;
+pub mod policy;
+pub mod reverse;
+pub mod save;
+pub mod session;
+pub mod sha256;
+pub mod simulation;
+pub mod strategy;
+pub mod tournament;
+pub mod transcript;
+pub mod web;
+
+pub use config::{random_seed, seeded_rng, Config, ConfigError, Difficulty};
+pub use date::Date;
+pub use game::{Feedback, Game};
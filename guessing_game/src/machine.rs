@@ -0,0 +1,363 @@
+//! The `--machine` front-end, for bots and scripts instead of people.
+//!
+//! Every line either way is one JSON object. The program writes these, each
+//! with a `type` member saying which it is:
+//!
+//! ```text
+//! {"type":"start","min":1,"max":100,"max_attempts":null,"seed":42}
+//! {"type":"prompt","attempt":1,"attempts_left":null}
+//! {"type":"feedback","guess":50,"result":"too_big","trend":null,"heat":null,"attempts":1}
+//! {"type":"hint","min":1,"max":49}
+//! {"type":"history","guesses":[{"guess":50,"result":"too_big","trend":null,"heat":null}]}
+//! {"type":"help","commands":["guess","hint","history","giveup","quit"]}
+//! {"type":"error","message":"..."}
+//! {"type":"result","outcome":"won","attempts":6,"guesses":[50,25,37,31,34,33],"secret":33}
+//! ```
+//!
+//! `start` comes first and `result` last, and a `prompt` is written whenever
+//! the program waits for a line. `attempts_left` and `max_attempts` are null
+//! when there is no limit. `result` is one of `too_small`, `too_big`, `win`,
+//! `out_of_range`, `already_guessed` or `game_over`, or `miss` when the
+//! feedback policy hides which way a guess was off. `trend` is `warmer`,
+//! `colder` or `same` and `heat` runs from `freezing` to `burning`, both null
+//! unless the policy gives them, and a `hint` is an `error` under a policy
+//! that hides which way guesses were off. `outcome` is one of
+//! `won`, `lost`, `gave_up`, `quit` or `end_of_input`. The secret in the final
+//! message is null when the game was left without being decided.
+//!
+//! The program reads either of
+//!
+//! ```text
+//! {"guess":50}
+//! {"command":"hint"}
+//! ```
+//!
+//! where the command is one of `hint`, `history`, `help`, `giveup` or `quit`.
+//! Anything else gets an `error` message and changes nothing.
+
+use crate::game::{Feedback, Game};
+use crate::json::Value;
+use crate::policy::{Clue, Heat, Trend};
+use crate::session::{self, Action, Frontend, Input, Outcome};
+use std::io::{self, BufRead, Write};
+
+pub struct JsonLines<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> JsonLines<R, W> {
+    pub fn new(input: R, output: W) -> JsonLines<R, W> {
+        JsonLines { input, output }
+    }
+
+    /// Announces a game, before it's handed to [`crate::session::run`].
+    pub fn start(&mut self, game: &Game, seed: u64) -> io::Result<()> {
+        self.send(Value::object([
+            ("type", "start".into()),
+            ("min", (*game.range().start()).into()),
+            ("max", (*game.range().end()).into()),
+            ("max_attempts", game.max_attempts().into()),
+            ("seed", seed.into()),
+        ]))
+    }
+
+    fn send(&mut self, message: Value) -> io::Result<()> {
+        writeln!(self.output, "{message}")?;
+        // whoever drives us waits for each line, so none may sit in a buffer
+        self.output.flush()
+    }
+}
+
+/// Turns one line of input into the action it asks for.
+pub fn parse_request(line: &str) -> Result<Action, String> {
+    let request = Value::parse(line).map_err(|error| error.to_string())?;
+    if !matches!(request, Value::Object(_)) {
+        return Err("expected a JSON object".to_string());
+    }
+
+    if let Some(guess) = request.get("guess") {
+        return guess
+            .as_u32()
+            .map(Action::Guess)
+            .ok_or_else(|| format!("`guess` must be a whole number from 0 to {}", u32::MAX));
+    }
+
+    match request.get("command").map(|command| command.as_str()) {
+        Some(Some("hint")) => Ok(Action::Hint),
+        Some(Some("history")) => Ok(Action::History),
+        Some(Some("help")) => Ok(Action::Help),
+        Some(Some("giveup")) => Ok(Action::GiveUp),
+        Some(Some("quit")) => Ok(Action::Quit),
+        Some(Some(command)) => Err(format!("unknown command `{command}`")),
+        Some(None) => Err("`command` must be a string".to_string()),
+        None => Err("expected a `guess` or a `command` member".to_string()),
+    }
+}
+
+pub fn feedback_name(feedback: Feedback) -> &'static str {
+    match feedback {
+        Feedback::TooSmall => "too_small",
+        Feedback::TooBig => "too_big",
+        Feedback::Win => "win",
+        Feedback::OutOfRange => "out_of_range",
+        Feedback::AlreadyGuessed => "already_guessed",
+        Feedback::GameOver => "game_over",
+    }
+}
+
+/// Fills in what the game's feedback policy says about a missed guess.
+fn describe_miss(clue: Clue, result: &mut Value, trend: &mut Value, heat: &mut Value) {
+    if clue.direction.is_none() {
+        *result = "miss".into();
+    }
+    *trend = clue.trend.map(Trend::name).into();
+    *heat = clue.heat.map(Heat::name).into();
+}
+
+/// The `index`th guess of `game` as it appears in a `history` message.
+pub fn describe_guess(game: &Game, index: usize) -> Value {
+    let guess = game.history()[index];
+    let feedback = Feedback::from(guess.cmp(&game.secret()));
+    let mut result = feedback_name(feedback).into();
+    let (mut trend, mut heat) = (Value::Null, Value::Null);
+    if feedback != Feedback::Win {
+        describe_miss(game.clue(index), &mut result, &mut trend, &mut heat);
+    }
+
+    Value::object([
+        ("guess", guess.into()),
+        ("result", result),
+        ("trend", trend),
+        ("heat", heat),
+    ])
+}
+
+pub fn outcome_name(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Won => "won",
+        Outcome::Lost => "lost",
+        Outcome::GaveUp => "gave_up",
+        Outcome::Quit => "quit",
+        Outcome::EndOfInput => "end_of_input",
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for JsonLines<R, W> {
+    fn read(&mut self) -> io::Result<Input> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(Input::End);
+        }
+
+        Ok(match parse_request(&line) {
+            Ok(action) => Input::Action(action),
+            Err(message) => Input::Invalid(message),
+        })
+    }
+
+    fn prompt(&mut self, game: &Game) -> io::Result<()> {
+        self.send(Value::object([
+            ("type", "prompt".into()),
+            ("attempt", (game.attempts() + 1).into()),
+            ("attempts_left", game.attempts_left().into()),
+        ]))
+    }
+
+    fn invalid(&mut self, message: &str) -> io::Result<()> {
+        self.send(Value::object([
+            ("type", "error".into()),
+            ("message", message.into()),
+        ]))
+    }
+
+    fn feedback(&mut self, game: &Game, guess: u32, feedback: Feedback) -> io::Result<()> {
+        let mut result = feedback_name(feedback).into();
+        let (mut trend, mut heat) = (Value::Null, Value::Null);
+        if matches!(feedback, Feedback::TooSmall | Feedback::TooBig) {
+            let clue = game.clue(game.attempts() - 1);
+            describe_miss(clue, &mut result, &mut trend, &mut heat);
+        }
+
+        self.send(Value::object([
+            ("type", "feedback".into()),
+            ("guess", guess.into()),
+            ("result", result),
+            ("trend", trend),
+            ("heat", heat),
+            ("attempts", game.attempts().into()),
+        ]))
+    }
+
+    fn hint(&mut self, game: &Game) -> io::Result<()> {
+        let Some(bounds) = game.hint() else {
+            return self.invalid(&session::no_hint(game));
+        };
+        self.send(Value::object([
+            ("type", "hint".into()),
+            ("min", (*bounds.start()).into()),
+            ("max", (*bounds.end()).into()),
+        ]))
+    }
+
+    fn history(&mut self, game: &Game) -> io::Result<()> {
+        let guesses = (0..game.attempts())
+            .map(|index| describe_guess(game, index))
+            .collect();
+
+        self.send(Value::object([
+            ("type", "history".into()),
+            ("guesses", Value::Array(guesses)),
+        ]))
+    }
+
+    fn help(&mut self) -> io::Result<()> {
+        self.send(Value::object([
+            ("type", "help".into()),
+            (
+                "commands",
+                vec!["guess", "hint", "history", "giveup", "quit"].into(),
+            ),
+        ]))
+    }
+
+    fn finish(&mut self, game: &Game, outcome: Outcome) -> io::Result<()> {
+        let decided = matches!(outcome, Outcome::Won | Outcome::Lost | Outcome::GaveUp);
+
+        self.send(Value::object([
+            ("type", "result".into()),
+            ("outcome", outcome_name(outcome).into()),
+            ("attempts", game.attempts().into()),
+            ("guesses", game.history().to_vec().into()),
+            ("secret", decided.then(|| game.secret()).into()),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::FeedbackPolicy;
+
+    #[test]
+    fn parses_requests() {
+        assert_eq!(parse_request(r#"{"guess": 7}"#), Ok(Action::Guess(7)));
+        assert_eq!(
+            parse_request(r#" {"command":"giveup"} "#),
+            Ok(Action::GiveUp)
+        );
+        assert!(parse_request(r#"{"guess": -7}"#).is_err());
+        assert!(parse_request(r#"{"guess": "7"}"#).is_err());
+        assert!(parse_request(r#"{"command": "cheat"}"#).is_err());
+        assert!(parse_request("[1]").is_err());
+        assert!(parse_request("7").is_err());
+    }
+
+    #[test]
+    fn writes_one_object_per_line() {
+        let mut game = Game::new(3, 1..=4);
+        let input = "{\"guess\":2}\nnot json\n{\"command\":\"hint\"}\n{\"guess\":3}\n";
+        let mut output = Vec::new();
+
+        let mut frontend = JsonLines::new(input.as_bytes(), &mut output);
+        frontend.start(&game, 9).unwrap();
+        let outcome = session::run(&mut game, &mut frontend).unwrap();
+        assert_eq!(outcome, Outcome::Won);
+
+        let lines: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| Value::parse(line).unwrap())
+            .collect();
+        let types: Vec<&str> = lines
+            .iter()
+            .map(|line| line.get("type").unwrap().as_str().unwrap())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "start", "prompt", "feedback", "prompt", "error", "prompt", "hint", "prompt",
+                "feedback", "result"
+            ]
+        );
+        assert_eq!(lines[6].get("min").and_then(Value::as_u32), Some(3));
+        assert_eq!(lines[9].get("secret").and_then(Value::as_u32), Some(3));
+    }
+
+    #[test]
+    fn hides_the_direction_when_the_policy_does() {
+        let mut game = Game::new(20, 1..=100).with_policy(FeedbackPolicy::Heat);
+        let mut output = Vec::new();
+        session::run(
+            &mut game,
+            &mut JsonLines::new("{\"guess\":95}\n{\"guess\":24}\n".as_bytes(), &mut output),
+        )
+        .unwrap();
+
+        let feedback: Vec<String> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .filter(|line| line.contains("\"feedback\""))
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            feedback,
+            [
+                r#"{"type":"feedback","guess":95,"result":"miss","trend":null,"heat":"freezing","attempts":1}"#,
+                r#"{"type":"feedback","guess":24,"result":"miss","trend":"warmer","heat":"hot","attempts":2}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn hints_give_nothing_away_when_the_policy_hides_the_direction() {
+        for policy in [FeedbackPolicy::WarmerColder, FeedbackPolicy::Heat] {
+            let mut game = Game::new(80, 1..=100).with_policy(policy);
+            let mut output = Vec::new();
+            session::run(
+                &mut game,
+                &mut JsonLines::new(
+                    "{\"guess\":50}\n{\"command\":\"hint\"}\n".as_bytes(),
+                    &mut output,
+                ),
+            )
+            .unwrap();
+
+            let output = String::from_utf8(output).unwrap();
+            let reply = output
+                .lines()
+                .map(|line| Value::parse(line).unwrap())
+                .find(|line| line.get("type").and_then(Value::as_str) == Some("error"))
+                .unwrap_or_else(|| panic!("{policy}: no error in {output}"));
+            assert!(reply
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap()
+                .contains(policy.name()));
+            assert!(!output.contains("\"hint\""), "{policy}: {output}");
+        }
+    }
+
+    #[test]
+    fn keeps_the_secret_when_quitting() {
+        let mut output = Vec::new();
+        let outcome = session::run(
+            &mut Game::new(3, 1..=4),
+            &mut JsonLines::new("{\"command\":\"quit\"}\n".as_bytes(), &mut output),
+        )
+        .unwrap();
+
+        assert_eq!(outcome, Outcome::Quit);
+        let last = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .last()
+            .unwrap()
+            .to_string();
+        assert!(Value::parse(&last)
+            .unwrap()
+            .get("secret")
+            .unwrap()
+            .is_null());
+    }
+}
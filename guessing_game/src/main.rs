@@ -1,36 +1,727 @@
-use rand::Rng;
-use std::cmp::Ordering;
-use std::io;
+use guessing_game::bulls_cows::BullsCows;
+use guessing_game::cli::{self, Command};
+use guessing_game::coach::{self, Coached};
+use guessing_game::commit::{Commitment, Salt};
+use guessing_game::daily::Daily;
+use guessing_game::domain::{Decimals, Dictionary, Domain, Guessing, Integers};
+use guessing_game::leaderboard::{Leaderboard, Score};
+use guessing_game::liar::{self, LyingGame};
+use guessing_game::machine::JsonLines;
+use guessing_game::net::{Event, RaceResult, Request, Server};
+use guessing_game::save::SavedGame;
+use guessing_game::session::{self, plural, Action, Console, Outcome};
+use guessing_game::tournament::{Tournament, TournamentConfig, TournamentOutcome};
+use guessing_game::transcript::{self, Recorder, Transcript};
+use guessing_game::web;
+use guessing_game::{
+    random_seed, reverse, seeded_rng, simulation, strategy, Config, Date, Feedback, Game,
+};
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use std::{env, fmt, io, process, thread};
 
 fn main() {
-    println!("Guess the number!");
+    let mut daily = None;
+    let (config, mut game) = match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Play(config)) => {
+            println!("Guess the number!");
 
-    let secret_number = rand::thread_rng().gen_range(1..=10);
+            // even unseeded games get a seed, so any of them can be replayed with --seed
+            let seed = config.seed().unwrap_or_else(random_seed);
+            let game = Game::from_config(&config, &mut seeded_rng(seed)).with_seed(seed);
+            (config, game)
+        }
+        Ok(Command::Daily(config, date)) => {
+            let puzzle = Daily::new(date.unwrap_or_else(Date::today));
+            println!(
+                "Guess the number of the day! This is the challenge of {} (UTC).",
+                puzzle.date()
+            );
+            daily = Some(puzzle);
+            (config, puzzle.game())
+        }
+        Ok(Command::Resume(config, file)) => {
+            match SavedGame::load(&file).and_then(|saved| saved.restore()) {
+                Ok(game) => {
+                    // the command line couldn't tell what feedback the saved game uses
+                    if let Some(reason) = config.coach().then(|| coach::refusal(&game)).flatten() {
+                        eprintln!(
+                            "error: can't resume {} with --coach: {reason}",
+                            file.display()
+                        );
+                        process::exit(2);
+                    }
+                    println!("Resuming the game saved in {}.", file.display());
+                    (config, game)
+                }
+                Err(error) => {
+                    eprintln!("error: can't resume {}: {error}", file.display());
+                    process::exit(1);
+                }
+            }
+        }
+        Ok(Command::Replay(file)) => {
+            run_replay(&file);
+            return;
+        }
+        Ok(Command::Simulate(config, games)) => {
+            run_simulation(&config, games);
+            return;
+        }
+        Ok(Command::Leaderboard) => {
+            print_leaderboard();
+            return;
+        }
+        Ok(Command::Machine(config)) => {
+            run_machine(&config);
+            return;
+        }
+        Ok(Command::Reverse(config)) => {
+            run_reverse(&config);
+            return;
+        }
+        Ok(Command::BullsCows(config, digits)) => {
+            run_bulls_cows(&config, digits);
+            return;
+        }
+        Ok(Command::Lies(config, lies)) => {
+            run_lies(&config, lies);
+            return;
+        }
+        Ok(Command::Big(config, max)) => {
+            let domain = Integers(1..=max);
+            let secret = domain.random(&mut seeded_rng(seed_of(&config)));
+            run_domain(&config, Guessing::new(domain, secret));
+            return;
+        }
+        Ok(Command::Decimals(config, tolerance)) => {
+            let range = config.range();
+            let domain = Decimals::new(
+                f64::from(*range.start())..=f64::from(*range.end()),
+                tolerance,
+            );
+            let secret = domain.random(&mut seeded_rng(seed_of(&config)));
+            run_domain(&config, Guessing::new(domain, secret));
+            return;
+        }
+        Ok(Command::Words(config)) => {
+            let domain = Dictionary::builtin();
+            let secret = domain.random(&mut seeded_rng(seed_of(&config)));
+            run_domain(&config, Guessing::new(domain, secret));
+            return;
+        }
+        Ok(Command::Tournament(config, file)) => {
+            run_tournament(&config, &file);
+            return;
+        }
+        Ok(Command::Host(config, port)) => {
+            run_host(&config, port);
+            return;
+        }
+        Ok(Command::Serve(config, port)) => {
+            run_serve(&config, port);
+            return;
+        }
+        Ok(Command::Join { address, name }) => {
+            run_join(&address, name);
+            return;
+        }
+        Ok(Command::Verify {
+            commitment,
+            secret,
+            salt,
+        }) => {
+            run_verify(&commitment, secret, &salt);
+            return;
+        }
+        Ok(Command::Help) => {
+            print!("{}", cli::USAGE);
+            return;
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{}", cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    let seed = game.seed().expect("every game played here has a seed");
+    println!("Seed: {seed}");
+
+    println!(
+        "The secret number is between {} and {}.",
+        game.range().start(),
+        game.range().end()
+    );
+    if let Some(left) = game.attempts_left() {
+        println!("You have {}.", plural(left as usize, "attempt"));
+    }
+    if game.attempts() > 0 {
+        println!(
+            "{} made before the game was saved, type `history` to see them.",
+            if game.attempts() == 1 {
+                "1 guess was".to_string()
+            } else {
+                format!("{} guesses were", game.attempts())
+            }
+        );
+    }
+
+    // the salt can't come from the printed seed, or it would be no secret;
+    // a resumed game keeps the one it started with, and so its commitment
+    let salt = game
+        .salt()
+        .unwrap_or_else(|| Salt::random(&mut rand::thread_rng()));
+    game = game.with_salt(salt);
+    let commitment = Commitment::new(game.secret(), &salt);
+    println!("Commitment: {commitment}");
+
+    println!("Type `help` to see the commands.");
+
+    let recorder =
+        config
+            .record()
+            .map(|path| match Recorder::create(path, &game, config.coach()) {
+                Ok(recorder) => {
+                    println!("Recording the game to {}.", path.display());
+                    recorder
+                }
+                Err(error) => {
+                    eprintln!("error: can't record to {}: {error}", path.display());
+                    process::exit(1);
+                }
+            });
+
+    let started = Instant::now();
+    let stdin = io::stdin();
+    let outcome = match &recorder {
+        Some(recorder) => play_console(
+            &mut game,
+            recorder.console(stdin.lock(), io::stdout()),
+            config.coach(),
+        ),
+        None => play_console(
+            &mut game,
+            Console {
+                input: stdin.lock(),
+                output: io::stdout(),
+            },
+            config.coach(),
+        ),
+    }
+    .expect("Failed to read line");
+    if let Some(recorder) = recorder {
+        if let Err(error) = recorder.finish(outcome) {
+            eprintln!("warning: the transcript is incomplete: {error}");
+        }
+    }
+
+    if outcome != Outcome::Quit {
+        print_reveal(&commitment, game.secret(), &salt);
+    }
+    if outcome == Outcome::EndOfInput {
+        process::exit(1);
+    }
+    if matches!(outcome, Outcome::Won | Outcome::Lost) {
+        print_summary(&game, seed);
+        if let Some(puzzle) = daily {
+            println!("\nShare your result:\n{}", puzzle.share(&game));
+        }
+        record_score(&config, &game, seed, started.elapsed());
+    }
+}
 
-    loop {
-        let mut guess = String::new();
+/// Plays `game` on `console`, with the coach grading every guess if `coach` is set.
+fn play_console<R: BufRead, W: Write>(
+    game: &mut Game,
+    mut console: Console<R, W>,
+    coach: bool,
+) -> io::Result<Outcome> {
+    if coach {
+        let mut coached = Coached::new(console, game);
+        session::run(game, &mut coached)
+    } else {
+        session::run(game, &mut console)
+    }
+}
 
-        println!("Please input your guess!");
+fn print_reveal(commitment: &Commitment, secret: u32, salt: &Salt) {
+    println!("Reveal: secret {secret}, salt {salt}");
+    println!("Check it with: guessing_game verify {commitment} {secret} {salt}");
+}
+
+fn run_verify(commitment: &Commitment, secret: u32, salt: &Salt) {
+    if commitment.verify(secret, salt) {
+        println!("The commitment matches: the secret was {secret} from the start.");
+    } else {
+        println!("The commitment does NOT match secret {secret} with that salt.");
+        process::exit(1);
+    }
+}
+
+fn run_replay(file: &Path) {
+    let replayed = Transcript::load(file).and_then(|transcript| {
+        transcript::replay(&transcript)?;
+        Ok(transcript)
+    });
+    match replayed {
+        Ok(transcript) => println!(
+            "Replayed {} of {}: the engine agrees with every one.",
+            plural(transcript.entries.len(), "line"),
+            file.display()
+        ),
+        Err(error) => {
+            eprintln!("error: {} doesn't replay: {error}", file.display());
+            process::exit(1);
+        }
+    }
+}
 
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line"); // guess will include the newline that the user will
-                                            // input to end the input, so, it will also be printed if we print guess
+fn print_summary(game: &Game, seed: u64) {
+    let guesses: Vec<String> = game.history().iter().map(u32::to_string).collect();
+
+    if game.is_won() {
+        println!(
+            "Found {} in {}.",
+            game.secret(),
+            plural(game.attempts(), "attempt")
+        );
+    } else {
+        println!("Out of attempts! The secret number was {}.", game.secret());
+    }
+    println!(
+        "Range {}..={}, seed {seed}, guesses: {}",
+        game.range().start(),
+        game.range().end(),
+        guesses.join(", ")
+    );
+}
+
+fn run_machine(config: &Config) {
+    let seed = config.seed().unwrap_or_else(random_seed);
+    let mut game = Game::from_config(config, &mut seeded_rng(seed));
+
+    let started = Instant::now();
+    let stdin = io::stdin();
+    let mut frontend = JsonLines::new(stdin.lock(), io::stdout().lock());
+    let outcome = frontend
+        .start(&game, seed)
+        .and_then(|()| session::run(&mut game, &mut frontend))
+        .expect("Failed to talk over stdin and stdout");
+
+    match outcome {
+        Outcome::Won | Outcome::Lost => record_score(config, &game, seed, started.elapsed()),
+        Outcome::EndOfInput => process::exit(1),
+        Outcome::GaveUp | Outcome::Quit => {}
+    }
+}
+
+/// Adds a finished game to the leaderboard, warning on stderr if it can't.
+fn record_score(config: &Config, game: &Game, seed: u64, elapsed: Duration) {
+    let Some(path) = Leaderboard::default_path() else {
+        eprintln!("warning: no data directory was found, so the score was not saved");
+        return;
+    };
+
+    let player = config
+        .player()
+        .map(str::to_string)
+        .or_else(|| env::var("USER").ok())
+        .or_else(|| env::var("USERNAME").ok())
+        .unwrap_or_else(|| "anonymous".to_string());
+    let score = Score {
+        player,
+        range: game.range().clone(),
+        max_attempts: game.max_attempts(),
+        won: game.is_won(),
+        attempts: game.attempts() as u32,
+        elapsed,
+        seed,
+        date: Date::today(),
+    };
+
+    if let Err(error) = Leaderboard::record(&path, score) {
+        eprintln!(
+            "warning: the score was not saved to {}: {error}",
+            path.display()
+        );
+    }
+}
+
+fn print_leaderboard() {
+    const SHOWN: usize = 10;
+
+    let Some(path) = Leaderboard::default_path() else {
+        eprintln!("error: no data directory was found");
+        process::exit(1);
+    };
+    let leaderboard = match Leaderboard::load(&path) {
+        Ok(leaderboard) => leaderboard,
+        Err(error) => {
+            eprintln!("error: can't read {}: {error}", path.display());
+            process::exit(1);
+        }
+    };
+
+    let categories = leaderboard.categories();
+    if categories.is_empty() {
+        println!("Nobody has won a game yet.");
+    }
+    for category in categories {
+        println!("{category}");
+        for (rank, score) in leaderboard.top(&category, SHOWN).iter().enumerate() {
+            let seconds = score.elapsed.as_secs_f64();
+            println!(
+                "{:>4}. {:<16} {:>12} {:>8.1}s  {}  seed {}",
+                rank + 1,
+                score.player,
+                plural(score.attempts as usize, "attempt"),
+                seconds,
+                score.date,
+                score.seed
+            );
+        }
+        println!();
+    }
+}
+
+fn run_simulation(config: &Config, games: usize) {
+    let seed = config.seed().unwrap_or_else(random_seed);
+    let range = config.range();
+    let size = u64::from(range.end() - range.start()) + 1;
+
+    println!(
+        "Playing {games} games per strategy on {}..={} with seed {seed}",
+        range.start(),
+        range.end()
+    );
+    println!(
+        "No strategy can promise fewer than {} guesses on {size} numbers.\n",
+        strategy::optimal_worst_case(size)
+    );
+
+    for stats in simulation::simulate_all(config, games, seed) {
+        println!("{stats}");
+    }
+    for name in simulation::impractical(config) {
+        println!("{name:<8} left out, it would take too long on {size} numbers");
+    }
+}
 
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
-        };
+fn run_reverse(config: &Config) {
+    println!(
+        "Pick a number between {} and {} and I'll guess it!",
+        config.range().start(),
+        config.range().end()
+    );
 
-        println!("You guessed: {}", guess);
+    let stdin = io::stdin();
+    let outcome = reverse::play(config.range().clone(), stdin.lock(), io::stdout())
+        .expect("Failed to read line");
 
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break;
+    match outcome {
+        reverse::Outcome::Found(..) | reverse::Outcome::Quit => {}
+        reverse::Outcome::Contradiction(_) | reverse::Outcome::EndOfInput => process::exit(1),
+    }
+}
+
+fn run_bulls_cows(config: &Config, digits: usize) {
+    println!("Guess the code! It has {digits} digits and none of them repeat.");
+
+    let seed = config.seed().unwrap_or_else(random_seed);
+    println!("Seed: {seed}");
+
+    let mut game =
+        BullsCows::with_rng(digits, &mut seeded_rng(seed)).with_max_attempts(config.max_attempts());
+    if let Some(max) = game.max_attempts() {
+        println!("You have {max} attempts.");
+    }
+    println!("Type `help` to see the commands.");
+
+    let stdin = io::stdin();
+    let mut console = Console {
+        input: stdin.lock(),
+        output: io::stdout(),
+    };
+    let outcome = session::run(&mut game, &mut console).expect("Failed to read line");
+
+    match outcome {
+        Outcome::Won => println!(
+            "Found {} in {}.",
+            game.secret(),
+            plural(game.attempts(), "attempt")
+        ),
+        Outcome::Lost => println!("Out of attempts! The secret code was {}.", game.secret()),
+        Outcome::EndOfInput => process::exit(1),
+        Outcome::GaveUp | Outcome::Quit => {}
+    }
+}
+
+fn run_lies(config: &Config, lies: u32) {
+    println!(
+        "Guess the number! I may lie about higher or lower {}.",
+        plural(lies as usize, "time")
+    );
+
+    let seed = config.seed().unwrap_or_else(random_seed);
+    println!("Seed: {seed}");
+
+    let mut game = LyingGame::from_config(config, lies, &mut seeded_rng(seed));
+    println!(
+        "The secret number is between {} and {}.",
+        game.range().start(),
+        game.range().end()
+    );
+    if let Some(max) = game.max_attempts() {
+        println!("You have {max} attempts.");
+    }
+    println!("Type `help` to see the commands.");
+
+    let stdin = io::stdin();
+    let mut console = Console {
+        input: stdin.lock(),
+        output: io::stdout(),
+    };
+    let outcome = session::run(&mut game, &mut console).expect("Failed to read line");
+
+    let oracle = game.oracle();
+    match outcome {
+        Outcome::Won => println!(
+            "Found {} in {}.",
+            oracle.secret(),
+            plural(game.attempts(), "attempt")
+        ),
+        Outcome::Lost => println!(
+            "Out of attempts! The secret number was {}.",
+            oracle.secret()
+        ),
+        Outcome::EndOfInput => process::exit(1),
+        Outcome::GaveUp | Outcome::Quit => return,
+    }
+    // the oracle's own count is checked against the transcript, not just trusted
+    match liar::verify(oracle.secret(), lies, oracle.transcript()) {
+        Ok(lied_at) => println!("{}", liar::describe_lies(&lied_at)),
+        Err(violation) => {
+            eprintln!("error: the oracle broke its rules: {violation}");
+            process::exit(1);
+        }
+    }
+}
+
+/// The seed of `config`, or a new one, printed so the game can be replayed.
+fn seed_of(config: &Config) -> u64 {
+    let seed = config.seed().unwrap_or_else(random_seed);
+    println!("Seed: {seed}");
+    seed
+}
+
+fn run_domain<D: Domain>(config: &Config, game: Guessing<D>)
+where
+    <D::Value as FromStr>::Err: fmt::Display,
+{
+    let mut game = game.with_max_attempts(config.max_attempts());
+    println!("Guess the secret! It is {}.", game.domain());
+    if let Some(max) = game.max_attempts() {
+        println!("You have {max} attempts.");
+    }
+    println!("Type `help` to see the commands.");
+
+    let stdin = io::stdin();
+    let mut console = Console {
+        input: stdin.lock(),
+        output: io::stdout(),
+    };
+    let outcome = session::run(&mut game, &mut console).expect("Failed to read line");
+
+    match outcome {
+        Outcome::Won => println!(
+            "Found {} in {}.",
+            game.secret(),
+            plural(game.attempts(), "attempt")
+        ),
+        Outcome::Lost => println!("Out of attempts! The secret was {}.", game.secret()),
+        Outcome::EndOfInput => process::exit(1),
+        Outcome::GaveUp | Outcome::Quit => {}
+    }
+}
+
+fn run_tournament(config: &Config, file: &Path) {
+    let tournament = match TournamentConfig::load(file) {
+        Ok(tournament) => tournament,
+        Err(error) => {
+            eprintln!(
+                "error: can't set up the tournament in {}: {error}",
+                file.display()
+            );
+            process::exit(1);
+        }
+    };
+    let seed = tournament
+        .seed()
+        .or(config.seed())
+        .unwrap_or_else(random_seed);
+    println!(
+        "Tournament of {} over {}, seed {seed}.",
+        tournament.players().join(", "),
+        plural(tournament.rounds().len(), "round")
+    );
+
+    let stdout = io::stdout();
+    // only a terminal can be cleared, a pipe or a file would just fill up with escapes
+    let mut tournament = Tournament::new(tournament, seed).with_clear_screen(stdout.is_terminal());
+    let outcome = tournament
+        .play(io::stdin().lock(), stdout.lock())
+        .expect("Failed to read line");
+
+    match outcome {
+        TournamentOutcome::Champion(_) => {}
+        TournamentOutcome::Abandoned => {
+            println!("\nThe tournament was abandoned, standings so far:");
+            print!("{}", tournament.table());
+        }
+        TournamentOutcome::EndOfInput => process::exit(1),
+    }
+}
+
+fn run_host(config: &Config, port: u16) {
+    let seed = config.seed().unwrap_or_else(random_seed);
+    let game = Game::from_config(config, &mut seeded_rng(seed));
+
+    let server = match Server::bind(("0.0.0.0", port), game) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("error: can't listen on port {port}: {error}");
+            process::exit(1);
+        }
+    };
+    println!(
+        "Hosting a race for a number between {} and {} on port {port} (seed {seed}).",
+        config.range().start(),
+        config.range().end()
+    );
+    println!("Waiting for players...");
+
+    match server.run() {
+        Ok(RaceResult::Winner { name, attempts }) => {
+            println!("{name} won the race in {}!", plural(attempts, "attempt"))
+        }
+        Ok(RaceResult::NoWinner) => println!("Every player ran out of attempts."),
+        Err(error) => {
+            eprintln!("error: the race was interrupted: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+fn run_serve(config: &Config, port: u16) {
+    // only this machine can reach it, there is no authentication to speak of
+    let server = match web::Server::bind(("127.0.0.1", port), config.clone()) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("error: can't listen on port {port}: {error}");
+            process::exit(1);
+        }
+    };
+    let port = server.local_addr().map_or(port, |address| address.port());
+    println!("Open http://localhost:{port}/ in a browser to play, and press Ctrl+C to stop.");
+
+    server.run()
+}
+
+fn run_join(address: &str, name: Option<String>) {
+    let mut stream = match TcpStream::connect(address) {
+        Ok(stream) => stream,
+        Err(error) => {
+            eprintln!("error: can't connect to {address}: {error}");
+            process::exit(1);
+        }
+    };
+    let reader = BufReader::new(stream.try_clone().expect("Failed to clone the connection"));
+
+    // the host's events arrive whenever other players guess, so they get a
+    // thread of their own and end the whole process once the race is over
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else { break };
+            let Ok(event) = line.parse::<Event>() else {
+                println!("The host sent something unexpected: {line}");
+                continue;
+            };
+            println!("{}", describe(&event));
+            if matches!(event, Event::Winner { .. } | Event::NoWinner(_)) {
+                process::exit(0);
+            }
+        }
+        println!("The host closed the connection.");
+        process::exit(1);
+    });
+
+    let mut send = |request: Request| {
+        if writeln!(stream, "{request}").is_err() {
+            println!("Lost the connection to the host.");
+            process::exit(1);
+        }
+    };
+    if let Some(name) = name {
+        send(Request::Name(name));
+    }
+
+    for line in io::stdin().lock().lines() {
+        let line = line.expect("Failed to read line");
+        match session::parse_action(&line) {
+            Ok(Action::Guess(guess)) => send(Request::Guess(guess)),
+            Ok(Action::Quit | Action::GiveUp) => break,
+            Ok(Action::Help) => println!("Type a number to guess it, or `quit` to leave the race."),
+            Ok(Action::Hint | Action::History | Action::Save(_)) => {
+                println!("That command isn't available in a race.")
             }
-        };
+            Err(error) => println!("{error}"),
+        }
+    }
+
+    send(Request::Quit);
+    process::exit(1);
+}
+
+fn describe(event: &Event) -> String {
+    match event {
+        Event::Welcome {
+            min,
+            max,
+            max_attempts,
+        } => {
+            let attempts = match max_attempts {
+                Some(max) => format!(" You have {max} attempts."),
+                None => String::new(),
+            };
+            format!(
+                "Race for a number between {min} and {max}!{attempts} Please input your guesses!"
+            )
+        }
+        Event::Feedback(guess, feedback) => match feedback {
+            Feedback::TooSmall => format!("{guess} is too small!"),
+            Feedback::TooBig => format!("{guess} is too big!"),
+            Feedback::Win => format!("{guess} is right, you win!"),
+            Feedback::OutOfRange => format!("{guess} is out of range!"),
+            Feedback::AlreadyGuessed => format!("You already guessed {guess}!"),
+            Feedback::GameOver => "You have no attempts left.".to_string(),
+        },
+        Event::Error(message) => format!("The host didn't understand: {message}"),
+        Event::Joined(name) => format!("{name} joined the race."),
+        Event::Attempts(count, name) => format!("{name} has made {}.", plural(*count, "guess")),
+        Event::Left(name) => format!("{name} left the race."),
+        Event::Winner {
+            name,
+            attempts,
+            secret,
+        } => format!(
+            "{name} found {secret} in {} and wins the race!",
+            plural(*attempts, "attempt")
+        ),
+        Event::NoWinner(secret) => {
+            format!("Nobody found it, the secret number was {secret}.")
+        }
     }
 }
@@ -0,0 +1,748 @@
+//! A race over the network: one host holds the secret and any number of
+//! players connect and try to find it first.
+//!
+//! The protocol is one line of text per message. Players send
+//!
+//! ```text
+//! NAME <name>     pick a name, only before the first guess
+//! GUESS <n>       guess a number
+//! QUIT            leave the race
+//! ```
+//!
+//! and the host answers with [`Event`]s, some to the player alone and some
+//! broadcast to everyone:
+//!
+//! ```text
+//! WELCOME <min> <max> [<attempts>]    to a player who just connected
+//! FEEDBACK <n> <small|big|win|range|repeat|over>
+//! ERROR <message>                     the line made no sense, nothing changed
+//! JOINED <name>                       broadcast once a player has a name
+//! ATTEMPTS <count> <name>             broadcast after every accepted guess
+//! LEFT <name>
+//! WINNER <attempts> <secret> <name>
+//! NO_WINNER <secret>                  every player ran out of attempts
+//! ```
+//!
+//! Names come last on their line so they may contain spaces. Players who
+//! guess without picking one are called `player <n>`, so no one may pick a
+//! name of that form.
+
+use crate::game::{Feedback, Game};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+/// Lines longer than this are taken as a misbehaving client.
+const MAX_LINE: u64 = 256;
+const MAX_NAME: usize = 32;
+/// How long the host sleeps between checks for new players.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// How long a write to a player may block before the host hangs up on them.
+/// Writes happen with every player locked out, so one player who stops
+/// reading mustn't stall the race for long.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A line sent by a player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Name(String),
+    Guess(u32),
+    Quit,
+}
+
+impl FromStr for Request {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Request, String> {
+        let line = line.trim();
+        let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match word.to_ascii_uppercase().as_str() {
+            "NAME" if rest.is_empty() => Err("NAME needs a name".to_string()),
+            "NAME" if rest.chars().count() > MAX_NAME => {
+                Err(format!("names can be at most {MAX_NAME} characters long"))
+            }
+            "NAME" if rest.chars().any(char::is_control) => {
+                Err("names can't contain control characters".to_string())
+            }
+            "NAME" if is_default_name(rest) => Err(format!(
+                "names like {rest} are kept for players who don't pick one"
+            )),
+            "NAME" => Ok(Request::Name(rest.to_string())),
+            "GUESS" => rest
+                .parse()
+                .map(Request::Guess)
+                .map_err(|_| format!("`{rest}` is not a number")),
+            "QUIT" => Ok(Request::Quit),
+            "" => Err("empty line".to_string()),
+            _ => Err(format!("unknown request `{word}`")),
+        }
+    }
+}
+
+/// Whether `name` is of the form given to players who don't pick one.
+fn is_default_name(name: &str) -> bool {
+    name.strip_prefix("player ")
+        .is_some_and(|id| !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+fn default_name(id: usize) -> String {
+    format!("player {id}")
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Request::Name(name) => write!(f, "NAME {name}"),
+            Request::Guess(guess) => write!(f, "GUESS {guess}"),
+            Request::Quit => write!(f, "QUIT"),
+        }
+    }
+}
+
+/// A line sent by the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Welcome {
+        min: u32,
+        max: u32,
+        max_attempts: Option<u32>,
+    },
+    Feedback(u32, Feedback),
+    Error(String),
+    Joined(String),
+    Attempts(usize, String),
+    Left(String),
+    Winner {
+        name: String,
+        attempts: usize,
+        secret: u32,
+    },
+    NoWinner(u32),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Welcome {
+                min,
+                max,
+                max_attempts: Some(attempts),
+            } => write!(f, "WELCOME {min} {max} {attempts}"),
+            Event::Welcome { min, max, .. } => write!(f, "WELCOME {min} {max}"),
+            Event::Feedback(guess, feedback) => {
+                write!(f, "FEEDBACK {guess} {}", feedback_word(*feedback))
+            }
+            Event::Error(message) => write!(f, "ERROR {message}"),
+            Event::Joined(name) => write!(f, "JOINED {name}"),
+            Event::Attempts(count, name) => write!(f, "ATTEMPTS {count} {name}"),
+            Event::Left(name) => write!(f, "LEFT {name}"),
+            Event::Winner {
+                name,
+                attempts,
+                secret,
+            } => write!(f, "WINNER {attempts} {secret} {name}"),
+            Event::NoWinner(secret) => write!(f, "NO_WINNER {secret}"),
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Event, String> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let bad = || format!("malformed event `{line}`");
+        let number = |field: Option<&str>| field.and_then(|n| n.parse().ok()).ok_or_else(bad);
+
+        let event = match word {
+            "WELCOME" => {
+                let mut fields = rest.split(' ');
+                let min = number(fields.next())?;
+                let max = number(fields.next())?;
+                let max_attempts = fields.next().map(|n| number(Some(n))).transpose()?;
+                Event::Welcome {
+                    min,
+                    max,
+                    max_attempts,
+                }
+            }
+            "FEEDBACK" => {
+                let (guess, feedback) = rest.split_once(' ').ok_or_else(bad)?;
+                let feedback = FEEDBACK_WORDS
+                    .iter()
+                    .find(|(_, word)| *word == feedback)
+                    .ok_or_else(bad)?
+                    .0;
+                Event::Feedback(number(Some(guess))?, feedback)
+            }
+            "ERROR" => Event::Error(rest.to_string()),
+            "JOINED" => Event::Joined(rest.to_string()),
+            "ATTEMPTS" => {
+                let (count, name) = rest.split_once(' ').ok_or_else(bad)?;
+                Event::Attempts(number(Some(count))? as usize, name.to_string())
+            }
+            "LEFT" => Event::Left(rest.to_string()),
+            "WINNER" => {
+                let mut fields = rest.splitn(3, ' ');
+                let attempts = number(fields.next())? as usize;
+                let secret = number(fields.next())?;
+                let name = fields.next().ok_or_else(bad)?.to_string();
+                Event::Winner {
+                    name,
+                    attempts,
+                    secret,
+                }
+            }
+            "NO_WINNER" => Event::NoWinner(number(Some(rest))?),
+            _ => return Err(bad()),
+        };
+        Ok(event)
+    }
+}
+
+const FEEDBACK_WORDS: [(Feedback, &str); 6] = [
+    (Feedback::TooSmall, "small"),
+    (Feedback::TooBig, "big"),
+    (Feedback::Win, "win"),
+    (Feedback::OutOfRange, "range"),
+    (Feedback::AlreadyGuessed, "repeat"),
+    (Feedback::GameOver, "over"),
+];
+
+fn feedback_word(feedback: Feedback) -> &'static str {
+    FEEDBACK_WORDS
+        .iter()
+        .find(|(candidate, _)| *candidate == feedback)
+        .map(|(_, word)| *word)
+        .expect("every feedback has a word")
+}
+
+/// How a race ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaceResult {
+    Winner {
+        name: String,
+        attempts: usize,
+    },
+    /// Every player who joined ran out of attempts.
+    NoWinner,
+}
+
+struct Player {
+    /// `None` until the player sends NAME or makes their first guess.
+    name: Option<String>,
+    stream: TcpStream,
+    game: Game,
+}
+
+impl Player {
+    /// Hangs up on the player if the write fails or times out. Their thread
+    /// then sees the connection close and has them leave the race.
+    fn write(&mut self, event: &Event) {
+        if writeln!(self.stream, "{event}").is_err() {
+            let _ = self.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    players: HashMap<usize, Player>,
+    result: Option<RaceResult>,
+}
+
+impl State {
+    fn send(&mut self, id: usize, event: &Event) {
+        if let Some(player) = self.players.get_mut(&id) {
+            player.write(event);
+        }
+    }
+
+    fn broadcast(&mut self, event: &Event) {
+        for player in self.players.values_mut() {
+            player.write(event);
+        }
+    }
+
+    /// Fixes the player's name if they haven't got one yet and tells everyone.
+    fn join(&mut self, id: usize, name: Option<String>) -> Result<String, String> {
+        let player = &self.players[&id];
+        if let Some(existing) = &player.name {
+            return match name {
+                Some(_) => Err(format!("you already joined as {existing}")),
+                None => Ok(existing.clone()),
+            };
+        }
+
+        let name = name.unwrap_or_else(|| default_name(id));
+        let taken = self
+            .players
+            .values()
+            .any(|other| other.name.as_deref() == Some(name.as_str()));
+        if taken {
+            return Err(format!("the name {name} is taken"));
+        }
+
+        self.players
+            .get_mut(&id)
+            .expect("the player is connected")
+            .name = Some(name.clone());
+        self.broadcast(&Event::Joined(name.clone()));
+        Ok(name)
+    }
+
+    fn handle(&mut self, id: usize, request: Request) {
+        match request {
+            Request::Name(name) => {
+                if let Err(message) = self.join(id, Some(name)) {
+                    self.send(id, &Event::Error(message));
+                }
+            }
+            Request::Guess(guess) => {
+                let name = match self.join(id, None) {
+                    Ok(name) => name,
+                    Err(message) => return self.send(id, &Event::Error(message)),
+                };
+                if self.result.is_some() {
+                    return self.send(id, &Event::Feedback(guess, Feedback::GameOver));
+                }
+
+                let game = &mut self
+                    .players
+                    .get_mut(&id)
+                    .expect("the player is connected")
+                    .game;
+                let feedback = game.guess(guess);
+                let attempts = game.attempts();
+                let secret = game.secret();
+
+                self.send(id, &Event::Feedback(guess, feedback));
+                if matches!(
+                    feedback,
+                    Feedback::TooSmall | Feedback::TooBig | Feedback::Win
+                ) {
+                    self.broadcast(&Event::Attempts(attempts, name.clone()));
+                }
+                if feedback == Feedback::Win {
+                    self.broadcast(&Event::Winner {
+                        name: name.clone(),
+                        attempts,
+                        secret,
+                    });
+                    self.result = Some(RaceResult::Winner { name, attempts });
+                } else if self.everyone_lost() {
+                    self.broadcast(&Event::NoWinner(secret));
+                    self.result = Some(RaceResult::NoWinner);
+                }
+            }
+            Request::Quit => {}
+        }
+    }
+
+    fn everyone_lost(&self) -> bool {
+        let mut joined = self
+            .players
+            .values()
+            .filter(|player| player.name.is_some())
+            .peekable();
+        joined.peek().is_some() && joined.all(|player| player.game.is_lost())
+    }
+
+    fn leave(&mut self, id: usize) {
+        if let Some(Player {
+            name: Some(name), ..
+        }) = self.players.remove(&id)
+        {
+            self.broadcast(&Event::Left(name));
+        }
+    }
+}
+
+/// The host of a race, holding the secret every player is after.
+pub struct Server {
+    listener: TcpListener,
+    game: Game,
+    state: Arc<Mutex<State>>,
+}
+
+impl Server {
+    /// Listens on `address` for a race over copies of `game`, so every player
+    /// chases the same secret with attempts of their own.
+    pub fn bind<A: ToSocketAddrs>(address: A, game: Game) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(address)?,
+            game,
+            state: Arc::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Lets players connect and race until one of them wins or every one of
+    /// them has run out of attempts.
+    pub fn run(self) -> io::Result<RaceResult> {
+        self.listener.set_nonblocking(true)?;
+
+        let mut next_id = 1;
+        loop {
+            let result = lock(&self.state).result.clone();
+            if let Some(result) = result {
+                // hang up on everyone, which ends all of the player threads
+                for player in lock(&self.state).players.values() {
+                    let _ = player.stream.shutdown(Shutdown::Both);
+                }
+                return Ok(result);
+            }
+
+            match self.listener.accept() {
+                Ok((stream, address)) => {
+                    // a client that resets while being set up only loses its own seat
+                    if let Err(error) = self.welcome(next_id, stream) {
+                        eprintln!("warning: dropped the connection from {address}: {error}");
+                    }
+                    next_id += 1;
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL_INTERVAL)
+                }
+                // aborted connections and running out of file descriptors pass
+                Err(error) => {
+                    eprintln!("warning: can't accept a player: {error}");
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        }
+    }
+
+    fn welcome(&self, id: usize, stream: TcpStream) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let reader = stream.try_clone()?;
+
+        let range = self.game.range();
+        let mut state = lock(&self.state);
+        state.players.insert(
+            id,
+            Player {
+                name: None,
+                stream,
+                game: self.game.clone(),
+            },
+        );
+        state.send(
+            id,
+            &Event::Welcome {
+                min: *range.start(),
+                max: *range.end(),
+                max_attempts: self.game.max_attempts(),
+            },
+        );
+
+        let state = Arc::clone(&self.state);
+        thread::spawn(move || serve_player(id, reader, state));
+        Ok(())
+    }
+}
+
+/// Reads one player's requests until they quit or disconnect.
+fn serve_player(id: usize, stream: TcpStream, state: Arc<Mutex<State>>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        match (&mut reader).take(MAX_LINE).read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if !line.ends_with(b"\n") && line.len() as u64 == MAX_LINE => {
+                lock(&state).send(id, &Event::Error("line too long".to_string()));
+                break;
+            }
+            Ok(_) => {}
+        }
+
+        match String::from_utf8_lossy(&line).parse() {
+            Ok(Request::Quit) => break,
+            Ok(request) => lock(&state).handle(id, request),
+            Err(message) => lock(&state).send(id, &Event::Error(message)),
+        }
+    }
+
+    let mut state = lock(&state);
+    if let Some(player) = state.players.get(&id) {
+        let _ = player.stream.shutdown(Shutdown::Both);
+    }
+    state.leave(id);
+}
+
+/// A player thread panicking mid-update leaves nothing half-done that the
+/// others can't live with, so a poisoned lock is used anyway.
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(address: SocketAddr) -> Client {
+            let writer = TcpStream::connect(address).unwrap();
+            writer
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            Client {
+                reader: BufReader::new(writer.try_clone().unwrap()),
+                writer,
+            }
+        }
+
+        fn send(&mut self, line: &str) {
+            writeln!(self.writer, "{line}").unwrap();
+        }
+
+        fn next(&mut self) -> Event {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            line.parse().unwrap()
+        }
+
+        /// Skips broadcasts until an event matching `wanted` shows up.
+        fn until(&mut self, wanted: impl Fn(&Event) -> bool) -> Event {
+            loop {
+                let event = self.next();
+                if wanted(&event) {
+                    return event;
+                }
+            }
+        }
+    }
+
+    fn host(game: Game) -> (SocketAddr, thread::JoinHandle<io::Result<RaceResult>>) {
+        let server = Server::bind("127.0.0.1:0", game).unwrap();
+        let address = server.local_addr().unwrap();
+        (address, thread::spawn(move || server.run()))
+    }
+
+    #[test]
+    fn events_survive_the_wire() {
+        let events = [
+            Event::Welcome {
+                min: 1,
+                max: 100,
+                max_attempts: None,
+            },
+            Event::Welcome {
+                min: 1,
+                max: 1000,
+                max_attempts: Some(10),
+            },
+            Event::Feedback(5, Feedback::AlreadyGuessed),
+            Event::Error("unknown request `HI`".to_string()),
+            Event::Attempts(3, "Ada Lovelace".to_string()),
+            Event::Winner {
+                name: "Grace Hopper".to_string(),
+                attempts: 4,
+                secret: 42,
+            },
+            Event::NoWinner(7),
+        ];
+        for event in events {
+            assert_eq!(event.to_string().parse(), Ok(event));
+        }
+    }
+
+    #[test]
+    fn parses_requests() {
+        assert_eq!("guess 12\r\n".parse(), Ok(Request::Guess(12)));
+        assert_eq!(
+            "NAME Ada Lovelace\n".parse(),
+            Ok(Request::Name("Ada Lovelace".to_string()))
+        );
+        assert!("GUESS twelve".parse::<Request>().is_err());
+        assert!("NAME \u{7}".parse::<Request>().is_err());
+        assert!("NAME player 2".parse::<Request>().is_err());
+        assert_eq!(
+            "NAME player two".parse(),
+            Ok(Request::Name("player two".to_string()))
+        );
+        assert!("HELLO".parse::<Request>().is_err());
+    }
+
+    #[test]
+    fn first_to_find_the_secret_wins() {
+        let (address, server) = host(Game::new(42, 1..=100));
+
+        let mut bob = Client::connect(address);
+        bob.next();
+        bob.send("NAME bob");
+        for guess in [10, 20, 30] {
+            bob.send(&format!("GUESS {guess}"));
+            bob.until(|event| matches!(event, Event::Feedback(..)));
+        }
+
+        let mut ada = Client::connect(address);
+        ada.next();
+        ada.send("NAME ada");
+        for guess in [50, 25, 42] {
+            ada.send(&format!("GUESS {guess}"));
+        }
+
+        let winner = Event::Winner {
+            name: "ada".to_string(),
+            attempts: 3,
+            secret: 42,
+        };
+        assert_eq!(
+            bob.until(|event| matches!(event, Event::Winner { .. })),
+            winner
+        );
+        assert_eq!(
+            ada.until(|event| matches!(event, Event::Winner { .. })),
+            winner
+        );
+        assert_eq!(
+            server.join().unwrap().unwrap(),
+            RaceResult::Winner {
+                name: "ada".to_string(),
+                attempts: 3
+            }
+        );
+    }
+
+    #[test]
+    fn races_several_players_at_once() {
+        let (address, server) = host(Game::new(777, 1..=1000));
+
+        // every player binary searches as fast as it can until someone wins
+        let players: Vec<_> = (0..4)
+            .map(|n| {
+                thread::spawn(move || {
+                    let mut client = Client::connect(address);
+                    client.next();
+                    client.send(&format!("NAME racer {n}"));
+                    let (mut low, mut high) = (1, 1000);
+                    loop {
+                        let guess = (low + high) / 2;
+                        client.send(&format!("GUESS {guess}"));
+                        let mut line = String::new();
+                        loop {
+                            line.clear();
+                            // the host hangs up once the race is over
+                            if client.reader.read_line(&mut line).unwrap_or(0) == 0 {
+                                return None;
+                            }
+                            match line.parse().unwrap() {
+                                Event::Feedback(_, Feedback::TooSmall) => low = guess + 1,
+                                Event::Feedback(_, Feedback::TooBig) => high = guess - 1,
+                                Event::Feedback(..) => continue,
+                                winner @ Event::Winner { .. } => return Some(winner),
+                                _ => continue,
+                            }
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let seen: Vec<Option<Event>> = players
+            .into_iter()
+            .map(|player| player.join().unwrap())
+            .collect();
+        let RaceResult::Winner { name, attempts } = server.join().unwrap().unwrap() else {
+            panic!("somebody must have won");
+        };
+        assert!(name.starts_with("racer "));
+        for event in seen.into_iter().flatten() {
+            assert_eq!(
+                event,
+                Event::Winner {
+                    name: name.clone(),
+                    attempts,
+                    secret: 777
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn broadcasts_attempts_and_departures() {
+        let (address, server) = host(Game::new(7, 1..=10));
+        let mut watcher = Client::connect(address);
+        watcher.next();
+        watcher.send("NAME watcher");
+        assert_eq!(watcher.next(), Event::Joined("watcher".to_string()));
+
+        let mut quitter = Client::connect(address);
+        quitter.next();
+        quitter.send("GUESS 3");
+        assert_eq!(watcher.next(), Event::Joined("player 2".to_string()));
+        assert_eq!(watcher.next(), Event::Attempts(1, "player 2".to_string()));
+        drop(quitter);
+        assert_eq!(watcher.next(), Event::Left("player 2".to_string()));
+
+        watcher.send("GUESS 7");
+        assert_eq!(watcher.next(), Event::Feedback(7, Feedback::Win));
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn survives_malformed_lines() {
+        let (address, server) = host(Game::new(7, 1..=10));
+
+        let mut rude = Client::connect(address);
+        rude.next();
+        rude.writer.write_all(b"\xff\xfe garbage\n").unwrap();
+        assert!(matches!(rude.next(), Event::Error(_)));
+        rude.send("GUESS -1");
+        assert!(matches!(rude.next(), Event::Error(_)));
+        rude.writer.write_all(&[b'x'; 1000]).unwrap();
+        assert_eq!(rude.next(), Event::Error("line too long".to_string()));
+
+        let mut polite = Client::connect(address);
+        polite.next();
+        polite.send("NAME polite");
+        polite.send("NAME again");
+        polite.until(|event| matches!(event, Event::Error(_)));
+        polite.send("GUESS 11");
+        assert_eq!(
+            polite.until(|event| matches!(event, Event::Feedback(..))),
+            Event::Feedback(11, Feedback::OutOfRange)
+        );
+        polite.send("GUESS 7");
+        polite.until(|event| matches!(event, Event::Winner { .. }));
+        assert!(server.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn ends_when_everyone_runs_out_of_attempts() {
+        let (address, server) = host(Game::new(7, 1..=10).with_max_attempts(Some(1)));
+
+        let mut client = Client::connect(address);
+        client.next();
+        client.send("GUESS 1");
+        assert_eq!(
+            client.until(|event| matches!(event, Event::NoWinner(_))),
+            Event::NoWinner(7)
+        );
+        assert_eq!(server.join().unwrap().unwrap(), RaceResult::NoWinner);
+    }
+}
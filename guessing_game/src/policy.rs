@@ -0,0 +1,250 @@
+use crate::config::ConfigError;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// What a player gets told about a guess that missed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeedbackPolicy {
+    /// "Too small!" or "Too big!", straight from comparing with the secret.
+    #[default]
+    Classic,
+    /// Only whether the guess is closer to the secret than the one before.
+    WarmerColder,
+    /// Warmer or colder, plus how close the guess is as a [`Heat`] level.
+    Heat,
+}
+
+impl FeedbackPolicy {
+    pub const ALL: [FeedbackPolicy; 3] = [
+        FeedbackPolicy::Classic,
+        FeedbackPolicy::WarmerColder,
+        FeedbackPolicy::Heat,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FeedbackPolicy::Classic => "classic",
+            FeedbackPolicy::WarmerColder => "warmer",
+            FeedbackPolicy::Heat => "heat",
+        }
+    }
+
+    /// Whether the player learns which way a missed guess was off, which is
+    /// what narrowing the range down takes.
+    pub fn reveals_direction(self) -> bool {
+        self == FeedbackPolicy::Classic
+    }
+
+    /// The clue for `guess`, `previous` being the accepted guess before it.
+    pub fn clue(
+        self,
+        range: &RangeInclusive<u32>,
+        secret: u32,
+        guess: u32,
+        previous: Option<u32>,
+    ) -> Clue {
+        let distance = guess.abs_diff(secret);
+        let trend = previous.map(|previous| match distance.cmp(&previous.abs_diff(secret)) {
+            Ordering::Less => Trend::Warmer,
+            Ordering::Greater => Trend::Colder,
+            Ordering::Equal => Trend::Same,
+        });
+        let size = u64::from(range.end() - range.start()) + 1;
+
+        match self {
+            FeedbackPolicy::Classic => Clue {
+                direction: Some(guess.cmp(&secret)),
+                trend: None,
+                heat: None,
+            },
+            FeedbackPolicy::WarmerColder => Clue {
+                direction: None,
+                trend,
+                heat: None,
+            },
+            FeedbackPolicy::Heat => Clue {
+                direction: None,
+                trend,
+                heat: Some(Heat::from_distance(distance, size)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for FeedbackPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FeedbackPolicy {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<FeedbackPolicy, ConfigError> {
+        FeedbackPolicy::ALL
+            .into_iter()
+            .find(|policy| policy.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ConfigError::UnknownFeedback(s.to_string()))
+    }
+}
+
+/// Whether a guess got closer to the secret than the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Warmer,
+    Colder,
+    Same,
+}
+
+impl Trend {
+    pub fn name(self) -> &'static str {
+        match self {
+            Trend::Warmer => "warmer",
+            Trend::Colder => "colder",
+            Trend::Same => "same",
+        }
+    }
+}
+
+/// How close a guess is, graded by its distance as a fraction of the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Heat {
+    Freezing,
+    Cold,
+    Cool,
+    Warm,
+    Hot,
+    Burning,
+}
+
+impl Heat {
+    /// The largest fraction of the range, in percent, each level reaches up to.
+    const THRESHOLDS: [(u64, Heat); 5] = [
+        (2, Heat::Burning),
+        (5, Heat::Hot),
+        (15, Heat::Warm),
+        (30, Heat::Cool),
+        (60, Heat::Cold),
+    ];
+
+    /// Grades a guess `distance` away from the secret in a range of `size` numbers.
+    pub fn from_distance(distance: u32, size: u64) -> Heat {
+        // compare distance / size with percent / 100 without any rounding
+        Heat::THRESHOLDS
+            .into_iter()
+            .find(|&(percent, _)| u64::from(distance) * 100 <= percent * size)
+            .map_or(Heat::Freezing, |(_, heat)| heat)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heat::Freezing => "freezing",
+            Heat::Cold => "cold",
+            Heat::Cool => "cool",
+            Heat::Warm => "warm",
+            Heat::Hot => "hot",
+            Heat::Burning => "burning",
+        }
+    }
+}
+
+/// Everything a [`FeedbackPolicy`] lets the player know about a missed guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clue {
+    /// `guess.cmp(&secret)`, when the policy reveals it.
+    pub direction: Option<Ordering>,
+    /// `None` for the first guess, which has nothing to compare with.
+    pub trend: Option<Trend>,
+    pub heat: Option<Heat>,
+}
+
+/// The clue as a short sentence, like "Too small!" or "Warmer! You're hot.".
+impl fmt::Display for Clue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+
+        match self.direction {
+            Some(Ordering::Less) => parts.push("Too small!".to_string()),
+            Some(Ordering::Greater) => parts.push("Too big!".to_string()),
+            Some(Ordering::Equal) | None => {}
+        }
+        match (self.direction, self.trend) {
+            (_, Some(Trend::Warmer)) => parts.push("Warmer!".to_string()),
+            (_, Some(Trend::Colder)) => parts.push("Colder!".to_string()),
+            (_, Some(Trend::Same)) => parts.push("Neither warmer nor colder.".to_string()),
+            (None, None) => parts.push("Not it!".to_string()),
+            (Some(_), None) => {}
+        }
+        if let Some(heat) = self.heat {
+            parts.push(format!("You're {}.", heat.name()));
+        }
+
+        f.write_str(&parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_reveals_the_direction() {
+        let clue = FeedbackPolicy::Classic.clue(&(1..=100), 40, 10, Some(90));
+
+        assert_eq!(clue.direction, Some(Ordering::Less));
+        assert_eq!(clue.trend, None);
+        assert_eq!(clue.to_string(), "Too small!");
+    }
+
+    #[test]
+    fn warmer_colder_compares_distances() {
+        let policy = FeedbackPolicy::WarmerColder;
+
+        assert_eq!(policy.clue(&(1..=100), 40, 90, None).to_string(), "Not it!");
+        assert_eq!(
+            policy.clue(&(1..=100), 40, 60, Some(90)).trend,
+            Some(Trend::Warmer)
+        );
+        assert_eq!(
+            policy.clue(&(1..=100), 40, 99, Some(60)).trend,
+            Some(Trend::Colder)
+        );
+        assert_eq!(
+            policy.clue(&(1..=100), 40, 20, Some(60)).trend,
+            Some(Trend::Same)
+        );
+        assert_eq!(policy.clue(&(1..=100), 40, 20, Some(60)).direction, None);
+    }
+
+    #[test]
+    fn grades_heat_by_fraction_of_the_range() {
+        assert_eq!(Heat::from_distance(1, 100), Heat::Burning);
+        assert_eq!(Heat::from_distance(2, 100), Heat::Burning);
+        assert_eq!(Heat::from_distance(3, 100), Heat::Hot);
+        assert_eq!(Heat::from_distance(15, 100), Heat::Warm);
+        assert_eq!(Heat::from_distance(30, 100), Heat::Cool);
+        assert_eq!(Heat::from_distance(31, 100), Heat::Cold);
+        assert_eq!(Heat::from_distance(61, 100), Heat::Freezing);
+        assert_eq!(Heat::from_distance(u32::MAX, 1 << 32), Heat::Freezing);
+        assert_eq!(Heat::from_distance(20, 1000), Heat::Burning);
+
+        let clue = FeedbackPolicy::Heat.clue(&(1..=1000), 500, 510, Some(900));
+        assert_eq!(clue.to_string(), "Warmer! You're burning.");
+    }
+
+    #[test]
+    fn only_classic_reveals_the_direction() {
+        for policy in FeedbackPolicy::ALL {
+            let clue = policy.clue(&(1..=100), 40, 10, Some(90));
+            assert_eq!(policy.reveals_direction(), clue.direction.is_some());
+        }
+    }
+
+    #[test]
+    fn parses_policy_names() {
+        assert_eq!("Heat".parse(), Ok(FeedbackPolicy::Heat));
+        assert!("lukewarm".parse::<FeedbackPolicy>().is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use crate::session::plural;
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+
+/// The human's reply to one of the program's guesses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    /// The secret is higher than the guess.
+    Higher,
+    /// The secret is lower than the guess.
+    Lower,
+    Correct,
+}
+
+impl Answer {
+    /// The `guess.cmp(&secret)` this answer stands for, the same ordering
+    /// [`crate::Game`] compares with when the roles are the other way round.
+    pub fn ordering(self) -> Ordering {
+        match self {
+            Answer::Higher => Ordering::Less,
+            Answer::Lower => Ordering::Greater,
+            Answer::Correct => Ordering::Equal,
+        }
+    }
+
+    pub fn parse(input: &str) -> Option<Answer> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "h" | "higher" | "+" | ">" => Some(Answer::Higher),
+            "l" | "lower" | "-" | "<" => Some(Answer::Lower),
+            "c" | "correct" | "yes" | "=" => Some(Answer::Correct),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Answer::Higher => "higher",
+            Answer::Lower => "lower",
+            Answer::Correct => "correct",
+        })
+    }
+}
+
+/// Two answers that can't both be true.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contradiction {
+    /// The answer that left no number possible, counting from 1.
+    pub attempt: usize,
+    pub guess: u32,
+    pub answer: Answer,
+    /// The earlier answer it conflicts with, as `(attempt, guess, answer)`.
+    /// `None` when it conflicts with the range the secret was picked from.
+    pub earlier: Option<(usize, u32, Answer)>,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "answer #{} ({} than {}) contradicts ",
+            self.attempt, self.answer, self.guess
+        )?;
+        match self.earlier {
+            Some((attempt, guess, answer)) => {
+                write!(f, "answer #{attempt} ({answer} than {guess})")
+            }
+            None => write!(f, "the range the number was picked from"),
+        }
+    }
+}
+
+/// The program's side of the reverse game: it keeps the interval the human's
+/// secret can still be in and always guesses its middle.
+#[derive(Debug, Clone)]
+pub struct Guesser {
+    low: u32,
+    high: u32,
+    answers: Vec<(u32, Answer)>,
+    /// Which answers moved `low` and `high` last, to blame when they cross.
+    low_set_by: Option<usize>,
+    high_set_by: Option<usize>,
+}
+
+impl Guesser {
+    pub fn new(range: RangeInclusive<u32>) -> Guesser {
+        assert!(!range.is_empty(), "the range of a game can't be empty");
+
+        Guesser {
+            low: *range.start(),
+            high: *range.end(),
+            answers: Vec::new(),
+            low_set_by: None,
+            high_set_by: None,
+        }
+    }
+
+    /// The guess the next answer is about.
+    pub fn guess(&self) -> u32 {
+        self.low + (self.high - self.low) / 2
+    }
+
+    /// The numbers still consistent with every answer.
+    pub fn bounds(&self) -> RangeInclusive<u32> {
+        self.low..=self.high
+    }
+
+    pub fn answers(&self) -> &[(u32, Answer)] {
+        &self.answers
+    }
+
+    /// Narrows the interval with the answer to the current guess.
+    ///
+    /// Returns the secret once it's confirmed correct, or the pair of answers that can't
+    /// both hold. After a contradiction the guesser is left unchanged.
+    pub fn answer(&mut self, answer: Answer) -> Result<Option<u32>, Contradiction> {
+        let guess = self.guess();
+        let index = self.answers.len();
+
+        let (low, high) = match answer.ordering() {
+            // the guess is below the secret, or the secret is below the guess
+            Ordering::Less if guess < self.high => (guess + 1, self.high),
+            Ordering::Greater if guess > self.low => (self.low, guess - 1),
+            Ordering::Equal => (guess, guess),
+            Ordering::Less => return Err(self.contradiction(guess, answer, self.high_set_by)),
+            Ordering::Greater => return Err(self.contradiction(guess, answer, self.low_set_by)),
+        };
+
+        self.answers.push((guess, answer));
+        if low != self.low {
+            self.low_set_by = Some(index);
+        }
+        if high != self.high {
+            self.high_set_by = Some(index);
+        }
+        self.low = low;
+        self.high = high;
+
+        // even with one number left it's asked about, so a lie can still show up
+        Ok((answer == Answer::Correct).then_some(guess))
+    }
+
+    fn contradiction(&self, guess: u32, answer: Answer, earlier: Option<usize>) -> Contradiction {
+        Contradiction {
+            attempt: self.answers.len() + 1,
+            guess,
+            answer,
+            earlier: earlier.map(|index| {
+                let (guess, answer) = self.answers[index];
+                (index + 1, guess, answer)
+            }),
+        }
+    }
+}
+
+/// How a reverse game came to an end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The secret and how many guesses it took to find it.
+    Found(u32, usize),
+    Contradiction(Contradiction),
+    Quit,
+    EndOfInput,
+}
+
+/// Plays the reverse game, asking about every guess on `output` and reading
+/// the answers from `input`.
+pub fn play<R: BufRead, W: Write>(
+    range: RangeInclusive<u32>,
+    mut input: R,
+    mut output: W,
+) -> io::Result<Outcome> {
+    let mut guesser = Guesser::new(range);
+
+    loop {
+        let guess = guesser.guess();
+        writeln!(
+            output,
+            "Is it {guess}? Answer higher (h), lower (l) or correct (c)."
+        )?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output, "No more input, the game ends here.")?;
+            return Ok(Outcome::EndOfInput);
+        }
+        if line.trim().eq_ignore_ascii_case("quit") {
+            writeln!(output, "Bye!")?;
+            return Ok(Outcome::Quit);
+        }
+
+        let Some(answer) = Answer::parse(&line) else {
+            writeln!(output, "`{}` is not an answer I understand.", line.trim())?;
+            continue;
+        };
+
+        match guesser.answer(answer) {
+            Ok(Some(secret)) => {
+                let attempts = guesser.answers().len();
+                writeln!(
+                    output,
+                    "Your number is {secret}! Found it in {}.",
+                    plural(attempts, "guess")
+                )?;
+                return Ok(Outcome::Found(secret, attempts));
+            }
+            Ok(None) => {}
+            Err(contradiction) => {
+                writeln!(output, "That can't be right: {contradiction}.")?;
+                return Ok(Outcome::Contradiction(contradiction));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers every guess honestly for `secret`.
+    fn honest(guess: u32, secret: u32) -> Answer {
+        match guess.cmp(&secret) {
+            Ordering::Less => Answer::Higher,
+            Ordering::Greater => Answer::Lower,
+            Ordering::Equal => Answer::Correct,
+        }
+    }
+
+    #[test]
+    fn finds_every_secret_with_honest_answers() {
+        for secret in 1..=100 {
+            let mut guesser = Guesser::new(1..=100);
+            let found = loop {
+                if let Some(found) = guesser.answer(honest(guesser.guess(), secret)).unwrap() {
+                    break found;
+                }
+            };
+            assert_eq!(found, secret);
+            assert!(guesser.answers().len() <= 7);
+        }
+    }
+
+    #[test]
+    fn blames_the_conflicting_answer() {
+        let mut guesser = Guesser::new(1..=10);
+
+        assert_eq!(guesser.guess(), 5);
+        assert_eq!(guesser.answer(Answer::Higher), Ok(None));
+        assert_eq!(guesser.guess(), 8);
+        assert_eq!(guesser.answer(Answer::Lower), Ok(None));
+        assert_eq!(guesser.guess(), 6);
+        assert_eq!(guesser.answer(Answer::Higher), Ok(None));
+        assert_eq!(guesser.guess(), 7);
+
+        // 7 is the only number left, so "higher" conflicts with "lower than 8"
+        let contradiction = guesser.answer(Answer::Higher).unwrap_err();
+        assert_eq!(contradiction.attempt, 4);
+        assert_eq!(contradiction.earlier, Some((2, 8, Answer::Lower)));
+        assert_eq!(
+            contradiction.to_string(),
+            "answer #4 (higher than 7) contradicts answer #2 (lower than 8)"
+        );
+        assert_eq!(guesser.bounds(), 7..=7);
+    }
+
+    #[test]
+    fn blames_the_range_when_no_answer_set_the_bound() {
+        let mut guesser = Guesser::new(1..=2);
+
+        assert_eq!(guesser.guess(), 1);
+        let contradiction = guesser.answer(Answer::Lower).unwrap_err();
+        assert_eq!(contradiction.earlier, None);
+        assert!(contradiction
+            .to_string()
+            .ends_with("the range the number was picked from"));
+    }
+
+    #[test]
+    fn plays_over_text() {
+        let mut output = Vec::new();
+        let outcome = play(1..=10, "h\nwhat\nlower\nc\n".as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(outcome, Outcome::Found(6, 3));
+        assert!(output.contains("`what` is not an answer"));
+        assert!(output.ends_with("Your number is 6! Found it in 3 guesses.\n"));
+
+        let mut output = Vec::new();
+        let outcome = play(1..=10, "c\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(outcome, Outcome::Found(5, 1));
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .ends_with("Your number is 5! Found it in 1 guess.\n"));
+
+        let outcome = play(1..=10, "h\n".as_bytes(), Vec::new()).unwrap();
+        assert_eq!(outcome, Outcome::EndOfInput);
+    }
+}
@@ -0,0 +1,526 @@
+//! Games saved half-way with the `save` command and picked up again with
+//! `--resume`.
+//!
+//! A save file is a single JSON object:
+//!
+//! ```text
+//! {"version":1,"min":1,"max":100,"max_attempts":null,"feedback":"classic",
+//!  "seed":42,"history":[50,25],"secret":"9c2e01f7",
+//!  "salt":"<32 hex digits>","checksum":"<64 hex digits>"}
+//! ```
+//!
+//! The `salt` is that of the commitment printed when the game started, so
+//! the resumed game can print the same one. It's left out for games that
+//! never made a commitment.
+//!
+//! The secret and the salt are XORed with bytes hashed from the rest of the
+//! file, so they can't be read at a glance, and the checksum is a SHA-256
+//! over everything else, so a file that was edited or damaged is rejected.
+//! Neither stops a player who reads this source; they keep honest players
+//! honest.
+
+use crate::commit::Salt;
+use crate::config::Config;
+use crate::game::{Feedback, Game};
+use crate::json::Value;
+use crate::policy::FeedbackPolicy;
+use crate::sha256::{self, from_hex, to_hex};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// The version of the file format this build writes.
+pub const VERSION: u64 = 1;
+
+/// Where `save` writes when it isn't given a file.
+pub const DEFAULT_FILE: &str = "guessing_game.save";
+
+/// Mixed into the hashes so they differ from a plain SHA-256 of the fields.
+const KEY: &str = "guessing_game save";
+
+/// Everything needed to carry on with a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedGame {
+    pub range: RangeInclusive<u32>,
+    pub max_attempts: Option<u32>,
+    pub feedback: FeedbackPolicy,
+    pub seed: u64,
+    pub secret: u32,
+    /// The accepted guesses, oldest first.
+    pub history: Vec<u32>,
+    /// The salt of the commitment to the secret, if the game made one.
+    pub salt: Option<Salt>,
+}
+
+impl SavedGame {
+    /// Takes down the state of `game`, which has to know its seed.
+    pub fn new(game: &Game) -> Result<SavedGame, SaveError> {
+        if game.is_over() {
+            return Err(SaveError::Invalid("the game is already over".to_string()));
+        }
+
+        Ok(SavedGame {
+            range: game.range().clone(),
+            max_attempts: game.max_attempts(),
+            feedback: game.policy(),
+            seed: game.seed().ok_or(SaveError::NoSeed)?,
+            secret: game.secret(),
+            history: game.history().to_vec(),
+            salt: game.salt(),
+        })
+    }
+
+    /// Rebuilds the game by playing the saved guesses again, which also
+    /// checks they make sense.
+    pub fn restore(&self) -> Result<Game, SaveError> {
+        let invalid = |reason: String| SaveError::Invalid(reason);
+
+        let config = Config::new(*self.range.start(), *self.range.end(), self.max_attempts)
+            .map_err(|error| invalid(error.to_string()))?;
+        if !config.range().contains(&self.secret) {
+            return Err(invalid("the secret is outside the range".to_string()));
+        }
+
+        let mut game = Game::new(self.secret, config.range().clone())
+            .with_max_attempts(config.max_attempts())
+            .with_policy(self.feedback)
+            .with_seed(self.seed);
+        if let Some(salt) = self.salt {
+            game = game.with_salt(salt);
+        }
+        for &guess in &self.history {
+            match game.guess(guess) {
+                Feedback::TooSmall | Feedback::TooBig => {}
+                Feedback::Win | Feedback::GameOver => {
+                    return Err(invalid("the game is already over".to_string()))
+                }
+                Feedback::OutOfRange | Feedback::AlreadyGuessed => {
+                    return Err(invalid(format!(
+                        "the guess {guess} could never have been made"
+                    )))
+                }
+            }
+        }
+        if game.is_over() {
+            return Err(invalid("the game is already over".to_string()));
+        }
+        Ok(game)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut fields = self.fields();
+        let secret = obfuscate("secret", &self.secret.to_be_bytes(), &fields);
+        let salt = self
+            .salt
+            .map(|salt| obfuscate("salt", salt.bytes(), &fields));
+        fields.push(("secret", secret.into()));
+        if let Some(salt) = salt {
+            fields.push(("salt", salt.into()));
+        }
+        let checksum = checksum(&fields);
+        fields.push(("checksum", checksum.into()));
+        Value::object(fields)
+    }
+
+    pub fn parse(text: &str) -> Result<SavedGame, SaveError> {
+        let corrupt = |reason: &str| SaveError::Corrupt(reason.to_string());
+
+        let document = Value::parse(text).map_err(|error| SaveError::Corrupt(error.to_string()))?;
+        match document.get("version").and_then(Value::as_u64) {
+            Some(VERSION) => {}
+            Some(version) => return Err(SaveError::UnsupportedVersion(version)),
+            None => return Err(corrupt("no version number")),
+        }
+
+        let field = |name: &str| {
+            document
+                .get(name)
+                .ok_or_else(|| corrupt(&format!("no `{name}`")))
+        };
+        let number = |name: &str| {
+            field(name)?
+                .as_u32()
+                .ok_or_else(|| corrupt(&format!("`{name}` is not a number")))
+        };
+        let max_attempts = match field("max_attempts")? {
+            Value::Null => None,
+            limit => Some(
+                limit
+                    .as_u32()
+                    .ok_or_else(|| corrupt("`max_attempts` is not a number"))?,
+            ),
+        };
+        let feedback = field("feedback")?
+            .as_str()
+            .and_then(|name| name.parse().ok())
+            .ok_or_else(|| corrupt("unknown `feedback`"))?;
+        let seed = field("seed")?
+            .as_u64()
+            .ok_or_else(|| corrupt("`seed` is not a number"))?;
+        let history = field("history")?
+            .as_array()
+            .ok_or_else(|| corrupt("`history` is not a list"))?
+            .iter()
+            .map(|guess| {
+                guess
+                    .as_u32()
+                    .ok_or_else(|| corrupt("unreadable guess in `history`"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut saved = SavedGame {
+            range: number("min")?..=number("max")?,
+            max_attempts,
+            feedback,
+            seed,
+            secret: 0,
+            history,
+            salt: None,
+        };
+
+        // the checksum covers the secret and salt as they're written, so it's checked first
+        let plain = saved.fields();
+        let mut fields = plain.clone();
+        let secret = field("secret")?
+            .as_str()
+            .ok_or_else(|| corrupt("`secret` is not a string"))?;
+        fields.push(("secret", secret.into()));
+        let salt = document
+            .get("salt")
+            .map(|salt| {
+                salt.as_str()
+                    .ok_or_else(|| corrupt("`salt` is not a string"))
+            })
+            .transpose()?;
+        if let Some(salt) = salt {
+            fields.push(("salt", salt.into()));
+        }
+        let expected = field("checksum")?
+            .as_str()
+            .ok_or_else(|| corrupt("`checksum` is not a string"))?;
+        if !checksum(&fields).eq_ignore_ascii_case(expected) {
+            return Err(SaveError::Tampered);
+        }
+
+        saved.secret = reveal("secret", secret, &plain)
+            .map(u32::from_be_bytes)
+            .ok_or_else(|| corrupt("`secret` is not 8 hex digits"))?;
+        saved.salt = salt
+            .map(|salt| {
+                reveal("salt", salt, &plain)
+                    .map(Salt::from_bytes)
+                    .ok_or_else(|| corrupt("`salt` is not 32 hex digits"))
+            })
+            .transpose()?;
+        Ok(saved)
+    }
+
+    /// Writes the game to `path`, replacing whatever was there.
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        // written next to the file and renamed over it, so a crash never leaves half a save
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, format!("{}\n", self.to_json()))?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<SavedGame, SaveError> {
+        SavedGame::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Every field but the secret, the salt and the checksum, in the order
+    /// they're written.
+    fn fields(&self) -> Vec<(&'static str, Value)> {
+        vec![
+            ("version", VERSION.into()),
+            ("min", (*self.range.start()).into()),
+            ("max", (*self.range.end()).into()),
+            ("max_attempts", self.max_attempts.into()),
+            ("feedback", self.feedback.name().into()),
+            ("seed", self.seed.into()),
+            ("history", self.history.clone().into()),
+        ]
+    }
+}
+
+/// A hash of `fields` and `purpose`, which tells the two uses apart.
+fn hash(purpose: &str, fields: &[(&str, Value)]) -> [u8; 32] {
+    let document = Value::object(fields.iter().map(|(name, value)| (*name, value.clone())));
+    sha256::digest(format!("{KEY} {purpose}:{document}").as_bytes())
+}
+
+fn checksum(fields: &[(&str, Value)]) -> String {
+    to_hex(&hash("checksum", fields))
+}
+
+/// Hides `plain`, at most 32 bytes, under a mask hashed from `fields`.
+fn obfuscate(purpose: &str, plain: &[u8], fields: &[(&str, Value)]) -> String {
+    let mask = hash(purpose, fields);
+    let bytes: Vec<u8> = plain
+        .iter()
+        .zip(mask)
+        .map(|(byte, mask)| byte ^ mask)
+        .collect();
+    to_hex(&bytes)
+}
+
+fn reveal<const N: usize>(
+    purpose: &str,
+    hidden: &str,
+    fields: &[(&str, Value)],
+) -> Option<[u8; N]> {
+    let mask = hash(purpose, fields);
+    let bytes: [u8; N] = from_hex(hidden)?.try_into().ok()?;
+    let mut plain = [0; N];
+    for (plain, (byte, mask)) in plain.iter_mut().zip(bytes.iter().zip(mask)) {
+        *plain = byte ^ mask;
+    }
+    Some(plain)
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Corrupt(String),
+    /// The file was written by a newer version of the game.
+    UnsupportedVersion(u64),
+    /// The checksum doesn't match, so the file was changed after it was saved.
+    Tampered,
+    /// The file is well-formed but the game in it isn't one that can be played on.
+    Invalid(String),
+    /// The game doesn't know the seed it was drawn with.
+    NoSeed,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "{error}"),
+            SaveError::Corrupt(reason) => write!(f, "the file is corrupt ({reason})"),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "the file has format version {version}, this game only knows version {VERSION}"
+            ),
+            SaveError::Tampered => write!(f, "the file was changed after the game was saved"),
+            SaveError::Invalid(reason) => write!(f, "the saved game can't be resumed, {reason}"),
+            SaveError::NoSeed => write!(f, "the game has no seed to save"),
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> SaveError {
+        SaveError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{seeded_rng, Difficulty};
+    use crate::session::{self, Console, Outcome};
+    use std::env;
+    use std::path::PathBuf;
+
+    fn game(seed: u64) -> Game {
+        let config = Difficulty::Normal
+            .config()
+            .with_feedback(FeedbackPolicy::Heat);
+        Game::from_config(&config, &mut seeded_rng(seed)).with_seed(seed)
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("guessing_game-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(DEFAULT_FILE)
+    }
+
+    #[test]
+    fn resumed_games_play_on_the_same() {
+        let guesses = [50, 25, 75, 60, 12, 90, 33, 41, 82, 67];
+
+        for seed in 0..20 {
+            let mut whole = game(seed);
+            let expected: Vec<_> = guesses.iter().map(|&guess| whole.guess(guess)).collect();
+
+            for split in 0..guesses.len() {
+                let mut first = game(seed);
+                let mut feedback: Vec<_> =
+                    guesses[..split].iter().map(|&g| first.guess(g)).collect();
+                let Ok(saved) = SavedGame::new(&first) else {
+                    // the game was over before the split, nothing to resume
+                    assert!(first.is_over());
+                    continue;
+                };
+
+                let text = saved.to_json().to_string();
+                let mut resumed = SavedGame::parse(&text).unwrap().restore().unwrap();
+                feedback.extend(guesses[split..].iter().map(|&guess| resumed.guess(guess)));
+
+                assert_eq!(feedback, expected, "seed {seed}, split {split}");
+                assert_eq!(resumed.history(), whole.history());
+                assert_eq!(
+                    resumed.clue(resumed.attempts() - 1),
+                    whole.clue(whole.attempts() - 1)
+                );
+                assert_eq!(resumed.seed(), Some(seed));
+            }
+        }
+    }
+
+    #[test]
+    fn hides_the_secret() {
+        let mut game = game(3);
+        game.guess(50);
+        let text = SavedGame::new(&game).unwrap().to_json().to_string();
+
+        let secret = Value::parse(&text).unwrap().get("secret").unwrap().clone();
+        assert_ne!(secret.as_str().unwrap(), format!("{:08x}", game.secret()));
+        assert!(!text.contains(&format!("\"secret\":{}", game.secret())));
+    }
+
+    #[test]
+    fn rejects_tampered_files() {
+        let mut game = Game::new(64, 1..=100).with_seed(9);
+        game.guess(50);
+        game.guess(70);
+        let text = SavedGame::new(&game).unwrap().to_json().to_string();
+        assert!(SavedGame::parse(&text).is_ok());
+
+        for (from, to) in [
+            ("[50,70]", "[50]"),
+            ("\"max\":100", "\"max\":60"),
+            ("\"seed\":9", "\"seed\":8"),
+        ] {
+            let edited = text.replace(from, to);
+            assert_ne!(edited, text);
+            assert!(
+                matches!(SavedGame::parse(&edited), Err(SaveError::Tampered)),
+                "{from}"
+            );
+        }
+
+        // a different secret with a checksum that doesn't know about it
+        let value = Value::parse(&text).unwrap();
+        let secret = value.get("secret").unwrap().as_str().unwrap();
+        let flipped = format!(
+            "{}{}",
+            &secret[..7],
+            if secret.ends_with('0') { '1' } else { '0' }
+        );
+        let edited = text.replace(secret, &flipped);
+        assert!(matches!(
+            SavedGame::parse(&edited),
+            Err(SaveError::Tampered)
+        ));
+    }
+
+    #[test]
+    fn keeps_the_salt_of_the_commitment() {
+        let salt = Salt::from_bytes([7; 16]);
+        let mut game = Game::new(64, 1..=100).with_seed(9).with_salt(salt);
+        game.guess(50);
+        let text = SavedGame::new(&game).unwrap().to_json().to_string();
+        assert!(!text.contains(&salt.to_string()));
+
+        let resumed = SavedGame::parse(&text).unwrap().restore().unwrap();
+        assert_eq!(resumed.salt(), Some(salt));
+
+        let value = Value::parse(&text).unwrap();
+        let hidden = value.get("salt").unwrap().as_str().unwrap();
+        let flipped = format!(
+            "{}{}",
+            &hidden[..31],
+            if hidden.ends_with('0') { '1' } else { '0' }
+        );
+        assert!(matches!(
+            SavedGame::parse(&text.replace(hidden, &flipped)),
+            Err(SaveError::Tampered)
+        ));
+
+        // games that never committed to their secret save without a salt
+        let text = SavedGame::new(&Game::new(64, 1..=100).with_seed(9))
+            .unwrap()
+            .to_json()
+            .to_string();
+        assert!(!text.contains("\"salt\""));
+        assert_eq!(SavedGame::parse(&text).unwrap().salt, None);
+    }
+
+    #[test]
+    fn rejects_incompatible_files() {
+        let game = Game::new(64, 1..=100).with_seed(9);
+        let text = SavedGame::new(&game).unwrap().to_json().to_string();
+
+        assert!(matches!(
+            SavedGame::parse(&text.replace("\"version\":1", "\"version\":2")),
+            Err(SaveError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            SavedGame::parse("{\"scores\":[]}"),
+            Err(SaveError::Corrupt(_))
+        ));
+        assert!(matches!(
+            SavedGame::parse("not json"),
+            Err(SaveError::Corrupt(_))
+        ));
+        assert!(matches!(
+            SavedGame::new(&Game::new(64, 1..=100)),
+            Err(SaveError::NoSeed)
+        ));
+
+        let impossible = SavedGame {
+            history: vec![50, 50],
+            ..SavedGame::new(&game).unwrap()
+        };
+        let text = impossible.to_json().to_string();
+        assert!(matches!(
+            SavedGame::parse(&text).unwrap().restore(),
+            Err(SaveError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn saves_from_the_prompt() {
+        let path = scratch("save-prompt");
+        let mut game = Game::new(64, 1..=100).with_seed(1);
+        let mut output = Vec::new();
+        let input = format!("50\nsave {}\nquit\n", path.display());
+        let outcome = session::play(&mut game, input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(outcome, Outcome::Quit);
+        assert!(output.contains(&format!("Game saved to {}.", path.display())));
+
+        let mut resumed = SavedGame::load(&path).unwrap().restore().unwrap();
+        let mut output = Vec::new();
+        let outcome = session::run(
+            &mut resumed,
+            &mut Console {
+                input: "history\n64\n".as_bytes(),
+                output: &mut output,
+            },
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(outcome, Outcome::Won);
+        assert!(output.contains("  1. 50 was too small\n"));
+        assert_eq!(resumed.attempts(), 2);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
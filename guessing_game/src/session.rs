@@ -0,0 +1,453 @@
+use crate::game::{Feedback, Game};
+use crate::save::{self, SaveError, SavedGame};
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::num::IntErrorKind;
+use std::path::Path;
+
+pub const HELP: &str = "\
+Type a number to guess it, or one of these commands:
+  hint     show the range the secret number is still in
+  history  list your guesses so far
+  save     write the game to a file to resume it later, `save FILE` to pick the file
+  giveup   reveal the secret number and end the game
+  quit     leave without revealing the secret number
+  help     show this message";
+
+/// How an interactive game came to an end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    GaveUp,
+    Quit,
+    /// The input was closed before the game was decided.
+    EndOfInput,
+}
+
+/// One line typed at the prompt, `G` being what a guess is made of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action<G = u32> {
+    Guess(G),
+    Hint,
+    History,
+    /// Save the game, to the given file or to [`save::DEFAULT_FILE`].
+    Save(Option<String>),
+    GiveUp,
+    Quit,
+    Help,
+}
+
+/// Why a line typed at the prompt is neither a guess nor a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    Empty,
+    Negative(String),
+    TooLarge(String),
+    NotANumber(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Empty => write!(f, "Please type a number, or `help` to see the commands."),
+            InputError::Negative(input) => {
+                write!(f, "`{input}` is negative, the secret number never is.")
+            }
+            InputError::TooLarge(input) => {
+                write!(f, "`{input}` is far too large to be the secret number.")
+            }
+            InputError::NotANumber(input) => {
+                write!(
+                    f,
+                    "`{input}` is neither a number nor a command, type `help` to see the commands."
+                )
+            }
+        }
+    }
+}
+
+/// The command typed at the prompt, if the input is one rather than a guess.
+pub fn parse_command<G>(input: &str) -> Option<Action<G>> {
+    let input = input.trim();
+    match input.to_ascii_lowercase().as_str() {
+        "hint" => return Some(Action::Hint),
+        "history" => return Some(Action::History),
+        "giveup" | "give up" => return Some(Action::GiveUp),
+        "quit" | "exit" => return Some(Action::Quit),
+        "help" | "?" => return Some(Action::Help),
+        _ => {}
+    }
+
+    // the file keeps its case, only the command itself is matched loosely
+    let (command, file) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    command.eq_ignore_ascii_case("save").then(|| {
+        let file = file.trim();
+        Action::Save((!file.is_empty()).then(|| file.to_string()))
+    })
+}
+
+/// Reads one line of player input, surrounding whitespace included.
+pub fn parse_action(line: &str) -> Result<Action, InputError> {
+    let input = line.trim();
+    if let Some(command) = parse_command(input) {
+        return Ok(command);
+    }
+
+    match input.parse::<u32>() {
+        Ok(guess) => Ok(Action::Guess(guess)),
+        Err(error) => Err(match error.kind() {
+            IntErrorKind::Empty => InputError::Empty,
+            IntErrorKind::PosOverflow => InputError::TooLarge(input.to_string()),
+            _ if input.starts_with('-') && input[1..].parse::<u64>().is_ok() => {
+                InputError::Negative(input.to_string())
+            }
+            _ => InputError::NotANumber(input.to_string()),
+        }),
+    }
+}
+
+/// One thing read from the player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input<G = u32> {
+    Action(Action<G>),
+    /// A line that isn't an action, with the message explaining why.
+    Invalid(String),
+    /// The input was closed.
+    End,
+}
+
+/// A game [`run`] can play: something guessed at until it's won or lost.
+pub trait Puzzle {
+    type Guess;
+    /// What the puzzle answers to a guess.
+    type Reply;
+
+    fn guess(&mut self, guess: Self::Guess) -> Self::Reply;
+    fn is_won(&self) -> bool;
+    fn is_over(&self) -> bool;
+}
+
+impl Puzzle for Game {
+    type Guess = u32;
+    type Reply = Feedback;
+
+    fn guess(&mut self, guess: u32) -> Feedback {
+        Game::guess(self, guess)
+    }
+
+    fn is_won(&self) -> bool {
+        Game::is_won(self)
+    }
+
+    fn is_over(&self) -> bool {
+        Game::is_over(self)
+    }
+}
+
+/// Where a game reads its input from and reports to.
+///
+/// [`run`] decides what happens in the game and calls these to tell the
+/// player about it, so the console and the machine interface only differ in
+/// how they parse and word things.
+pub trait Frontend<P: Puzzle = Game> {
+    fn read(&mut self) -> io::Result<Input<P::Guess>>;
+    fn prompt(&mut self, game: &P) -> io::Result<()>;
+    fn invalid(&mut self, message: &str) -> io::Result<()>;
+    fn feedback(&mut self, game: &P, guess: P::Guess, reply: P::Reply) -> io::Result<()>;
+    fn hint(&mut self, game: &P) -> io::Result<()>;
+    fn history(&mut self, game: &P) -> io::Result<()>;
+    fn help(&mut self) -> io::Result<()>;
+    /// Saves `game` to `path` for `--resume`. Front-ends of games that can't
+    /// be saved just say so.
+    fn save(&mut self, game: &P, path: &Path) -> io::Result<()> {
+        let _ = (game, path);
+        self.invalid("This game can't be saved.")
+    }
+    /// Called exactly once, however the game ended.
+    fn finish(&mut self, game: &P, outcome: Outcome) -> io::Result<()>;
+}
+
+/// Plays `game` to the end through `frontend`.
+pub fn run<P>(game: &mut P, frontend: &mut dyn Frontend<P>) -> io::Result<Outcome>
+where
+    P: Puzzle,
+    P::Guess: Clone,
+{
+    let outcome = loop {
+        if game.is_over() {
+            break if game.is_won() {
+                Outcome::Won
+            } else {
+                Outcome::Lost
+            };
+        }
+
+        frontend.prompt(game)?;
+
+        match frontend.read()? {
+            Input::Action(Action::Guess(guess)) => {
+                let reply = game.guess(guess.clone());
+                frontend.feedback(game, guess, reply)?;
+            }
+            Input::Action(Action::Hint) => frontend.hint(game)?,
+            Input::Action(Action::History) => frontend.history(game)?,
+            Input::Action(Action::Help) => frontend.help()?,
+            Input::Action(Action::Save(file)) => {
+                let file = file.as_deref().unwrap_or(save::DEFAULT_FILE);
+                frontend.save(game, Path::new(file))?
+            }
+            Input::Action(Action::GiveUp) => break Outcome::GaveUp,
+            Input::Action(Action::Quit) => break Outcome::Quit,
+            Input::Invalid(message) => frontend.invalid(&message)?,
+            Input::End => break Outcome::EndOfInput,
+        }
+    };
+
+    frontend.finish(game, outcome)?;
+    Ok(outcome)
+}
+
+/// Plays `game` as text, reading lines from `input` and writing every prompt
+/// and reply to `output`.
+pub fn play<R: BufRead, W: Write>(game: &mut Game, input: R, output: W) -> io::Result<Outcome> {
+    run(game, &mut Console { input, output })
+}
+
+/// `count` followed by `word`, with an English plural ending when it needs one.
+pub fn plural(count: usize, word: &str) -> String {
+    match (count, word.ends_with('s')) {
+        (1, _) => format!("{count} {word}"),
+        (_, true) => format!("{count} {word}es"),
+        (_, false) => format!("{count} {word}s"),
+    }
+}
+
+/// Why a game whose policy hides the direction of a miss gives no hints.
+pub(crate) fn no_hint(game: &Game) -> String {
+    format!(
+        "There are no hints with {} feedback, they would tell which way your guesses were off.",
+        game.policy()
+    )
+}
+
+/// What the console says once it tried to save a game to `path`.
+pub(crate) fn save_message(saved: Result<(), SaveError>, path: &Path) -> String {
+    match saved {
+        Ok(()) => format!(
+            "Game saved to {0}. Resume it with `guessing_game --resume {0}`.",
+            path.display()
+        ),
+        Err(error) => format!("The game could not be saved to {}: {error}", path.display()),
+    }
+}
+
+/// The human front-end: one command per line in, plain sentences out.
+pub struct Console<R, W> {
+    pub input: R,
+    pub output: W,
+}
+
+impl<R: BufRead, W: Write> Frontend for Console<R, W> {
+    fn read(&mut self) -> io::Result<Input> {
+        let mut line = String::new();
+        // read_line returns 0 only once the input is closed, an empty line still has its newline
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(Input::End);
+        }
+
+        Ok(match parse_action(&line) {
+            Ok(action) => Input::Action(action),
+            Err(error) => Input::Invalid(error.to_string()),
+        })
+    }
+
+    fn prompt(&mut self, _: &Game) -> io::Result<()> {
+        writeln!(self.output, "Please input your guess!")
+    }
+
+    fn invalid(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.output, "{message}")
+    }
+
+    fn feedback(&mut self, game: &Game, guess: u32, feedback: Feedback) -> io::Result<()> {
+        writeln!(self.output, "You guessed: {guess}")?;
+
+        match feedback {
+            // how much a miss gives away is up to the game's feedback policy
+            Feedback::TooSmall | Feedback::TooBig => {
+                writeln!(self.output, "{}", game.clue(game.attempts() - 1))
+            }
+            Feedback::OutOfRange => writeln!(
+                self.output,
+                "The secret number is between {} and {}!",
+                game.range().start(),
+                game.range().end()
+            ),
+            Feedback::AlreadyGuessed => writeln!(self.output, "You already guessed {guess}!"),
+            Feedback::Win => writeln!(self.output, "You win!"),
+            Feedback::GameOver => Ok(()),
+        }
+    }
+
+    fn hint(&mut self, game: &Game) -> io::Result<()> {
+        match game.hint() {
+            Some(bounds) => writeln!(
+                self.output,
+                "The secret number is between {} and {}.",
+                bounds.start(),
+                bounds.end()
+            ),
+            None => writeln!(self.output, "{}", no_hint(game)),
+        }
+    }
+
+    fn history(&mut self, game: &Game) -> io::Result<()> {
+        if game.history().is_empty() {
+            return writeln!(self.output, "No guesses yet.");
+        }
+
+        for (index, guess) in game.history().iter().enumerate() {
+            let clue = game.clue(index);
+            let verdict = match clue.direction {
+                _ if *guess == game.secret() => "was right".to_string(),
+                Some(Ordering::Less) => "was too small".to_string(),
+                Some(_) => "was too big".to_string(),
+                None => format!("- {clue}"),
+            };
+            writeln!(self.output, "{:>3}. {guess} {verdict}", index + 1)?;
+        }
+        Ok(())
+    }
+
+    fn help(&mut self) -> io::Result<()> {
+        writeln!(self.output, "{HELP}")
+    }
+
+    fn save(&mut self, game: &Game, path: &Path) -> io::Result<()> {
+        let saved = SavedGame::new(game).and_then(|saved| saved.write(path));
+        writeln!(self.output, "{}", save_message(saved, path))
+    }
+
+    fn finish(&mut self, game: &Game, outcome: Outcome) -> io::Result<()> {
+        match outcome {
+            Outcome::GaveUp => writeln!(self.output, "The secret number was {}.", game.secret()),
+            Outcome::Quit => writeln!(self.output, "Bye!"),
+            Outcome::EndOfInput => writeln!(self.output, "No more input, the game ends here."),
+            Outcome::Won | Outcome::Lost => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::FeedbackPolicy;
+
+    fn run(game: &mut Game, input: &str) -> (Outcome, String) {
+        let mut output = Vec::new();
+        let outcome = play(game, input.as_bytes(), &mut output).unwrap();
+        (outcome, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parses_guesses_and_commands() {
+        assert_eq!(parse_action(" 42\n"), Ok(Action::Guess(42)));
+        assert_eq!(parse_action("Hint\n"), Ok(Action::Hint));
+        assert_eq!(parse_action("giveup"), Ok(Action::GiveUp));
+        assert_eq!(parse_action("save\n"), Ok(Action::Save(None)));
+        assert_eq!(
+            parse_action(" SAVE  My Game.save \n"),
+            Ok(Action::Save(Some("My Game.save".to_string())))
+        );
+        assert_eq!(
+            parse_action("saved"),
+            Err(InputError::NotANumber("saved".to_string()))
+        );
+        assert_eq!(parse_action("\n"), Err(InputError::Empty));
+        assert_eq!(
+            parse_action("-4"),
+            Err(InputError::Negative("-4".to_string()))
+        );
+        assert_eq!(
+            parse_action("99999999999"),
+            Err(InputError::TooLarge("99999999999".to_string()))
+        );
+        assert_eq!(
+            parse_action("four"),
+            Err(InputError::NotANumber("four".to_string()))
+        );
+    }
+
+    #[test]
+    fn ends_cleanly_at_end_of_input() {
+        let mut game = Game::new(7, 1..=10);
+        let (outcome, output) = run(&mut game, "3\nabc\n");
+
+        assert_eq!(outcome, Outcome::EndOfInput);
+        assert!(output.contains("Too small!"));
+        assert!(output.contains("`abc` is neither a number nor a command"));
+        assert!(output.ends_with("No more input, the game ends here.\n"));
+    }
+
+    #[test]
+    fn plays_to_a_win() {
+        let mut game = Game::new(7, 1..=10);
+        let (outcome, output) = run(&mut game, "3\n\n3\n7\nnever read\n");
+
+        assert_eq!(outcome, Outcome::Won);
+        assert!(output.contains("Please type a number"));
+        assert!(output.contains("You already guessed 3!"));
+        assert!(output.ends_with("You win!\n"));
+    }
+
+    #[test]
+    fn plays_to_a_loss() {
+        let mut game = Game::new(7, 1..=10).with_max_attempts(Some(1));
+        let (outcome, _) = run(&mut game, "2\n7\n");
+
+        assert_eq!(outcome, Outcome::Lost);
+    }
+
+    #[test]
+    fn follows_the_feedback_policy() {
+        let mut game = Game::new(20, 1..=100).with_policy(FeedbackPolicy::Heat);
+        let (_, output) = run(&mut game, "95\n24\nhistory\n");
+
+        assert!(output.contains("You guessed: 95\nNot it! You're freezing.\n"));
+        assert!(output.contains("You guessed: 24\nWarmer! You're hot.\n"));
+        assert!(output.contains("  2. 24 - Warmer! You're hot.\n"));
+        assert!(!output.contains("Too"));
+    }
+
+    #[test]
+    fn hints_give_nothing_away_when_the_policy_hides_the_direction() {
+        for policy in [FeedbackPolicy::WarmerColder, FeedbackPolicy::Heat] {
+            let mut game = Game::new(80, 1..=100).with_policy(policy);
+            let (_, output) = run(&mut game, "50\nhint\n");
+
+            assert!(
+                output.contains(&format!("There are no hints with {policy} feedback")),
+                "{output}"
+            );
+            assert!(!output.contains("between 51"), "{policy}: {output}");
+        }
+    }
+
+    #[test]
+    fn answers_commands() {
+        let mut game = Game::new(7, 1..=10);
+        let (outcome, output) = run(&mut game, "history\n3\n9\nhint\nhistory\ngiveup\n");
+
+        assert_eq!(outcome, Outcome::GaveUp);
+        assert!(output.contains("No guesses yet."));
+        assert!(output.contains("between 4 and 8."));
+        assert!(output.contains("  1. 3 was too small\n  2. 9 was too big\n"));
+        assert!(output.ends_with("The secret number was 7.\n"));
+
+        let (outcome, output) = run(&mut Game::new(7, 1..=10), "quit\n");
+        assert_eq!(outcome, Outcome::Quit);
+        assert!(!output.contains('7'));
+    }
+}
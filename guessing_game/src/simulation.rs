@@ -0,0 +1,223 @@
+use crate::config::{seeded_rng, Config};
+use crate::game::Game;
+use crate::strategy::{self, Strategy};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How many guesses a strategy needed over a batch of games.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    name: &'static str,
+    guesses: Vec<u32>,
+    max_attempts: Option<u32>,
+}
+
+impl Stats {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn games(&self) -> usize {
+        self.guesses.len()
+    }
+
+    pub fn mean(&self) -> f64 {
+        let total: u64 = self.guesses.iter().map(|&n| u64::from(n)).sum();
+        total as f64 / self.guesses.len() as f64
+    }
+
+    pub fn median(&self) -> f64 {
+        let middle = self.guesses.len() / 2;
+        if self.guesses.len().is_multiple_of(2) {
+            f64::from(self.guesses[middle - 1] + self.guesses[middle]) / 2.0
+        } else {
+            f64::from(self.guesses[middle])
+        }
+    }
+
+    pub fn worst(&self) -> u32 {
+        self.guesses.last().copied().unwrap_or(0)
+    }
+
+    /// The number of games that would have been lost to the attempt limit.
+    pub fn over_limit(&self) -> usize {
+        match self.max_attempts {
+            Some(max) => self.guesses.iter().filter(|&&n| n > max).count(),
+            None => 0,
+        }
+    }
+
+    /// How many games took each number of guesses.
+    pub fn histogram(&self) -> BTreeMap<u32, usize> {
+        let mut histogram = BTreeMap::new();
+        for &n in &self.guesses {
+            *histogram.entry(n).or_insert(0) += 1;
+        }
+        histogram
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const BAR_WIDTH: usize = 40;
+        const MAX_ROWS: u32 = 20;
+
+        write!(
+            f,
+            "{:<8} mean {:.2}  median {}  worst {}",
+            self.name,
+            self.mean(),
+            self.median(),
+            self.worst()
+        )?;
+        if let Some(max) = self.max_attempts {
+            write!(f, "  over the limit of {max}: {}", self.over_limit())?;
+        }
+        writeln!(f)?;
+
+        // long tails, like the linear strategy's, are grouped into at most MAX_ROWS rows
+        let histogram = self.histogram();
+        let (Some(&fewest), Some(&most)) = (histogram.keys().next(), histogram.keys().last())
+        else {
+            return Ok(());
+        };
+        let width = (most - fewest + 1).div_ceil(MAX_ROWS);
+        let mut rows: BTreeMap<u32, usize> = BTreeMap::new();
+        for (guesses, count) in histogram {
+            *rows
+                .entry(fewest + (guesses - fewest) / width * width)
+                .or_insert(0) += count;
+        }
+
+        let tallest = rows.values().copied().max().unwrap_or(1);
+        for (start, count) in rows {
+            let label = if width == 1 {
+                start.to_string()
+            } else {
+                format!("{start}-{}", start + width - 1)
+            };
+            // every non-empty row gets at least one mark so it's visible
+            let bar = (count * BAR_WIDTH).div_ceil(tallest);
+            writeln!(f, "  {label:>9} | {:<BAR_WIDTH$} {count}", "#".repeat(bar))?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `games` games with `strategy`, drawing every secret from `seed`.
+///
+/// The attempt limit of `config` isn't enforced, games over it are only
+/// counted, so the statistics cover how long each game really takes.
+pub fn simulate(strategy: &mut dyn Strategy, config: &Config, games: usize, seed: u64) -> Stats {
+    assert!(games > 0, "a simulation needs at least one game");
+
+    let mut rng = seeded_rng(seed);
+    let mut guesses: Vec<u32> = (0..games)
+        .map(|_| {
+            strategy::play(
+                &mut Game::with_rng(config.range().clone(), &mut rng),
+                strategy,
+            )
+        })
+        .collect();
+    guesses.sort_unstable();
+
+    Stats {
+        name: strategy.name(),
+        guesses,
+        max_attempts: config.max_attempts(),
+    }
+}
+
+/// Runs [`simulate`] on the same secrets for every strategy of the crate
+/// that is practical on the range of `config`.
+pub fn simulate_all(config: &Config, games: usize, seed: u64) -> Vec<Stats> {
+    // salted so that the random bot's stream stays apart from the one the
+    // secrets are drawn from
+    const STRATEGY_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    let size = range_size(config);
+    strategy::all(seed ^ STRATEGY_SALT)
+        .iter_mut()
+        .filter(|strategy| strategy.is_practical(size))
+        .map(|strategy| simulate(strategy.as_mut(), config, games, seed))
+        .collect()
+}
+
+/// The names of the strategies [`simulate_all`] leaves out on the range of
+/// `config`, because they would take too long.
+pub fn impractical(config: &Config) -> Vec<&'static str> {
+    let size = range_size(config);
+    strategy::all(0)
+        .iter()
+        .filter(|strategy| !strategy.is_practical(size))
+        .map(|strategy| strategy.name())
+        .collect()
+}
+
+fn range_size(config: &Config) -> u64 {
+    let range = config.range();
+    u64::from(range.end() - range.start()) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{BinarySearch, Ternary};
+
+    fn stats(guesses: Vec<u32>) -> Stats {
+        Stats {
+            name: "test",
+            guesses,
+            max_attempts: Some(3),
+        }
+    }
+
+    #[test]
+    fn summarises_guess_counts() {
+        let odd = stats(vec![1, 2, 2, 4, 6]);
+
+        assert_eq!(odd.mean(), 3.0);
+        assert_eq!(odd.median(), 2.0);
+        assert_eq!(odd.worst(), 6);
+        assert_eq!(odd.over_limit(), 2);
+        assert_eq!(
+            odd.histogram(),
+            BTreeMap::from([(1, 1), (2, 2), (4, 1), (6, 1)])
+        );
+        assert_eq!(stats(vec![1, 2, 3, 4]).median(), 2.5);
+    }
+
+    #[test]
+    fn is_reproducible() {
+        let config = Config::new(1, 100, None).unwrap();
+        let first = simulate_all(&config, 200, 9);
+
+        assert_eq!(first, simulate_all(&config, 200, 9));
+        assert_ne!(first, simulate_all(&config, 200, 10));
+        assert_eq!(first.len(), 4);
+    }
+
+    #[test]
+    fn leaves_out_the_linear_bot_on_large_ranges() {
+        let config = Config::new(1, 1_000_000, None).unwrap();
+        let names: Vec<&str> = simulate_all(&config, 10, 4)
+            .iter()
+            .map(Stats::name)
+            .collect();
+
+        assert_eq!(names, ["binary", "ternary", "random"]);
+        assert_eq!(impractical(&config), ["linear"]);
+        assert!(impractical(&Config::new(1, 100, None).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn binary_search_never_exceeds_its_bound() {
+        let config = Config::new(1, 1000, None).unwrap();
+        let binary = simulate(&mut BinarySearch, &config, 2000, 3);
+        let ternary = simulate(&mut Ternary, &config, 2000, 3);
+
+        assert!(binary.worst() <= strategy::optimal_worst_case(1000));
+        assert!(binary.mean() < ternary.mean());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::config::seeded_rng;
+use crate::game::{Feedback, Game};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+/// A bot that plays the guessing game on its own.
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    /// Picks the next guess from `bounds`, the part of the game's range still
+    /// consistent with the feedback so far.
+    fn next_guess(&mut self, bounds: &RangeInclusive<u32>) -> u32;
+
+    /// Whether the strategy gets through a game on `size` numbers quickly
+    /// enough to be worth simulating.
+    fn is_practical(&self, _size: u64) -> bool {
+        true
+    }
+}
+
+/// The largest range the linear strategy is simulated on: it needs half the
+/// range in guesses on average, which is billions on the whole of `u32`.
+pub const LINEAR_LIMIT: u64 = 10_000;
+
+/// The part of `bounds` still consistent with `feedback` on `guess`.
+pub fn narrow(bounds: &RangeInclusive<u32>, guess: u32, feedback: Feedback) -> RangeInclusive<u32> {
+    match feedback {
+        Feedback::TooSmall => (*bounds.start()).max(guess + 1)..=*bounds.end(),
+        Feedback::TooBig => *bounds.start()..=(*bounds.end()).min(guess - 1),
+        Feedback::Win => guess..=guess,
+        _ => bounds.clone(),
+    }
+}
+
+/// Always guesses the middle of what's left, the strategy the game is meant to teach.
+pub struct BinarySearch;
+
+impl Strategy for BinarySearch {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn next_guess(&mut self, bounds: &RangeInclusive<u32>) -> u32 {
+        bounds.start() + (bounds.end() - bounds.start()) / 2
+    }
+}
+
+/// Guesses the point a third of the way into what's left.
+pub struct Ternary;
+
+impl Strategy for Ternary {
+    fn name(&self) -> &'static str {
+        "ternary"
+    }
+
+    fn next_guess(&mut self, bounds: &RangeInclusive<u32>) -> u32 {
+        bounds.start() + (bounds.end() - bounds.start()) / 3
+    }
+}
+
+/// Guesses any number that's still possible, uniformly at random.
+pub struct Random {
+    rng: StdRng,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random {
+            rng: seeded_rng(seed),
+        }
+    }
+}
+
+impl Strategy for Random {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn next_guess(&mut self, bounds: &RangeInclusive<u32>) -> u32 {
+        self.rng.gen_range(bounds.clone())
+    }
+}
+
+/// Counts up from the smallest number that's still possible.
+pub struct Linear;
+
+impl Strategy for Linear {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn next_guess(&mut self, bounds: &RangeInclusive<u32>) -> u32 {
+        *bounds.start()
+    }
+
+    fn is_practical(&self, size: u64) -> bool {
+        size <= LINEAR_LIMIT
+    }
+}
+
+/// Every strategy shipped with the crate, the random one seeded with `seed`.
+pub fn all(seed: u64) -> Vec<Box<dyn Strategy>> {
+    vec![
+        Box::new(BinarySearch),
+        Box::new(Ternary),
+        Box::new(Random::new(seed)),
+        Box::new(Linear),
+    ]
+}
+
+/// Lets `strategy` play `game` until it wins and returns how many guesses it took.
+///
+/// The game should have no attempt limit. Panics if the strategy makes a guess
+/// the game rejects, since that would never end.
+pub fn play(game: &mut Game, strategy: &mut dyn Strategy) -> u32 {
+    let mut bounds = game.range().clone();
+
+    while !game.is_won() {
+        let guess = strategy.next_guess(&bounds);
+        let feedback = game.guess(guess);
+        assert!(
+            matches!(
+                feedback,
+                Feedback::TooSmall | Feedback::TooBig | Feedback::Win
+            ),
+            "the {} strategy guessed {guess} and got {feedback:?}",
+            strategy.name()
+        );
+        bounds = narrow(&bounds, guess, feedback);
+    }
+    game.attempts() as u32
+}
+
+/// The most guesses `strategy` needs for any secret in `range`.
+pub fn worst_case(strategy: &mut dyn Strategy, range: RangeInclusive<u32>) -> u32 {
+    range
+        .clone()
+        .map(|secret| play(&mut Game::new(secret, range.clone()), strategy))
+        .max()
+        .unwrap_or(0)
+}
+
+/// ⌈log2(n+1)⌉, the fewest guesses that can always find a secret among `n` numbers.
+pub fn optimal_worst_case(n: u64) -> u32 {
+    // n+1 needs exactly as many bits as n has, unless n+1 is a power of two;
+    // either way the ceiling of its logarithm is the bit length of n
+    u64::BITS - n.leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn narrows_with_feedback() {
+        let history = [
+            (50, Feedback::TooBig),
+            (20, Feedback::TooSmall),
+            (40, Feedback::TooBig),
+            (60, Feedback::TooBig),
+        ];
+        let bounds = history
+            .into_iter()
+            .fold(1..=100, |bounds, (guess, feedback)| {
+                narrow(&bounds, guess, feedback)
+            });
+        assert_eq!(bounds, 21..=39);
+        assert_eq!(narrow(&bounds, 30, Feedback::Win), 30..=30);
+    }
+
+    #[test]
+    fn every_strategy_finds_every_secret() {
+        for mut strategy in all(7) {
+            for secret in 1..=50 {
+                let guesses = play(&mut Game::new(secret, 1..=50), strategy.as_mut());
+                assert!(guesses <= 50, "{} took {guesses} guesses", strategy.name());
+            }
+        }
+    }
+
+    #[test]
+    fn linear_needs_one_guess_per_number() {
+        assert_eq!(worst_case(&mut Linear, 1..=30), 30);
+        assert!(Linear.is_practical(LINEAR_LIMIT));
+        assert!(!Linear.is_practical(LINEAR_LIMIT + 1));
+        assert!(BinarySearch.is_practical(1 << 32));
+    }
+
+    #[test]
+    fn binary_search_worst_case_is_optimal() {
+        for n in (1..=200).chain([255, 256, 257, 1000, 1023, 1024]) {
+            let bound = optimal_worst_case(n as u64);
+            assert_eq!(bound, (n as f64 + 1.0).log2().ceil() as u32);
+            assert_eq!(worst_case(&mut BinarySearch, 1..=n), bound, "range 1..={n}");
+        }
+    }
+
+    #[test]
+    fn binary_search_handles_the_edges_of_u32() {
+        let range = u32::MAX - 1000..=u32::MAX;
+        assert_eq!(
+            play(&mut Game::new(u32::MAX, range.clone()), &mut BinarySearch),
+            10
+        );
+        assert_eq!(play(&mut Game::new(0, 0..=u32::MAX), &mut BinarySearch), 32);
+    }
+}
@@ -0,0 +1,801 @@
+//! Hot-seat tournaments: several players take turns at one terminal over a
+//! series of rounds, with a running scoreboard.
+//!
+//! A tournament is set up by a JSON file like
+//!
+//! ```text
+//! {
+//!   "players": ["Ada", "Grace", "Linus"],
+//!   "secret": "shared",
+//!   "rounds": ["easy", "normal", {"min": 1, "max": 500, "attempts": 12}],
+//!   "sudden_death": "normal",
+//!   "seed": 42
+//! }
+//! ```
+//!
+//! where `secret` is `shared` for everyone in a round to chase the same
+//! number, or `own` for each player to get a secret of their own. A round is
+//! either a difficulty or a range with an optional attempt limit. Only
+//! `players` and `rounds` are required; sudden death defaults to normal and
+//! the seed to a random one.
+//!
+//! A round won in `n` guesses scores `2w - n` points, but at least 1, where
+//! `w` is the fewest guesses that can always find a secret in the round's
+//! range; a lost round scores nothing. A binary search always earns at least
+//! `w`, so harder rounds are worth more. Players tied for the most points at
+//! the end play sudden-death rounds on a shared secret, until one of them
+//! finds it in fewer guesses than all of the others.
+
+use crate::config::{seeded_rng, Config, Difficulty};
+use crate::game::Game;
+use crate::json::{self, Value};
+use crate::session::{self, plural, Console, Outcome};
+use crate::strategy::optimal_worst_case;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+const MAX_NAME: usize = 32;
+/// Moves the cursor home and clears the terminal, so the next player can't
+/// read the last one's guesses.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Whose secret a player chases in a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecretRule {
+    /// Everyone in a round guesses the same secret.
+    #[default]
+    Shared,
+    /// Each player gets a secret of their own.
+    Own,
+}
+
+impl SecretRule {
+    pub fn name(self) -> &'static str {
+        match self {
+            SecretRule::Shared => "shared",
+            SecretRule::Own => "own",
+        }
+    }
+}
+
+impl FromStr for SecretRule {
+    type Err = TournamentError;
+
+    fn from_str(s: &str) -> Result<SecretRule, TournamentError> {
+        [SecretRule::Shared, SecretRule::Own]
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| {
+                TournamentError::Invalid(format!("`secret` is `shared` or `own`, not `{s}`"))
+            })
+    }
+}
+
+/// Who plays a tournament and how, checked before it starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TournamentConfig {
+    players: Vec<String>,
+    rounds: Vec<Config>,
+    rule: SecretRule,
+    sudden_death: Config,
+    seed: Option<u64>,
+}
+
+impl TournamentConfig {
+    /// Needs at least two players with distinct names and at least one round.
+    pub fn new(
+        players: Vec<String>,
+        rounds: Vec<Config>,
+    ) -> Result<TournamentConfig, TournamentError> {
+        let invalid = |reason: String| Err(TournamentError::Invalid(reason));
+        if players.len() < 2 {
+            return invalid("a tournament needs at least two players".to_string());
+        }
+        for (index, name) in players.iter().enumerate() {
+            if name.trim().is_empty() || name.chars().count() > MAX_NAME {
+                return invalid(format!("player names have 1 to {MAX_NAME} characters"));
+            }
+            if players[..index].contains(name) {
+                return invalid(format!("{name} is in the list of players twice"));
+            }
+        }
+        if rounds.is_empty() {
+            return invalid("a tournament needs at least one round".to_string());
+        }
+
+        Ok(TournamentConfig {
+            players,
+            rounds,
+            rule: SecretRule::default(),
+            sudden_death: Difficulty::Normal.config(),
+            seed: None,
+        })
+    }
+
+    pub fn with_rule(mut self, rule: SecretRule) -> TournamentConfig {
+        self.rule = rule;
+        self
+    }
+
+    pub fn with_sudden_death(mut self, sudden_death: Config) -> TournamentConfig {
+        self.sudden_death = sudden_death;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> TournamentConfig {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn load(path: &Path) -> Result<TournamentConfig, TournamentError> {
+        TournamentConfig::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<TournamentConfig, TournamentError> {
+        let invalid = |reason: String| TournamentError::Invalid(reason);
+
+        let document = Value::parse(text)?;
+        let Value::Object(members) = &document else {
+            return Err(invalid("the file must hold a JSON object".to_string()));
+        };
+        let known = ["players", "rounds", "secret", "sudden_death", "seed"];
+        if let Some((name, _)) = members
+            .iter()
+            .find(|(name, _)| !known.contains(&name.as_str()))
+        {
+            return Err(invalid(format!("unknown setting `{name}`")));
+        }
+
+        let players = document
+            .get("players")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("`players` must be a list of names".to_string()))?
+            .iter()
+            .map(|player| {
+                player
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| invalid(format!("the player {player} is not a name")))
+            })
+            .collect::<Result<_, _>>()?;
+        let rounds = document
+            .get("rounds")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("`rounds` must be a list".to_string()))?
+            .iter()
+            .enumerate()
+            .map(|(index, round)| {
+                parse_round(round)
+                    .map_err(|reason| invalid(format!("round {}: {reason}", index + 1)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut config = TournamentConfig::new(players, rounds)?;
+        if let Some(rule) = document.get("secret") {
+            let rule = rule
+                .as_str()
+                .ok_or_else(|| invalid("`secret` must be a string".to_string()))?;
+            config = config.with_rule(rule.parse()?);
+        }
+        if let Some(round) = document.get("sudden_death") {
+            let round =
+                parse_round(round).map_err(|reason| invalid(format!("sudden death: {reason}")))?;
+            config = config.with_sudden_death(round);
+        }
+        if let Some(seed) = document.get("seed") {
+            let seed = seed
+                .as_u64()
+                .ok_or_else(|| invalid("`seed` must be a whole number".to_string()))?;
+            config = config.with_seed(seed);
+        }
+        Ok(config)
+    }
+
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    pub fn rounds(&self) -> &[Config] {
+        &self.rounds
+    }
+
+    pub fn rule(&self) -> SecretRule {
+        self.rule
+    }
+
+    pub fn sudden_death(&self) -> &Config {
+        &self.sudden_death
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+}
+
+/// A difficulty name, or an object with `min`, `max` and maybe `attempts`.
+fn parse_round(round: &Value) -> Result<Config, String> {
+    if let Some(name) = round.as_str() {
+        return name
+            .parse::<Difficulty>()
+            .map(Difficulty::config)
+            .map_err(|error| error.to_string());
+    }
+
+    let number = |name: &str| {
+        round
+            .get(name)
+            .map(|value| {
+                value
+                    .as_u32()
+                    .ok_or_else(|| format!("`{name}` must be a whole number"))
+            })
+            .transpose()
+    };
+    let (Some(min), Some(max)) = (number("min")?, number("max")?) else {
+        return Err("a round is a difficulty or has a `min` and a `max`".to_string());
+    };
+    Config::new(min, max, number("attempts")?).map_err(|error| error.to_string())
+}
+
+/// The points for `game`, played in a round of `config`.
+pub fn points(config: &Config, game: &Game) -> u32 {
+    if !game.is_won() {
+        return 0;
+    }
+    let range = config.range();
+    let worth = optimal_worst_case(u64::from(range.end() - range.start()) + 1);
+    (2 * worth).saturating_sub(game.attempts() as u32).max(1)
+}
+
+/// One line of the scoreboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Standing {
+    pub player: String,
+    pub points: u32,
+    /// Rounds the player found the secret in.
+    pub wins: u32,
+    /// Guesses over all rounds, won or lost.
+    pub guesses: usize,
+}
+
+/// How a tournament came to an end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TournamentOutcome {
+    Champion(String),
+    /// A player typed `quit`, so the rounds left weren't played.
+    Abandoned,
+    /// The input was closed before the tournament was decided.
+    EndOfInput,
+}
+
+/// A tournament in progress.
+pub struct Tournament {
+    config: TournamentConfig,
+    rng: StdRng,
+    standings: Vec<Standing>,
+    champion: Option<String>,
+    clear_screen: bool,
+}
+
+impl Tournament {
+    /// Starts a tournament drawing every secret from `seed`.
+    pub fn new(config: TournamentConfig, seed: u64) -> Tournament {
+        let standings = config
+            .players
+            .iter()
+            .map(|player| Standing {
+                player: player.clone(),
+                points: 0,
+                wins: 0,
+                guesses: 0,
+            })
+            .collect();
+
+        Tournament {
+            config,
+            rng: seeded_rng(seed),
+            standings,
+            champion: None,
+            clear_screen: false,
+        }
+    }
+
+    /// Clears the terminal after every turn, once the player has read how it
+    /// went, so the next one can't see their guesses.
+    pub fn with_clear_screen(mut self, clear_screen: bool) -> Tournament {
+        self.clear_screen = clear_screen;
+        self
+    }
+
+    /// Plays every round and any sudden death it takes to find a champion,
+    /// reading every player's input from `input` in turn.
+    pub fn play<R: BufRead, W: Write>(
+        &mut self,
+        mut input: R,
+        mut output: W,
+    ) -> io::Result<TournamentOutcome> {
+        let rounds = self.config.rounds.clone();
+        for (index, round) in rounds.iter().enumerate() {
+            writeln!(
+                output,
+                "\n=== Round {} of {}: {} ===",
+                index + 1,
+                rounds.len(),
+                describe(round)
+            )?;
+
+            let players: Vec<usize> = (0..self.standings.len()).collect();
+            let games = self.deal(round, players.len(), self.config.rule);
+            for (player, mut game) in players.into_iter().zip(games) {
+                let outcome = self.turn(player, &mut game, &mut input, &mut output)?;
+                match outcome {
+                    Outcome::Quit => return Ok(TournamentOutcome::Abandoned),
+                    Outcome::EndOfInput => return Ok(TournamentOutcome::EndOfInput),
+                    Outcome::Won | Outcome::Lost | Outcome::GaveUp => {}
+                }
+
+                let earned = points(round, &game);
+                let standing = &mut self.standings[player];
+                standing.points += earned;
+                standing.wins += u32::from(game.is_won());
+                standing.guesses += game.attempts();
+                writeln!(
+                    output,
+                    "{} {} and scores {}.",
+                    standing.player,
+                    if game.is_won() {
+                        format!(
+                            "found {} in {}",
+                            game.secret(),
+                            plural(game.attempts(), "guess")
+                        )
+                    } else {
+                        format!("missed {}", game.secret())
+                    },
+                    plural(earned as usize, "point")
+                )?;
+                self.pass_on(&mut input, &mut output)?;
+            }
+
+            writeln!(output, "\nScores after round {}:", index + 1)?;
+            write!(output, "{}", self.table())?;
+        }
+
+        let best = self.standings.iter().map(|standing| standing.points).max();
+        let mut tied: Vec<usize> = (0..self.standings.len())
+            .filter(|&player| Some(self.standings[player].points) == best)
+            .collect();
+
+        let mut sudden_death = 0;
+        while tied.len() > 1 {
+            sudden_death += 1;
+            let names: Vec<&str> = tied
+                .iter()
+                .map(|&player| self.standings[player].player.as_str())
+                .collect();
+            writeln!(
+                output,
+                "\n=== Sudden death {sudden_death}: {} on {} ===",
+                names.join(", "),
+                describe(&self.config.sudden_death)
+            )?;
+
+            let round = self.config.sudden_death.clone();
+            let games = self.deal(&round, tied.len(), SecretRule::Shared);
+            let mut results = Vec::new();
+            for (&player, mut game) in tied.iter().zip(games) {
+                match self.turn(player, &mut game, &mut input, &mut output)? {
+                    Outcome::Quit => return Ok(TournamentOutcome::Abandoned),
+                    Outcome::EndOfInput => return Ok(TournamentOutcome::EndOfInput),
+                    Outcome::Won | Outcome::Lost | Outcome::GaveUp => {}
+                }
+                writeln!(
+                    output,
+                    "{} {}.",
+                    self.standings[player].player,
+                    if game.is_won() {
+                        format!("found it in {}", plural(game.attempts(), "guess"))
+                    } else {
+                        "missed it".to_string()
+                    }
+                )?;
+                self.pass_on(&mut input, &mut output)?;
+                results.push((player, game.is_won().then(|| game.attempts())));
+            }
+
+            // nobody finding it leaves everyone in, otherwise the fastest stay
+            if let Some(fastest) = results.iter().filter_map(|(_, attempts)| *attempts).min() {
+                tied = results
+                    .iter()
+                    .filter(|(_, attempts)| *attempts == Some(fastest))
+                    .map(|(player, _)| *player)
+                    .collect();
+            }
+        }
+
+        let champion = self.standings[tied[0]].player.clone();
+        writeln!(output, "\nFinal standings:")?;
+        self.champion = Some(champion.clone());
+        write!(output, "{}", self.table())?;
+        writeln!(output, "{champion} wins the tournament!")?;
+        Ok(TournamentOutcome::Champion(champion))
+    }
+
+    /// A game of `round` for each of `count` players, all with the same secret
+    /// if the rule says so.
+    fn deal(&mut self, round: &Config, count: usize, rule: SecretRule) -> Vec<Game> {
+        let shared: u64 = self.rng.gen();
+        (0..count)
+            .map(|_| {
+                let seed = match rule {
+                    SecretRule::Shared => shared,
+                    SecretRule::Own => self.rng.gen(),
+                };
+                Game::from_config(round, &mut seeded_rng(seed)).with_seed(seed)
+            })
+            .collect()
+    }
+
+    fn turn<R: BufRead, W: Write>(
+        &self,
+        player: usize,
+        game: &mut Game,
+        input: &mut R,
+        output: &mut W,
+    ) -> io::Result<Outcome> {
+        writeln!(
+            output,
+            "\n{}, it's your turn!",
+            self.standings[player].player
+        )?;
+        if let Some(max) = game.max_attempts() {
+            writeln!(output, "You have {}.", plural(max as usize, "attempt"))?;
+        }
+        session::run(game, &mut Console { input, output })
+    }
+
+    fn pass_on<R: BufRead, W: Write>(&self, input: &mut R, output: &mut W) -> io::Result<()> {
+        if !self.clear_screen {
+            return Ok(());
+        }
+        writeln!(
+            output,
+            "Press Enter to hide your game and pass the keyboard on."
+        )?;
+        input.read_line(&mut String::new())?;
+        write!(output, "{CLEAR_SCREEN}")?;
+        output.flush()
+    }
+
+    /// The players from the most points to the fewest, ties in the order
+    /// they were listed in, and the champion first once there is one.
+    pub fn standings(&self) -> Vec<&Standing> {
+        let mut standings: Vec<&Standing> = self.standings.iter().collect();
+        standings.sort_by_key(|standing| {
+            (
+                std::cmp::Reverse(standing.points),
+                Some(&standing.player) != self.champion.as_ref(),
+            )
+        });
+        standings
+    }
+
+    /// The scoreboard as a table, tied players sharing a rank.
+    pub fn table(&self) -> String {
+        let standings = self.standings();
+        let width = standings
+            .iter()
+            .map(|standing| standing.player.chars().count())
+            .chain([6])
+            .max()
+            .unwrap_or_default();
+
+        let mut table = format!(
+            "{:>4}  {:<width$}  {:>6}  {:>4}  {:>7}\n",
+            "Rank", "Player", "Points", "Wins", "Guesses"
+        );
+        let mut rank = 0;
+        let mut previous = None;
+        for (index, standing) in standings.iter().enumerate() {
+            if previous != Some(standing.points) {
+                rank = index + 1;
+            }
+            previous = Some(standing.points);
+            let crown = if Some(&standing.player) == self.champion.as_ref() {
+                "  champion"
+            } else {
+                ""
+            };
+            table += &format!(
+                "{rank:>4}  {:<width$}  {:>6}  {:>4}  {:>7}{crown}\n",
+                standing.player, standing.points, standing.wins, standing.guesses
+            );
+        }
+        table
+    }
+}
+
+fn describe(round: &Config) -> String {
+    let range = round.range();
+    match round.max_attempts() {
+        Some(max) => format!(
+            "{}..={} in {}",
+            range.start(),
+            range.end(),
+            plural(max as usize, "attempt")
+        ),
+        None => format!("{}..={}", range.start(), range.end()),
+    }
+}
+
+#[derive(Debug)]
+pub enum TournamentError {
+    Io(io::Error),
+    Json(json::ParseError),
+    /// The file is valid JSON but not a tournament that can be played.
+    Invalid(String),
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TournamentError::Io(error) => write!(f, "{error}"),
+            TournamentError::Json(error) => write!(f, "the file is not valid JSON ({error})"),
+            TournamentError::Invalid(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl Error for TournamentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TournamentError::Io(error) => Some(error),
+            TournamentError::Json(error) => Some(error),
+            TournamentError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for TournamentError {
+    fn from(error: io::Error) -> TournamentError {
+        TournamentError::Io(error)
+    }
+}
+
+impl From<json::ParseError> for TournamentError {
+    fn from(error: json::ParseError) -> TournamentError {
+        TournamentError::Json(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(players: &[&str]) -> Vec<String> {
+        players.iter().map(|player| player.to_string()).collect()
+    }
+
+    fn round(min: u32, max: u32, attempts: Option<u32>) -> Config {
+        Config::new(min, max, attempts).unwrap()
+    }
+
+    /// The shared secrets a tournament with `seed` deals for `rounds`, in order.
+    fn secrets(config: &TournamentConfig, seed: u64, rounds: &[Config]) -> Vec<u32> {
+        let mut peek = Tournament::new(config.clone(), seed);
+        rounds
+            .iter()
+            .map(|round| peek.deal(round, 1, SecretRule::Shared)[0].secret())
+            .collect()
+    }
+
+    fn play(tournament: &mut Tournament, input: &str) -> (TournamentOutcome, String) {
+        let mut output = Vec::new();
+        let outcome = tournament.play(input.as_bytes(), &mut output).unwrap();
+        (outcome, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parses_a_tournament_file() {
+        let config = TournamentConfig::parse(
+            r#"{
+                "players": ["Ada", "Grace", "Linus"],
+                "secret": "own",
+                "rounds": ["easy", "Hard", {"min": 5, "max": 500, "attempts": 12}, {"min": 1, "max": 2}],
+                "sudden_death": {"min": 1, "max": 50},
+                "seed": 42
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.players(), names(&["Ada", "Grace", "Linus"]));
+        assert_eq!(config.rule(), SecretRule::Own);
+        assert_eq!(
+            config.rounds(),
+            [
+                Difficulty::Easy.config(),
+                Difficulty::Hard.config(),
+                round(5, 500, Some(12)),
+                round(1, 2, None)
+            ]
+        );
+        assert_eq!(config.sudden_death(), &round(1, 50, None));
+        assert_eq!(config.seed(), Some(42));
+
+        let minimal =
+            TournamentConfig::parse(r#"{"players":["a","b"],"rounds":["normal"]}"#).unwrap();
+        assert_eq!(minimal.rule(), SecretRule::Shared);
+        assert_eq!(minimal.sudden_death(), &Difficulty::Normal.config());
+        assert_eq!(minimal.seed(), None);
+    }
+
+    #[test]
+    fn rejects_bad_tournament_files() {
+        let error = |text: &str| TournamentConfig::parse(text).unwrap_err().to_string();
+
+        assert!(matches!(
+            TournamentConfig::parse("{"),
+            Err(TournamentError::Json(_))
+        ));
+        assert_eq!(
+            error(r#"{"players":["a"],"rounds":["easy"]}"#),
+            "a tournament needs at least two players"
+        );
+        assert_eq!(
+            error(r#"{"players":["a","a"],"rounds":["easy"]}"#),
+            "a is in the list of players twice"
+        );
+        assert_eq!(
+            error(r#"{"players":["a","b"],"rounds":[]}"#),
+            "a tournament needs at least one round"
+        );
+        assert_eq!(
+            error(r#"{"players":["a","b"],"rounds":["easy",{"min":9,"max":2}]}"#),
+            "round 2: the minimum 9 is greater than the maximum 2"
+        );
+        assert_eq!(
+            error(r#"{"players":["a","b"],"rounds":["brutal"]}"#),
+            "round 1: unknown difficulty `brutal`, expected one of easy, normal or hard"
+        );
+        assert_eq!(
+            error(r#"{"players":["a","b"],"rounds":["easy"],"secret":"mine"}"#),
+            "`secret` is `shared` or `own`, not `mine`"
+        );
+        assert_eq!(
+            error(r#"{"players":["a","b"],"rounds":["easy"],"seeds":1}"#),
+            "unknown setting `seeds`"
+        );
+    }
+
+    #[test]
+    fn harder_rounds_are_worth_more() {
+        let won_in = |config: &Config, secret: u32, guesses: &[u32]| {
+            let mut game =
+                Game::new(secret, config.range().clone()).with_max_attempts(config.max_attempts());
+            for &guess in guesses {
+                game.guess(guess);
+            }
+            points(config, &game)
+        };
+        let easy = Difficulty::Easy.config();
+        let hard = Difficulty::Hard.config();
+
+        assert_eq!(won_in(&easy, 5, &[5]), 7);
+        assert_eq!(won_in(&easy, 5, &[2, 8, 5]), 5);
+        assert_eq!(won_in(&easy, 5, &[1, 2, 3, 4, 6, 7, 8, 9, 10, 5]), 1);
+        assert_eq!(won_in(&easy, 5, &[1]), 0);
+        assert_eq!(won_in(&hard, 500, &[500]), 19);
+        assert_eq!(
+            won_in(
+                &hard,
+                500,
+                &[250, 375, 437, 468, 484, 492, 496, 498, 499, 500]
+            ),
+            10
+        );
+    }
+
+    #[test]
+    fn shares_secrets_by_the_rule() {
+        let config =
+            TournamentConfig::new(names(&["a", "b", "c"]), vec![Difficulty::Hard.config()])
+                .unwrap();
+        let mut tournament = Tournament::new(config, 1);
+
+        let shared = tournament.deal(&Difficulty::Hard.config(), 3, SecretRule::Shared);
+        assert!(shared
+            .iter()
+            .all(|game| game.secret() == shared[0].secret()));
+        let own = tournament.deal(&Difficulty::Hard.config(), 3, SecretRule::Own);
+        let secrets: Vec<u32> = own.iter().map(Game::secret).collect();
+        assert!(
+            secrets.windows(2).any(|pair| pair[0] != pair[1]),
+            "{secrets:?}"
+        );
+    }
+
+    #[test]
+    fn plays_rounds_and_ranks_the_players() {
+        let rounds = vec![round(1, 2, None), round(1, 2, Some(1))];
+        let config = TournamentConfig::new(names(&["Ada", "Grace"]), rounds.clone()).unwrap();
+        let [first, second] = secrets(&config, 7, &rounds)[..] else {
+            unreachable!()
+        };
+        let mut tournament = Tournament::new(config, 7);
+
+        let input = format!("{first}\n{first}\n{second}\ngiveup\n");
+        let (outcome, output) = play(&mut tournament, &input);
+        assert_eq!(outcome, TournamentOutcome::Champion("Ada".to_string()));
+        assert!(output.contains("=== Round 1 of 2: 1..=2 ==="));
+        assert!(output.contains("=== Round 2 of 2: 1..=2 in 1 attempt ==="));
+        assert!(output.contains(&format!(
+            "Ada found {first} in 1 guess and scores 3 points."
+        )));
+        assert!(output.contains(&format!("Grace missed {second} and scores 0 points.")));
+        assert!(!output.contains("Sudden death"));
+        assert!(output.ends_with(
+            "Final standings:\n\
+             Rank  Player  Points  Wins  Guesses\n   \
+                1  Ada          6     2        2  champion\n   \
+                2  Grace        3     1        1\n\
+             Ada wins the tournament!\n"
+        ));
+    }
+
+    #[test]
+    fn breaks_ties_with_sudden_death() {
+        let config = TournamentConfig::new(names(&["Ada", "Grace"]), vec![round(1, 2, None)])
+            .unwrap()
+            .with_sudden_death(round(1, 2, Some(1)));
+        let rounds = [round(1, 2, None), round(1, 2, Some(1))];
+        let [first, decider] = secrets(&config, 3, &rounds)[..] else {
+            unreachable!()
+        };
+        let mut tournament = Tournament::new(config, 3);
+
+        let input = format!("{first}\n{first}\n{}\n{decider}\n", 3 - decider);
+        let (outcome, output) = play(&mut tournament, &input);
+        assert_eq!(outcome, TournamentOutcome::Champion("Grace".to_string()));
+        assert!(output.contains("=== Sudden death 1: Ada, Grace on 1..=2 in 1 attempt ==="));
+        assert!(output.contains("Ada missed it.\n"));
+        assert!(output.contains("Grace found it in 1 guess.\n"));
+        assert!(!output.contains("Sudden death 2"));
+        assert!(output.ends_with(
+            "   1  Grace        3     1        1  champion\n   \
+                1  Ada          3     1        1\n\
+             Grace wins the tournament!\n"
+        ));
+    }
+
+    #[test]
+    fn stops_when_a_player_quits_or_the_input_ends() {
+        let rounds = vec![round(1, 2, None)];
+        let config = TournamentConfig::new(names(&["Ada", "Grace"]), rounds.clone()).unwrap();
+        let secret = secrets(&config, 1, &rounds)[0];
+
+        let input = format!("{secret}\nquit\n");
+        let (outcome, _) = play(&mut Tournament::new(config.clone(), 1), &input);
+        assert_eq!(outcome, TournamentOutcome::Abandoned);
+        let (outcome, output) = play(&mut Tournament::new(config, 1), &format!("{secret}\n"));
+        assert_eq!(outcome, TournamentOutcome::EndOfInput);
+        assert!(output.contains("Grace, it's your turn!"));
+    }
+
+    #[test]
+    fn clears_the_screen_between_turns() {
+        let rounds = vec![round(1, 2, None)];
+        let config = TournamentConfig::new(names(&["Ada", "Grace"]), rounds.clone()).unwrap();
+        let secret = secrets(&config, 1, &rounds)[0];
+        let mut tournament = Tournament::new(config, 1).with_clear_screen(true);
+
+        let (_, output) = play(&mut tournament, &format!("{secret}\n\n{secret}\n\n"));
+        assert_eq!(output.matches(CLEAR_SCREEN).count(), 2);
+        assert!(output.contains("Press Enter to hide your game and pass the keyboard on."));
+    }
+}
@@ -0,0 +1,885 @@
+//! Transcripts of console sessions, written with `--record` and checked
+//! against the engine with `--replay`.
+//!
+//! A transcript is one JSON object per line: first the game the session was
+//! played on, then every line read or printed in order, with the
+//! milliseconds since the session started, and last how it ended:
+//!
+//! ```text
+//! {"version":1,"min":1,"max":100,"max_attempts":null,"feedback":"classic","coach":false,"seed":42,"secret":37,"history":[]}
+//! {"ms":0,"out":"Please input your guess!"}
+//! {"ms":2113,"in":"50"}
+//! {"ms":2113,"out":"You guessed: 50"}
+//! {"ms":2113,"out":"Too big!"}
+//! {"ms":9870,"outcome":"won"}
+//! ```
+//!
+//! A replay types the same lines into a game with the same secret and fails
+//! at the first line the engine prints differently, so a transcript recorded
+//! once is a golden test of the game logic from then on. The secret is
+//! written out rather than drawn from the seed again, which keeps old
+//! transcripts valid if `rand` changes its generator, and it means a
+//! transcript gives the game away to anyone who reads it.
+
+use crate::coach::Coached;
+use crate::config::Config;
+use crate::game::{Feedback, Game};
+use crate::json::Value;
+use crate::machine::outcome_name;
+use crate::policy::FeedbackPolicy;
+use crate::save::SavedGame;
+use crate::session::{self, Console, Frontend, Input, Outcome};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, Read, Write};
+use std::mem;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// The version of the file format this build writes.
+pub const VERSION: u64 = 1;
+
+/// The game a transcript was recorded on, as it was when the session started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setup {
+    pub range: RangeInclusive<u32>,
+    pub max_attempts: Option<u32>,
+    pub feedback: FeedbackPolicy,
+    /// Whether the [`crate::coach`] was grading the guesses.
+    pub coach: bool,
+    pub seed: Option<u64>,
+    pub secret: u32,
+    /// Guesses made before the session, as in a resumed game.
+    pub history: Vec<u32>,
+}
+
+impl Setup {
+    pub fn new(game: &Game, coach: bool) -> Setup {
+        Setup {
+            range: game.range().clone(),
+            max_attempts: game.max_attempts(),
+            feedback: game.policy(),
+            coach,
+            seed: game.seed(),
+            secret: game.secret(),
+            history: game.history().to_vec(),
+        }
+    }
+
+    /// Sets the game up again, playing the guesses it started with.
+    pub fn game(&self) -> Result<Game, TranscriptError> {
+        let invalid = |reason: String| TranscriptError::Invalid(reason);
+
+        let config = Config::new(*self.range.start(), *self.range.end(), self.max_attempts)
+            .map_err(|error| invalid(error.to_string()))?;
+        if !config.range().contains(&self.secret) {
+            return Err(invalid("the secret is outside the range".to_string()));
+        }
+
+        let mut game = Game::new(self.secret, config.range().clone())
+            .with_max_attempts(config.max_attempts())
+            .with_policy(self.feedback);
+        if let Some(seed) = self.seed {
+            game = game.with_seed(seed);
+        }
+        for &guess in &self.history {
+            if !matches!(game.guess(guess), Feedback::TooSmall | Feedback::TooBig) {
+                return Err(invalid(format!(
+                    "the earlier guess {guess} could never have been made"
+                )));
+            }
+        }
+        Ok(game)
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::object([
+            ("version", VERSION.into()),
+            ("min", (*self.range.start()).into()),
+            ("max", (*self.range.end()).into()),
+            ("max_attempts", self.max_attempts.into()),
+            ("feedback", self.feedback.name().into()),
+            ("coach", self.coach.into()),
+            ("seed", self.seed.into()),
+            ("secret", self.secret.into()),
+            ("history", self.history.clone().into()),
+        ])
+    }
+
+    fn from_json(document: &Value) -> Result<Setup, TranscriptError> {
+        let corrupt = |reason: &str| TranscriptError::Corrupt(1, reason.to_string());
+
+        match document.get("version").and_then(Value::as_u64) {
+            Some(VERSION) => {}
+            Some(version) => return Err(TranscriptError::UnsupportedVersion(version)),
+            None => return Err(corrupt("no version number")),
+        }
+
+        let field = |name: &str| {
+            document
+                .get(name)
+                .ok_or_else(|| corrupt(&format!("no `{name}`")))
+        };
+        let number = |name: &str| {
+            field(name)?
+                .as_u32()
+                .ok_or_else(|| corrupt(&format!("`{name}` is not a number")))
+        };
+        let max_attempts = match field("max_attempts")? {
+            Value::Null => None,
+            limit => Some(
+                limit
+                    .as_u32()
+                    .ok_or_else(|| corrupt("`max_attempts` is not a number"))?,
+            ),
+        };
+        let seed = match field("seed")? {
+            Value::Null => None,
+            seed => Some(
+                seed.as_u64()
+                    .ok_or_else(|| corrupt("`seed` is not a number"))?,
+            ),
+        };
+        let history = field("history")?
+            .as_array()
+            .ok_or_else(|| corrupt("`history` is not a list"))?
+            .iter()
+            .map(|guess| {
+                guess
+                    .as_u32()
+                    .ok_or_else(|| corrupt("unreadable guess in `history`"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Setup {
+            range: number("min")?..=number("max")?,
+            max_attempts,
+            feedback: field("feedback")?
+                .as_str()
+                .and_then(|name| name.parse().ok())
+                .ok_or_else(|| corrupt("unknown `feedback`"))?,
+            coach: field("coach")?
+                .as_bool()
+                .ok_or_else(|| corrupt("`coach` is not true or false"))?,
+            seed,
+            secret: number("secret")?,
+            history,
+        })
+    }
+}
+
+/// A line that went into or came out of the console.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    Input(String),
+    Output(String),
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Input(text) => write!(f, "the input {text:?}"),
+            Line::Output(text) => write!(f, "the output {text:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// How long after the start of the session the line was read or printed.
+    pub elapsed: Duration,
+    pub line: Line,
+}
+
+impl Entry {
+    pub fn to_json(&self) -> Value {
+        let (direction, text) = match &self.line {
+            Line::Input(text) => ("in", text),
+            Line::Output(text) => ("out", text),
+        };
+        Value::object([
+            ("ms", (self.elapsed.as_millis() as u64).into()),
+            (direction, text.as_str().into()),
+        ])
+    }
+}
+
+/// A whole transcript, as read back from its file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    pub setup: Setup,
+    /// Every line of the session, `entries[n]` being on line `n + 2` of the file.
+    pub entries: Vec<Entry>,
+    /// How the session ended, or `None` if the transcript stops before it did.
+    pub outcome: Option<Outcome>,
+}
+
+impl Transcript {
+    pub fn parse(text: &str) -> Result<Transcript, TranscriptError> {
+        let mut lines = text.lines().enumerate().map(|(index, line)| {
+            let number = index + 1;
+            Value::parse(line)
+                .map(|value| (number, value))
+                .map_err(|error| TranscriptError::Corrupt(number, error.to_string()))
+        });
+
+        let (_, header) = lines
+            .next()
+            .ok_or_else(|| TranscriptError::Corrupt(1, "the file is empty".to_string()))??;
+        let mut transcript = Transcript {
+            setup: Setup::from_json(&header)?,
+            entries: Vec::new(),
+            outcome: None,
+        };
+
+        for line in lines {
+            let (number, value) = line?;
+            let corrupt = |reason: &str| TranscriptError::Corrupt(number, reason.to_string());
+            if transcript.outcome.is_some() {
+                return Err(corrupt("there is more after the outcome"));
+            }
+
+            let elapsed = value
+                .get("ms")
+                .and_then(Value::as_u64)
+                .map(Duration::from_millis)
+                .ok_or_else(|| corrupt("no `ms`"))?;
+            let text = |name: &str| value.get(name).and_then(Value::as_str).map(str::to_string);
+            if let Some(text) = text("in") {
+                transcript.entries.push(Entry {
+                    elapsed,
+                    line: Line::Input(text),
+                });
+            } else if let Some(text) = text("out") {
+                transcript.entries.push(Entry {
+                    elapsed,
+                    line: Line::Output(text),
+                });
+            } else if let Some(name) = text("outcome") {
+                let outcome = OUTCOMES
+                    .into_iter()
+                    .find(|&outcome| outcome_name(outcome) == name)
+                    .ok_or_else(|| corrupt("unknown `outcome`"))?;
+                transcript.outcome = Some(outcome);
+            } else {
+                return Err(corrupt("neither `in`, `out` nor `outcome`"));
+            }
+        }
+        Ok(transcript)
+    }
+
+    pub fn load(path: &Path) -> Result<Transcript, TranscriptError> {
+        Transcript::parse(&fs::read_to_string(path)?)
+    }
+}
+
+const OUTCOMES: [Outcome; 5] = [
+    Outcome::Won,
+    Outcome::Lost,
+    Outcome::GaveUp,
+    Outcome::Quit,
+    Outcome::EndOfInput,
+];
+
+/// Takes down every line a console reads and prints, through the input and
+/// output it hands out from [`Recorder::console`].
+pub struct Recorder {
+    log: Rc<RefCell<Log>>,
+}
+
+impl Recorder {
+    /// Records a session of `game` to a new file at `path`, line by line so
+    /// that a session that crashes still leaves what led up to it.
+    pub fn create(path: &Path, game: &Game, coach: bool) -> io::Result<Recorder> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", Setup::new(game, coach).to_json())?;
+        file.flush()?;
+        Ok(Recorder::with_sink(Some(Box::new(file))))
+    }
+
+    /// Records to memory only, for [`Recorder::finish`] to hand back.
+    pub fn in_memory() -> Recorder {
+        Recorder::with_sink(None)
+    }
+
+    fn with_sink(sink: Option<Box<dyn Write>>) -> Recorder {
+        Recorder {
+            log: Rc::new(RefCell::new(Log {
+                started: Instant::now(),
+                sink,
+                error: None,
+                entries: Vec::new(),
+                input: Vec::new(),
+                output: Vec::new(),
+            })),
+        }
+    }
+
+    /// A console on `input` and `output` whose every line is recorded.
+    pub fn console<R: BufRead, W: Write>(
+        &self,
+        input: R,
+        output: W,
+    ) -> Console<Recorded<R>, Recorded<W>> {
+        Console {
+            input: Recorded {
+                inner: input,
+                log: Rc::clone(&self.log),
+            },
+            output: Recorded {
+                inner: output,
+                log: Rc::clone(&self.log),
+            },
+        }
+    }
+
+    /// Ends the transcript with `outcome` and returns every line of it, or
+    /// the first error writing the file ran into.
+    pub fn finish(self, outcome: Outcome) -> io::Result<Vec<Entry>> {
+        let mut log = self.log.borrow_mut();
+        if !log.output.is_empty() {
+            log.print(b"\n");
+        }
+
+        let ms = log.started.elapsed().as_millis() as u64;
+        log.write(&Value::object([
+            ("ms", ms.into()),
+            ("outcome", outcome_name(outcome).into()),
+        ]));
+        match log.error.take() {
+            Some(error) => Err(error),
+            None => Ok(mem::take(&mut log.entries)),
+        }
+    }
+}
+
+/// Adds `bytes` to `pending` and takes out the lines they complete.
+fn complete_lines(pending: &mut Vec<u8>, bytes: &[u8]) -> Vec<String> {
+    pending.extend_from_slice(bytes);
+    let mut lines = Vec::new();
+    while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+        let line: Vec<u8> = pending.drain(..=end).collect();
+        lines.push(
+            String::from_utf8_lossy(&line[..end])
+                .trim_end_matches('\r')
+                .to_string(),
+        );
+    }
+    lines
+}
+
+struct Log {
+    started: Instant,
+    sink: Option<Box<dyn Write>>,
+    /// The first error writing to the sink, after which it's left alone.
+    error: Option<io::Error>,
+    entries: Vec<Entry>,
+    /// What was read or printed since the last complete line.
+    input: Vec<u8>,
+    output: Vec<u8>,
+}
+
+impl Log {
+    fn read(&mut self, bytes: &[u8]) {
+        for text in complete_lines(&mut self.input, bytes) {
+            self.push(Line::Input(text));
+        }
+    }
+
+    fn print(&mut self, bytes: &[u8]) {
+        for text in complete_lines(&mut self.output, bytes) {
+            self.push(Line::Output(text));
+        }
+    }
+
+    fn push(&mut self, line: Line) {
+        let entry = Entry {
+            elapsed: self.started.elapsed(),
+            line,
+        };
+        self.write(&entry.to_json());
+        self.entries.push(entry);
+    }
+
+    fn write(&mut self, value: &Value) {
+        let Some(sink) = self.sink.as_mut() else {
+            return;
+        };
+        if self.error.is_none() {
+            if let Err(error) = writeln!(sink, "{value}").and_then(|()| sink.flush()) {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+/// The input or the output of a recorded console.
+pub struct Recorded<T> {
+    inner: T,
+    log: Rc<RefCell<Log>>,
+}
+
+impl<R: BufRead> Read for Recorded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.log.borrow_mut().read(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for Recorded<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let buffer = self.inner.fill_buf()?;
+        // the last line may have no newline, and then it ends with the input
+        let mut log = self.log.borrow_mut();
+        if buffer.is_empty() && !log.input.is_empty() {
+            log.read(b"\n");
+        }
+        Ok(buffer)
+    }
+
+    fn consume(&mut self, amount: usize) {
+        // what's consumed was just returned by fill_buf, so this reads nothing new
+        if let Ok(buffer) = self.inner.fill_buf() {
+            let consumed = buffer[..amount.min(buffer.len())].to_vec();
+            self.log.borrow_mut().read(&consumed);
+        }
+        self.inner.consume(amount);
+    }
+}
+
+impl<W: Write> Write for Recorded<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.log.borrow_mut().print(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Plays the session of `transcript` again and checks the engine prints
+/// exactly what it did then.
+///
+/// Saves are only pretended: a replay says the game was saved without
+/// touching the file, which may be somebody's real save by now.
+pub fn replay(transcript: &Transcript) -> Result<(), TranscriptError> {
+    let expected_outcome = transcript.outcome.ok_or(TranscriptError::Unfinished)?;
+    let mut game = transcript.setup.game()?;
+    let input: String = transcript
+        .entries
+        .iter()
+        .filter_map(|entry| match &entry.line {
+            Line::Input(text) => Some(format!("{text}\n")),
+            Line::Output(_) => None,
+        })
+        .collect();
+
+    let recorder = Recorder::in_memory();
+    let mut console = recorder.console(input.as_bytes(), io::sink());
+    let outcome = if transcript.setup.coach {
+        let mut coached = Coached::new(console, &game);
+        session::run(&mut game, &mut DryRun(&mut coached))?
+    } else {
+        session::run(&mut game, &mut DryRun(&mut console))?
+    };
+    let replayed = recorder.finish(outcome)?;
+
+    let diverged = |index: usize, expected: String, found: String| {
+        Err(TranscriptError::Diverged(Divergence {
+            line: index + 2,
+            expected,
+            found,
+        }))
+    };
+    let end = "the end of the session".to_string();
+    for (index, entry) in transcript.entries.iter().enumerate() {
+        match replayed.get(index) {
+            Some(actual) if actual.line == entry.line => {}
+            actual => {
+                let found = actual.map_or(end.clone(), |actual| actual.line.to_string());
+                return diverged(index, entry.line.to_string(), found);
+            }
+        }
+    }
+    let index = transcript.entries.len();
+    if let Some(extra) = replayed.get(index) {
+        return diverged(index, end, extra.line.to_string());
+    }
+    if outcome != expected_outcome {
+        return diverged(
+            index,
+            format!("the outcome {}", outcome_name(expected_outcome)),
+            format!("the outcome {}", outcome_name(outcome)),
+        );
+    }
+    Ok(())
+}
+
+/// Passes everything on to the console but saves, which it words the way
+/// the console does without writing anything.
+struct DryRun<'a>(&'a mut dyn Frontend);
+
+impl Frontend for DryRun<'_> {
+    fn read(&mut self) -> io::Result<Input> {
+        self.0.read()
+    }
+
+    fn prompt(&mut self, game: &Game) -> io::Result<()> {
+        self.0.prompt(game)
+    }
+
+    fn invalid(&mut self, message: &str) -> io::Result<()> {
+        self.0.invalid(message)
+    }
+
+    fn feedback(&mut self, game: &Game, guess: u32, feedback: Feedback) -> io::Result<()> {
+        self.0.feedback(game, guess, feedback)
+    }
+
+    fn hint(&mut self, game: &Game) -> io::Result<()> {
+        self.0.hint(game)
+    }
+
+    fn history(&mut self, game: &Game) -> io::Result<()> {
+        self.0.history(game)
+    }
+
+    fn help(&mut self) -> io::Result<()> {
+        self.0.help()
+    }
+
+    fn save(&mut self, game: &Game, path: &Path) -> io::Result<()> {
+        let saved = SavedGame::new(game).map(drop);
+        self.0.invalid(&session::save_message(saved, path))
+    }
+
+    fn finish(&mut self, game: &Game, outcome: Outcome) -> io::Result<()> {
+        self.0.finish(game, outcome)
+    }
+}
+
+/// The first line where a replay and its transcript part ways.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The line of the transcript file, counting from 1.
+    pub line: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+#[derive(Debug)]
+pub enum TranscriptError {
+    Io(io::Error),
+    /// This line of the file can't be read.
+    Corrupt(usize, String),
+    /// The file was written by a newer version of the game.
+    UnsupportedVersion(u64),
+    /// The file is well-formed but the game in it can't be set up.
+    Invalid(String),
+    /// The transcript stops before the session ended, so there is nothing
+    /// to hold the end of a replay to.
+    Unfinished,
+    /// The engine didn't do what the transcript says it did.
+    Diverged(Divergence),
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TranscriptError::Io(error) => write!(f, "{error}"),
+            TranscriptError::Corrupt(line, reason) => {
+                write!(f, "line {line} of the transcript is corrupt ({reason})")
+            }
+            TranscriptError::UnsupportedVersion(version) => write!(
+                f,
+                "the transcript is version {version}, this game only reads version {VERSION}"
+            ),
+            TranscriptError::Invalid(reason) => write!(f, "the transcript is invalid: {reason}"),
+            TranscriptError::Unfinished => {
+                write!(f, "the transcript ends before the session did")
+            }
+            TranscriptError::Diverged(divergence) => write!(
+                f,
+                "the engine diverges at line {} of the transcript: expected {}, but got {}",
+                divergence.line, divergence.expected, divergence.found
+            ),
+        }
+    }
+}
+
+impl Error for TranscriptError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TranscriptError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TranscriptError {
+    fn from(error: io::Error) -> TranscriptError {
+        TranscriptError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game() -> Game {
+        Game::new(37, 1..=100)
+            .with_max_attempts(Some(7))
+            .with_seed(42)
+    }
+
+    /// Plays `input` on `game` with a recording console, returning the
+    /// transcript as it would be read back from its file.
+    fn record(mut game: Game, coach: bool, input: &str) -> Transcript {
+        let recorder = Recorder::in_memory();
+        let setup = Setup::new(&game, coach);
+        let mut console = recorder.console(input.as_bytes(), Vec::new());
+        let outcome = if coach {
+            let mut coached = Coached::new(console, &game);
+            session::run(&mut game, &mut coached).unwrap()
+        } else {
+            session::run(&mut game, &mut console).unwrap()
+        };
+        Transcript {
+            setup,
+            entries: recorder.finish(outcome).unwrap(),
+            outcome: Some(outcome),
+        }
+    }
+
+    fn lines(transcript: &Transcript) -> Vec<Line> {
+        transcript
+            .entries
+            .iter()
+            .map(|entry| entry.line.clone())
+            .collect()
+    }
+
+    fn output(text: &str) -> Line {
+        Line::Output(text.to_string())
+    }
+
+    fn input(text: &str) -> Line {
+        Line::Input(text.to_string())
+    }
+
+    #[test]
+    fn records_every_line_in_order() {
+        let transcript = record(game(), false, "50\nhint\n37");
+
+        assert_eq!(
+            lines(&transcript),
+            [
+                output("Please input your guess!"),
+                input("50"),
+                output("You guessed: 50"),
+                output("Too big!"),
+                output("Please input your guess!"),
+                input("hint"),
+                output("The secret number is between 1 and 49."),
+                output("Please input your guess!"),
+                input("37"),
+                output("You guessed: 37"),
+                output("You win!"),
+            ]
+        );
+        assert_eq!(transcript.outcome, Some(Outcome::Won));
+    }
+
+    #[test]
+    fn writes_and_reads_back_the_file() {
+        let path = std::env::temp_dir().join(format!(
+            "guessing_game-transcript-{}.jsonl",
+            std::process::id()
+        ));
+        let mut game = game();
+        let recorder = Recorder::create(&path, &game, false).unwrap();
+        let console = recorder.console(&b"50\n25\nquit\n"[..], Vec::new());
+        let outcome = session::run(&mut game, &mut { console }).unwrap();
+        let entries = recorder.finish(outcome).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(text.starts_with(
+            "{\"version\":1,\"min\":1,\"max\":100,\"max_attempts\":7,\"feedback\":\"classic\",\
+             \"coach\":false,\"seed\":42,\"secret\":37,\"history\":[]}\n"
+        ));
+        assert!(text.ends_with(",\"outcome\":\"quit\"}\n"));
+
+        let transcript = Transcript::parse(&text).unwrap();
+        assert_eq!(transcript.setup, Setup::new(&self::game(), false));
+        // the file keeps whole milliseconds only
+        let written: Vec<Line> = entries.into_iter().map(|entry| entry.line).collect();
+        assert_eq!(lines(&transcript), written);
+        assert_eq!(transcript.outcome, Some(Outcome::Quit));
+        replay(&transcript).unwrap();
+    }
+
+    #[test]
+    fn replays_what_it_recorded() {
+        let sessions = [
+            (game(), false, "50\n50\n0\nfoo\nhistory\nhelp\n37\n"),
+            (game(), false, "1\n2\n3\n4\n5\n6\n7\n"),
+            (game(), false, "60\ngiveup\n"),
+            (game(), false, "60\n"),
+            (game(), true, "50\n25\n37\n"),
+            (
+                game().with_policy(FeedbackPolicy::Heat),
+                false,
+                "90\n50\n40\n",
+            ),
+        ];
+
+        for (game, coach, input) in sessions {
+            let transcript = record(game, coach, input);
+            if let Err(error) = replay(&transcript) {
+                panic!("{input:?} doesn't replay: {error}");
+            }
+        }
+    }
+
+    #[test]
+    fn replays_a_resumed_game() {
+        let mut resumed = game();
+        resumed.guess(50);
+        resumed.guess(25);
+        let transcript = record(resumed, true, "history\n37\n");
+
+        assert_eq!(transcript.setup.history, [50, 25]);
+        replay(&transcript).unwrap();
+    }
+
+    #[test]
+    fn fails_on_the_first_line_that_differs() {
+        let mut transcript = record(game(), false, "50\n37\n");
+        transcript.entries[3].line = output("Too small!");
+
+        match replay(&transcript) {
+            Err(TranscriptError::Diverged(divergence)) => assert_eq!(
+                divergence,
+                Divergence {
+                    line: 5,
+                    expected: "the output \"Too small!\"".to_string(),
+                    found: "the output \"Too big!\"".to_string(),
+                }
+            ),
+            other => panic!("expected a divergence, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn fails_when_the_engine_says_more_or_less() {
+        let mut transcript = record(game(), false, "50\n37\n");
+        transcript.entries.pop();
+        let error = replay(&transcript).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the engine diverges at line 9 of the transcript: expected \
+             the end of the session, but got the output \"You win!\""
+        );
+
+        let mut transcript = record(game(), false, "50\n37\n");
+        transcript.entries.push(Entry {
+            elapsed: Duration::ZERO,
+            line: output("Well done."),
+        });
+        assert!(matches!(
+            replay(&transcript),
+            Err(TranscriptError::Diverged(Divergence { line: 10, .. }))
+        ));
+
+        let mut transcript = record(game(), false, "50\n37\n");
+        transcript.outcome = Some(Outcome::Lost);
+        assert!(matches!(
+            replay(&transcript),
+            Err(TranscriptError::Diverged(Divergence { line: 10, .. }))
+        ));
+    }
+
+    #[test]
+    fn fails_when_the_secret_differs() {
+        let mut transcript = record(game(), false, "50\n37\n");
+        transcript.setup.secret = 38;
+        assert!(matches!(
+            replay(&transcript),
+            Err(TranscriptError::Diverged(Divergence { line: 9, .. }))
+        ));
+    }
+
+    #[test]
+    fn pretends_to_save() {
+        let path = std::env::temp_dir().join(format!(
+            "guessing_game-transcript-save-{}",
+            std::process::id()
+        ));
+        let input = format!("50\nsave {}\nquit\n", path.display());
+        let transcript = record(game(), false, &input);
+        fs::remove_file(&path).unwrap();
+
+        replay(&transcript).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn rejects_broken_transcripts() {
+        let header = Setup::new(&game(), false).to_json().to_string();
+        let cases = [
+            (
+                "",
+                "line 1 of the transcript is corrupt (the file is empty)",
+            ),
+            (
+                "{\"version\":2}",
+                "the transcript is version 2, this game only reads version 1",
+            ),
+            (
+                "{\"version\":1}",
+                "line 1 of the transcript is corrupt (no `max_attempts`)",
+            ),
+            (
+                &format!("{header}\n{{\"in\":\"1\"}}"),
+                "line 2 of the transcript is corrupt (no `ms`)",
+            ),
+            (
+                &format!("{header}\n{{\"ms\":0}}"),
+                "line 2 of the transcript is corrupt (neither `in`, `out` nor `outcome`)",
+            ),
+            (
+                &format!("{header}\n{{\"ms\":0,\"outcome\":\"won\"}}\n{{\"ms\":0,\"in\":\"1\"}}"),
+                "line 3 of the transcript is corrupt (there is more after the outcome)",
+            ),
+        ];
+        for (text, message) in cases {
+            assert_eq!(
+                Transcript::parse(text).unwrap_err().to_string(),
+                message,
+                "{text}"
+            );
+        }
+
+        let unfinished =
+            Transcript::parse(&format!("{header}\n{{\"ms\":0,\"in\":\"1\"}}")).unwrap();
+        assert!(matches!(
+            replay(&unfinished),
+            Err(TranscriptError::Unfinished)
+        ));
+
+        let outside = header.replace("\"secret\":37", "\"secret\":101");
+        let outside = Transcript::parse(&format!("{outside}\n{{\"ms\":0,\"outcome\":\"quit\"}}"));
+        assert!(matches!(
+            replay(&outside.unwrap()),
+            Err(TranscriptError::Invalid(_))
+        ));
+    }
+}
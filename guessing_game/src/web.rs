@@ -0,0 +1,673 @@
+//! Play in a browser: a small HTTP/1.1 server on nothing but `std::net`.
+//!
+//! `GET /` serves a single page, which plays through a JSON API:
+//!
+//! ```text
+//! POST /api/game                 start a new game, replacing any earlier one
+//! GET  /api/game                 the game so far
+//! POST /api/guess  {"guess":50}  guess a number
+//! ```
+//!
+//! Games are written as
+//!
+//! ```text
+//! {"min":1,"max":100,"max_attempts":null,"attempts":1,"attempts_left":null,
+//!  "guesses":[{"guess":50,"result":"too_big","trend":null,"heat":null}],
+//!  "status":"playing","secret":null,"seed":null}
+//! ```
+//!
+//! with guesses described as in a [`crate::machine`] `history` message and
+//! `status` one of `playing`, `won` or `lost`. The secret and the seed stay
+//! null until the game is decided. A guess is answered with
+//! `{"result":"too_big","game":{...}}`, the result named as in a `feedback`
+//! message, and anything that goes wrong with `{"error":"..."}` and a 4xx or
+//! 5xx status.
+//!
+//! Each browser plays its own game, found again through the `session` cookie
+//! the first `POST /api/game` sets. Sessions only live in memory, and the
+//! least recently used one is dropped once there are too many. Every response
+//! closes its connection, which spares the server any keep-alive bookkeeping.
+
+use crate::config::{random_seed, seeded_rng, Config};
+use crate::game::{Feedback, Game};
+use crate::json::Value;
+use crate::machine::{describe_guess, feedback_name, parse_request};
+use crate::session::Action;
+use crate::sha256::to_hex;
+use rand::Rng;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PAGE: &str = include_str!("web/index.html");
+/// The request line and headers together may not be longer than this.
+const MAX_HEAD: u64 = 8 * 1024;
+/// Bodies only ever hold a guess, so anything bigger is a misbehaving client.
+const MAX_BODY: usize = 1024;
+const MAX_SESSIONS: usize = 10_000;
+/// How long a client may take to send its request or read the response.
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait before accepting again after that failed.
+const ACCEPT_RETRY: Duration = Duration::from_millis(10);
+const COOKIE: &str = "session";
+
+/// An HTTP request, read in full.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    /// The request target without its query string.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The first header called `name`, whatever its case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The value of the cookie called `name`.
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.header("cookie")?
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(cookie, _)| *cookie == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Reads one request, or says what was wrong with it in the response to send.
+pub fn read_request<R: BufRead>(mut reader: R) -> Result<Request, Response> {
+    let mut remaining = MAX_HEAD;
+    let mut next_line = || {
+        let mut line = String::new();
+        let read = (&mut reader)
+            .take(remaining)
+            .read_line(&mut line)
+            .map_err(|_| Response::error(400, "the request isn't readable text"))?;
+        remaining -= read as u64;
+        if !line.ends_with('\n') {
+            return Err(match remaining {
+                0 => Response::error(431, "the request headers are too long"),
+                _ => Response::error(400, "the request ended early"),
+            });
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    };
+
+    let request_line = next_line()?;
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(target), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Response::error(400, "malformed request line"));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(Response::error(505, "only HTTP/1.x is supported"));
+    }
+    let path = target.split('?').next().unwrap_or_default().to_string();
+    let method = method.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let line = next_line()?;
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(Response::error(400, "malformed header"));
+        };
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+    if request.header("transfer-encoding").is_some() {
+        return Err(Response::error(501, "chunked bodies aren't supported"));
+    }
+    let length = match request.header("content-length") {
+        None => 0,
+        Some(length) => length
+            .parse()
+            .map_err(|_| Response::error(400, "malformed Content-Length"))?,
+    };
+    if length > MAX_BODY {
+        return Err(Response::error(413, "the body is too long"));
+    }
+
+    request.body = vec![0; length];
+    reader
+        .read_exact(&mut request.body)
+        .map_err(|_| Response::error(400, "the body ended early"))?;
+    Ok(request)
+}
+
+/// An HTTP response, always closing the connection after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+impl Response {
+    fn new(status: u16, content_type: &str, body: String) -> Response {
+        Response {
+            status,
+            headers: vec![("Content-Type", content_type.to_string())],
+            body,
+        }
+    }
+
+    fn json(status: u16, body: Value) -> Response {
+        Response::new(status, "application/json", body.to_string())
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        Response::json(status, Value::object([("error", message.into())]))
+    }
+
+    fn with_header(mut self, name: &'static str, value: String) -> Response {
+        self.headers.push((name, value));
+        self
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\n",
+            self.status,
+            reason(self.status)
+        )?;
+        for (name, value) in &self.headers {
+            write!(writer, "{name}: {value}\r\n")?;
+        }
+        write!(
+            writer,
+            "Content-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        )?;
+        writer.flush()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        501 => "Not Implemented",
+        505 => "HTTP Version Not Supported",
+        _ => "Unknown",
+    }
+}
+
+struct Session {
+    game: Game,
+    last_used: Instant,
+}
+
+/// Every browser's game, by the token in its cookie.
+struct Sessions {
+    games: HashMap<String, Session>,
+    capacity: usize,
+}
+
+impl Sessions {
+    fn new(capacity: usize) -> Sessions {
+        Sessions {
+            games: HashMap::new(),
+            capacity,
+        }
+    }
+
+    fn get(&mut self, token: &str) -> Option<&mut Game> {
+        let session = self.games.get_mut(token)?;
+        session.last_used = Instant::now();
+        Some(&mut session.game)
+    }
+
+    fn insert(&mut self, token: String, game: Game) {
+        if !self.games.contains_key(&token) && self.games.len() >= self.capacity {
+            let oldest = self
+                .games
+                .iter()
+                .min_by_key(|(_, session)| session.last_used)
+                .map(|(token, _)| token.clone());
+            if let Some(oldest) = oldest {
+                self.games.remove(&oldest);
+            }
+        }
+
+        let last_used = Instant::now();
+        self.games.insert(token, Session { game, last_used });
+    }
+}
+
+/// Everything but the sockets: turns requests into responses.
+pub struct App {
+    config: Config,
+    sessions: Mutex<Sessions>,
+}
+
+impl App {
+    /// Starts every new game with the range, attempts and feedback of
+    /// `config`, and with its seed if it has one.
+    pub fn new(config: Config) -> App {
+        App {
+            config,
+            sessions: Mutex::new(Sessions::new(MAX_SESSIONS)),
+        }
+    }
+
+    pub fn handle(&self, request: &Request) -> Response {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/") => Response::new(200, "text/html; charset=utf-8", PAGE.to_string()),
+            ("POST", "/api/game") => self.start(request),
+            ("GET", "/api/game") => match lock(&self.sessions).get(token(request)) {
+                Some(game) => Response::json(200, describe_game(game)),
+                None => no_game(),
+            },
+            ("POST", "/api/guess") => self.guess(request),
+            (_, "/") => not_allowed("GET"),
+            (_, "/api/game") => not_allowed("GET, POST"),
+            (_, "/api/guess") => not_allowed("POST"),
+            _ => Response::error(404, "there is nothing here"),
+        }
+    }
+
+    fn start(&self, request: &Request) -> Response {
+        let seed = self.config.seed().unwrap_or_else(random_seed);
+        let game = Game::from_config(&self.config, &mut seeded_rng(seed)).with_seed(seed);
+        let body = describe_game(&game);
+
+        let mut sessions = lock(&self.sessions);
+        let known = request
+            .cookie(COOKIE)
+            .filter(|token| sessions.games.contains_key(*token));
+        match known {
+            Some(token) => {
+                sessions.insert(token.to_string(), game);
+                Response::json(201, body)
+            }
+            None => {
+                let token = to_hex(&rand::thread_rng().gen::<[u8; 16]>());
+                sessions.insert(token.clone(), game);
+                Response::json(201, body).with_header(
+                    "Set-Cookie",
+                    format!("{COOKIE}={token}; Path=/; HttpOnly; SameSite=Strict"),
+                )
+            }
+        }
+    }
+
+    fn guess(&self, request: &Request) -> Response {
+        let Ok(body) = std::str::from_utf8(&request.body) else {
+            return Response::error(400, "the body isn't UTF-8 text");
+        };
+        let guess = match parse_request(body) {
+            Ok(Action::Guess(guess)) => guess,
+            Ok(_) => return Response::error(400, "only guesses can be posted here"),
+            Err(message) => return Response::error(400, &message),
+        };
+
+        let mut sessions = lock(&self.sessions);
+        let Some(game) = sessions.get(token(request)) else {
+            return no_game();
+        };
+        let feedback = game.guess(guess);
+        let result = match feedback {
+            Feedback::TooSmall | Feedback::TooBig | Feedback::Win => {
+                describe_guess(game, game.attempts() - 1)
+                    .get("result")
+                    .cloned()
+                    .expect("every guess has a result")
+            }
+            _ => feedback_name(feedback).into(),
+        };
+
+        Response::json(
+            200,
+            Value::object([("result", result), ("game", describe_game(game))]),
+        )
+    }
+}
+
+fn token(request: &Request) -> &str {
+    request.cookie(COOKIE).unwrap_or_default()
+}
+
+fn no_game() -> Response {
+    Response::error(404, "no game was started, POST /api/game to start one")
+}
+
+fn not_allowed(allowed: &str) -> Response {
+    Response::error(405, "that method isn't allowed here").with_header("Allow", allowed.to_string())
+}
+
+fn describe_game(game: &Game) -> Value {
+    let status = if game.is_won() {
+        "won"
+    } else if game.is_lost() {
+        "lost"
+    } else {
+        "playing"
+    };
+    let guesses = (0..game.attempts())
+        .map(|index| describe_guess(game, index))
+        .collect();
+    let decided = game.is_over();
+
+    Value::object([
+        ("min", (*game.range().start()).into()),
+        ("max", (*game.range().end()).into()),
+        ("max_attempts", game.max_attempts().into()),
+        ("attempts", game.attempts().into()),
+        ("attempts_left", game.attempts_left().into()),
+        ("guesses", Value::Array(guesses)),
+        ("status", status.into()),
+        ("secret", decided.then(|| game.secret()).into()),
+        ("seed", game.seed().filter(|_| decided).into()),
+    ])
+}
+
+/// Serves the page and the API, one thread per connection.
+pub struct Server {
+    listener: TcpListener,
+    app: Arc<App>,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(address: A, config: Config) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(address)?,
+            app: Arc::new(App::new(config)),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answers requests for as long as the program runs.
+    pub fn run(self) -> ! {
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                // aborted connections and running out of file descriptors pass
+                Err(error) => {
+                    eprintln!("warning: can't accept a connection: {error}");
+                    thread::sleep(ACCEPT_RETRY);
+                    continue;
+                }
+            };
+            let app = Arc::clone(&self.app);
+            // a client that hangs up halfway only ends its own connection
+            thread::spawn(move || serve(&app, stream));
+        }
+    }
+}
+
+fn serve(app: &App, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let response = match read_request(BufReader::new(&stream)) {
+        Ok(request) => app.handle(&request),
+        Err(response) => response,
+    };
+    response.write_to(&stream)
+}
+
+/// A thread panicking mid-request leaves at worst one game half-updated,
+/// which is no reason to stop serving everyone else.
+fn lock(sessions: &Mutex<Sessions>) -> MutexGuard<'_, Sessions> {
+    sessions
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Difficulty;
+
+    fn parse(raw: &str) -> Result<Request, Response> {
+        read_request(raw.as_bytes())
+    }
+
+    /// Sends `raw` as it is and splits the answer into status, head and body.
+    fn send(address: SocketAddr, raw: &str) -> (u16, String, String) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream.write_all(raw.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head[9..12].parse().unwrap();
+        (status, head.to_string(), body.to_string())
+    }
+
+    fn post(address: SocketAddr, path: &str, cookie: &str, body: &str) -> (u16, String, Value) {
+        let (status, head, body) = send(
+            address,
+            &format!(
+                "POST {path} HTTP/1.1\r\nHost: localhost\r\nCookie: {cookie}\r\n\
+                 Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        );
+        (status, head, Value::parse(&body).unwrap())
+    }
+
+    fn serve_on_localhost(config: Config) -> SocketAddr {
+        let server = Server::bind("127.0.0.1:0", config).unwrap();
+        let address = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        address
+    }
+
+    fn session_cookie(head: &str) -> String {
+        let line = head
+            .lines()
+            .find_map(|line| line.strip_prefix("Set-Cookie: "))
+            .expect("a cookie was set");
+        line.split(';').next().unwrap().to_string()
+    }
+
+    #[test]
+    fn reads_a_request() {
+        let request = parse(
+            "POST /api/guess?x=1 HTTP/1.1\r\nhost: localhost\r\n\
+             COOKIE: theme=dark; session=abc\r\nContent-Length: 12\r\n\r\n{\"guess\":50}",
+        )
+        .unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/guess");
+        assert_eq!(request.header("Host"), Some("localhost"));
+        assert_eq!(request.cookie("session"), Some("abc"));
+        assert_eq!(request.cookie("missing"), None);
+        assert_eq!(request.body, b"{\"guess\":50}");
+
+        // bare newlines are accepted too
+        let request = parse("GET / HTTP/1.0\n\n").unwrap();
+        assert_eq!((request.method.as_str(), request.body.len()), ("GET", 0));
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        let status = |raw: &str| parse(raw).unwrap_err().status;
+
+        assert_eq!(status("GET /\r\n\r\n"), 400);
+        assert_eq!(status("GET / HTTP/2\r\n\r\n"), 505);
+        assert_eq!(status("GET / HTTP/1.1\r\nno colon\r\n\r\n"), 400);
+        assert_eq!(status("GET / HTTP/1.1\r\nHost: x\r\n"), 400);
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\n{}"),
+            400
+        );
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nContent-Length: 5000\r\n\r\n"),
+            413
+        );
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"),
+            501
+        );
+
+        let long = format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(9000));
+        assert_eq!(status(&long), 431);
+    }
+
+    #[test]
+    fn writes_a_response() {
+        let mut written = Vec::new();
+        Response::error(404, "gone").write_to(&mut written).unwrap();
+
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\n\
+             Content-Length: 16\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n\
+             {\"error\":\"gone\"}"
+        );
+    }
+
+    #[test]
+    fn serves_the_page() {
+        let address = serve_on_localhost(Difficulty::Easy.config());
+
+        let (status, head, body) = send(address, "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert_eq!(status, 200);
+        assert!(head.contains("Content-Type: text/html"));
+        assert!(body.contains("/api/guess"));
+
+        let (status, _, _) = send(address, "GET /nope HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 404);
+        let (status, head, _) = send(address, "DELETE /api/game HTTP/1.1\r\n\r\n");
+        assert_eq!(status, 405);
+        assert!(head.contains("Allow: GET, POST"));
+    }
+
+    #[test]
+    fn plays_a_game_over_http() {
+        let config = Difficulty::Normal.config().with_seed(7);
+        let secret = Game::from_config(&config, &mut seeded_rng(7)).secret();
+        let address = serve_on_localhost(config);
+
+        let (status, head, game) = post(address, "/api/game", "", "");
+        assert_eq!(status, 201);
+        assert_eq!(game.get("status").and_then(Value::as_str), Some("playing"));
+        assert!(game.get("secret").unwrap().is_null());
+        let cookie = session_cookie(&head);
+
+        let (mut low, mut high) = (1, 100);
+        let won = loop {
+            let guess = (low + high) / 2;
+            let (status, _, answer) = post(
+                address,
+                "/api/guess",
+                &cookie,
+                &format!("{{\"guess\":{guess}}}"),
+            );
+            assert_eq!(status, 200);
+            match answer.get("result").and_then(Value::as_str).unwrap() {
+                "too_small" => low = guess + 1,
+                "too_big" => high = guess - 1,
+                "win" => break answer,
+                other => panic!("unexpected result {other}"),
+            }
+        };
+
+        let game = won.get("game").unwrap();
+        assert_eq!(game.get("status").and_then(Value::as_str), Some("won"));
+        assert_eq!(game.get("secret").and_then(Value::as_u32), Some(secret));
+        assert_eq!(game.get("seed").and_then(Value::as_u64), Some(7));
+
+        // the session remembers the game, and the next guess is too late
+        let (status, _, body) = send(
+            address,
+            &format!("GET /api/game HTTP/1.1\r\nCookie: {cookie}\r\n\r\n"),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(Value::parse(&body).unwrap(), *game);
+        let (_, _, answer) = post(address, "/api/guess", &cookie, "{\"guess\":1}");
+        assert_eq!(
+            answer.get("result").and_then(Value::as_str),
+            Some("game_over")
+        );
+    }
+
+    #[test]
+    fn keeps_sessions_apart() {
+        let address = serve_on_localhost(Difficulty::Easy.config());
+
+        let (_, head, _) = post(address, "/api/game", "", "");
+        let first = session_cookie(&head);
+        let (_, head, _) = post(address, "/api/game", "", "");
+        let second = session_cookie(&head);
+        assert_ne!(first, second);
+
+        post(address, "/api/guess", &first, "{\"guess\":11}");
+        let (_, _, answer) = post(address, "/api/guess", &second, "{\"guess\":3}");
+        let attempts = answer.get("game").and_then(|game| game.get("attempts"));
+        assert_eq!(attempts.and_then(Value::as_u64), Some(1));
+
+        // a new game in a known session keeps the cookie
+        let (status, head, game) = post(address, "/api/game", &first, "");
+        assert_eq!(status, 201);
+        assert!(!head.contains("Set-Cookie"));
+        assert_eq!(game.get("attempts").and_then(Value::as_u64), Some(0));
+    }
+
+    #[test]
+    fn reports_api_errors() {
+        let address = serve_on_localhost(Difficulty::Easy.config());
+
+        let (status, _, answer) = post(address, "/api/guess", "", "{\"guess\":3}");
+        assert_eq!(status, 404);
+        assert!(answer.get("error").is_some());
+
+        let (_, head, _) = post(address, "/api/game", "", "");
+        let cookie = session_cookie(&head);
+        for body in ["", "{\"guess\":-1}", "{\"command\":\"hint\"}", "[3]"] {
+            let (status, _, answer) = post(address, "/api/guess", &cookie, body);
+            assert_eq!(status, 400, "{body}");
+            assert!(answer.get("error").is_some());
+        }
+    }
+
+    #[test]
+    fn drops_the_least_recently_used_session() {
+        let mut sessions = Sessions::new(2);
+        sessions.insert("a".to_string(), Game::new(1, 1..=10));
+        thread::sleep(Duration::from_millis(2));
+        sessions.insert("b".to_string(), Game::new(2, 1..=10));
+        thread::sleep(Duration::from_millis(2));
+        sessions.get("a");
+        sessions.insert("c".to_string(), Game::new(3, 1..=10));
+
+        assert!(sessions.get("a").is_some());
+        assert!(sessions.get("b").is_none());
+        assert!(sessions.get("c").is_some());
+    }
+}
@@ -0,0 +1,155 @@
+//! Drives the real binary over pipes in `--machine` mode.
+
+use guessing_game::json::Value;
+use guessing_game::leaderboard::DATA_DIR_VARIABLE;
+use std::env;
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+struct Bot {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl Bot {
+    fn spawn(args: &[&str]) -> Bot {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_guessing_game"))
+            .arg("--machine")
+            .args(args)
+            // finished games go on the leaderboard, which mustn't be the real one
+            .env(
+                DATA_DIR_VARIABLE,
+                env::temp_dir().join("guessing_game-machine-tests"),
+            )
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("the binary should start");
+
+        Bot {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()).lines(),
+            child,
+        }
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.stdin, "{line}").unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let line = self.stdout.next().expect("a message").unwrap();
+        Value::parse(&line).unwrap_or_else(|error| panic!("{line:?} is not JSON: {error}"))
+    }
+
+    /// Reads the next message and checks its type.
+    fn expect(&mut self, kind: &str) -> Value {
+        let message = self.receive();
+        assert_eq!(
+            message.get("type").and_then(Value::as_str),
+            Some(kind),
+            "{message}"
+        );
+        message
+    }
+
+    fn exit_code(mut self) -> Option<i32> {
+        drop(self.stdin);
+        self.child.wait().unwrap().code()
+    }
+}
+
+fn number(message: &Value, key: &str) -> u32 {
+    message.get(key).and_then(Value::as_u32).unwrap()
+}
+
+#[test]
+fn plays_a_full_game_with_binary_search() {
+    let mut bot = Bot::spawn(&["--min", "1", "--max", "1000", "--seed", "2024"]);
+
+    let start = bot.expect("start");
+    assert_eq!(start.get("seed").and_then(Value::as_u64), Some(2024));
+    let (mut low, mut high) = (number(&start, "min"), number(&start, "max"));
+
+    loop {
+        bot.expect("prompt");
+        let guess = low + (high - low) / 2;
+        bot.send(&format!(r#"{{"guess": {guess}}}"#));
+
+        let feedback = bot.expect("feedback");
+        assert_eq!(number(&feedback, "guess"), guess);
+        match feedback.get("result").and_then(Value::as_str).unwrap() {
+            "too_small" => low = guess + 1,
+            "too_big" => high = guess - 1,
+            "win" => break,
+            other => panic!("unexpected result {other}"),
+        }
+    }
+
+    let result = bot.expect("result");
+    assert_eq!(result.get("outcome").and_then(Value::as_str), Some("won"));
+    assert!(number(&result, "attempts") <= 10);
+    assert_eq!(bot.exit_code(), Some(0));
+}
+
+#[test]
+fn same_seed_gives_the_same_secret() {
+    let secret = || {
+        let mut bot = Bot::spawn(&["-d", "normal", "--seed", "77"]);
+        bot.expect("start");
+        bot.expect("prompt");
+        bot.send(r#"{"command": "giveup"}"#);
+        number(&bot.expect("result"), "secret")
+    };
+
+    assert_eq!(secret(), secret());
+}
+
+#[test]
+fn answers_commands_and_rejects_bad_lines() {
+    let mut bot = Bot::spawn(&["--seed", "5", "--attempts", "3"]);
+    bot.expect("start");
+
+    bot.expect("prompt");
+    bot.send("Please input your guess!");
+    assert!(bot
+        .expect("error")
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap()
+        .contains("JSON"));
+
+    bot.expect("prompt");
+    bot.send(r#"{"guess": 0}"#);
+    assert_eq!(
+        bot.expect("feedback").get("result").and_then(Value::as_str),
+        Some("out_of_range")
+    );
+
+    let prompt = bot.expect("prompt");
+    assert_eq!(number(&prompt, "attempts_left"), 3);
+    bot.send(r#"{"command": "hint"}"#);
+    let hint = bot.expect("hint");
+    assert_eq!((number(&hint, "min"), number(&hint, "max")), (1, 10));
+
+    bot.expect("prompt");
+    bot.send(r#"{"command": "quit"}"#);
+    assert!(bot.expect("result").get("secret").unwrap().is_null());
+    assert_eq!(bot.exit_code(), Some(0));
+}
+
+#[test]
+fn end_of_input_ends_the_game_with_a_failure() {
+    let mut bot = Bot::spawn(&[]);
+    bot.expect("start");
+    bot.expect("prompt");
+    drop(bot.stdin);
+
+    let result = Value::parse(&bot.stdout.next().unwrap().unwrap()).unwrap();
+    assert_eq!(
+        result.get("outcome").and_then(Value::as_str),
+        Some("end_of_input")
+    );
+    assert_eq!(bot.child.wait().unwrap().code(), Some(1));
+}
@@ -0,0 +1,33 @@
+//! Replays every transcript in `tests/transcripts`, recorded with `--record`,
+//! against the engine: a change to what the game says shows up here.
+//!
+//! When a change is meant to alter the wording, record the affected sessions
+//! again rather than editing the files by hand.
+
+use guessing_game::transcript::{self, Transcript};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn golden_transcripts_replay() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/transcripts");
+    let mut replayed = 0;
+
+    for entry in fs::read_dir(&directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path
+            .extension()
+            .is_none_or(|extension| extension != "jsonl")
+        {
+            continue;
+        }
+        let transcript = Transcript::load(&path)
+            .unwrap_or_else(|error| panic!("can't read {}: {error}", path.display()));
+        if let Err(error) = transcript::replay(&transcript) {
+            panic!("{} doesn't replay: {error}", path.display());
+        }
+        replayed += 1;
+    }
+
+    assert!(replayed >= 5, "only {replayed} transcripts were found");
+}